@@ -0,0 +1,290 @@
+//! Level measurements for interleaved sample buffers.
+
+use crate::fft;
+use crate::math;
+use std::f64::consts::PI;
+
+// Length and hop of the loudness measurement blocks (400 ms with 75% overlap).
+const LOUDNESS_BLOCK_SECONDS: f64 = 0.4;
+const LOUDNESS_HOP_SECONDS: f64 = 0.1;
+// Blocks quieter than this never count towards integrated loudness.
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+// Blocks this far below the ungated loudness are dropped as well.
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+// Length and hop of the spectral distance frames.
+const SPECTRUM_FRAME: usize = 2048;
+const SPECTRUM_HOP: usize = 1024;
+// Spectral components are compared down to this level (relative to the peak bin of a
+// full-scale sine), so near-silent bins don't dominate the distance.
+const SPECTRUM_FLOOR_DB: f64 = -100.0;
+
+// Oversampling factor and filter length (per phase) of the true-peak meter.
+const TRUE_PEAK_OVERSAMPLING: usize = 4;
+const TRUE_PEAK_TAPS: usize = 12;
+
+/// Peak and loudness of a buffer, as reported before and after processing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Levels {
+    pub peak_dbfs: f32,
+    /// Integrated loudness (see [`loudness`]).
+    pub loudness_lufs: f32,
+}
+
+impl Levels {
+    /// Measures an interleaved stereo buffer.
+    pub fn measure(samples: &[f32], sample_rate: u32) -> Self {
+        Self {
+            peak_dbfs: to_dbfs(peak(samples)),
+            loudness_lufs: loudness(samples, sample_rate),
+        }
+    }
+}
+
+/// Returns the highest absolute sample value.
+pub fn peak(samples: &[f32]) -> f32 {
+    samples.iter().map(|s| s.abs()).fold(0.0, f32::max)
+}
+
+/// Returns the highest absolute value of the reconstructed (analog) signal, estimated by
+/// oversampling each channel 4x as described in ITU-R BS.1770.
+///
+/// Inter-sample peaks make this at least as high as [`peak`], and higher for content with
+/// energy near Nyquist.
+pub fn true_peak(samples: &[f32]) -> f32 {
+    let phases = true_peak_filter();
+    let half = TRUE_PEAK_TAPS / 2;
+    let mut max = peak(samples);
+
+    for channel in 0..2 {
+        // Zero padding lets every output position use a full window of input samples
+        let mut signal = vec![0.0; half - 1];
+        signal.extend(samples.iter().skip(channel).step_by(2));
+        signal.extend(std::iter::repeat_n(0.0, half));
+
+        for window in signal.windows(TRUE_PEAK_TAPS) {
+            for phase in &phases {
+                let value: f32 = window.iter().zip(phase).map(|(s, c)| s * c).sum();
+                max = max.max(value.abs());
+            }
+        }
+    }
+    max
+}
+
+// Windowed-sinc interpolation filter, split into one set of taps per fractional position.
+fn true_peak_filter() -> Vec<[f32; TRUE_PEAK_TAPS]> {
+    let half = TRUE_PEAK_TAPS as f64 / 2.0;
+    (1..TRUE_PEAK_OVERSAMPLING)
+        .map(|phase| {
+            let fraction = phase as f64 / TRUE_PEAK_OVERSAMPLING as f64;
+            let mut taps = [0.0; TRUE_PEAK_TAPS];
+            for (k, tap) in taps.iter_mut().enumerate() {
+                // Distance from the interpolated point to input sample k
+                let x = k as f64 - (half - 1.0) - fraction;
+                let sinc = if x == 0.0 {
+                    1.0
+                } else {
+                    math::sin_f64(PI * x) / (PI * x)
+                };
+                let window = 0.5 + 0.5 * math::cos_f64(PI * x / half);
+                *tap = (sinc * window) as f32;
+            }
+            taps
+        })
+        .collect()
+}
+
+/// Returns the Root Mean Square level (the signal's energy) of all samples.
+pub fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+
+    let sum: f64 = samples.iter().map(|&s| s as f64 * s as f64).sum();
+    (sum / samples.len() as f64).sqrt() as f32
+}
+
+/// Converts a linear amplitude into decibels relative to full scale.
+/// Silence maps to negative infinity.
+pub fn to_dbfs(amplitude: f32) -> f32 {
+    20.0 * math::log10(amplitude)
+}
+
+/// Converts decibels relative to full scale into a linear amplitude.
+pub fn from_dbfs(db: f32) -> f32 {
+    math::powf(10.0, db / 20.0)
+}
+
+/// Returns the integrated loudness of an interleaved stereo buffer in LUFS, as specified
+/// by ITU-R BS.1770 (K-weighting, 400 ms blocks, absolute and relative gating).
+///
+/// Silence, and buffers shorter than one measurement block, map to negative infinity.
+pub fn loudness(samples: &[f32], sample_rate: u32) -> f32 {
+    let rate = sample_rate as f64;
+    let block = (LOUDNESS_BLOCK_SECONDS * rate) as usize;
+    let hop = (LOUDNESS_HOP_SECONDS * rate) as usize;
+    let frames = samples.len() / 2;
+    if block == 0 || hop == 0 || frames < block {
+        return f32::NEG_INFINITY;
+    }
+
+    // Running sum of the K-weighted energy of both channels, so each block is O(1)
+    let mut left = KWeighting::new(rate);
+    let mut right = KWeighting::new(rate);
+    let mut energy = Vec::with_capacity(frames + 1);
+    energy.push(0.0);
+    let mut total = 0.0;
+    for frame in samples.chunks_exact(2) {
+        let l = left.process(frame[0] as f64);
+        let r = right.process(frame[1] as f64);
+        total += l * l + r * r;
+        energy.push(total);
+    }
+
+    let blocks: Vec<f64> = (0..=frames - block)
+        .step_by(hop)
+        .map(|start| (energy[start + block] - energy[start]) / block as f64)
+        .collect();
+
+    let gated_loudness = |threshold: f64| {
+        let gated: Vec<f64> = blocks
+            .iter()
+            .copied()
+            .filter(|&power| block_loudness(power) > threshold)
+            .collect();
+        (!gated.is_empty()).then(|| block_loudness(gated.iter().sum::<f64>() / gated.len() as f64))
+    };
+
+    let Some(ungated) = gated_loudness(ABSOLUTE_GATE_LUFS) else {
+        return f32::NEG_INFINITY;
+    };
+    let threshold = ungated + RELATIVE_GATE_LU;
+    gated_loudness(threshold.max(ABSOLUTE_GATE_LUFS)).map_or(f32::NEG_INFINITY, |l| l as f32)
+}
+
+/// Returns the log-spectral distance between two interleaved stereo buffers, in dB: the
+/// RMS difference of their power spectra on a decibel scale, averaged over 2048-sample
+/// frames of each channel. Identical buffers measure 0; a render whose treble is gone
+/// or buried in noise measures tens of dB from its input.
+///
+/// Only the frames both buffers cover are compared, and frames silent in both are
+/// skipped. Gain alone counts as distance, so match loudness first to compare tone.
+pub fn spectral_distance(a: &[f32], b: &[f32]) -> f32 {
+    let window = fft::hann(SPECTRUM_FRAME);
+    let frames = a.len().min(b.len()) / 2;
+    let floor = math::powf_f64(10.0, SPECTRUM_FLOOR_DB / 10.0);
+    // A full-scale sine peaks at a quarter of the frame length under the Hann window
+    let scale = 1.0 / (SPECTRUM_FRAME as f64 / 4.0).powi(2);
+    let spectrum = |samples: &[f32], start: usize, channel: usize| {
+        let (mut re, mut im) = (vec![0.0; SPECTRUM_FRAME], vec![0.0; SPECTRUM_FRAME]);
+        for (i, re) in re.iter_mut().enumerate() {
+            let sample = samples
+                .get((start + i) * 2 + channel)
+                .filter(|_| start + i < frames);
+            *re = sample.copied().unwrap_or(0.0) * window[i];
+        }
+        fft::forward(&mut re, &mut im);
+        (0..=SPECTRUM_FRAME / 2)
+            .map(|k| ((re[k] * re[k] + im[k] * im[k]) as f64 * scale).max(floor))
+            .collect::<Vec<f64>>()
+    };
+
+    let (mut total, mut counted) = (0.0, 0);
+    for channel in 0..2 {
+        let mut start = 0;
+        // Short buffers still get one (zero-padded) frame
+        while start < frames.max(1) {
+            let (p, q) = (spectrum(a, start, channel), spectrum(b, start, channel));
+            if p.iter().chain(&q).any(|&power| power > floor) {
+                let squares: f64 = p
+                    .iter()
+                    .zip(&q)
+                    .map(|(p, q)| (10.0 * math::log10_f64(p / q)).powi(2))
+                    .sum();
+                total += (squares / p.len() as f64).sqrt();
+                counted += 1;
+            }
+            start += SPECTRUM_HOP;
+        }
+    }
+    match counted {
+        0 => 0.0,
+        n => (total / n as f64) as f32,
+    }
+}
+
+// Loudness of a block from its mean K-weighted energy summed over channels.
+fn block_loudness(power: f64) -> f64 {
+    -0.691 + 10.0 * math::log10_f64(power)
+}
+
+// The BS.1770 pre-filter: a high shelf modeling the head, followed by a high-pass
+// (the "RLB" curve). Coefficients are derived for any sample rate.
+struct KWeighting {
+    shelf: Biquad,
+    high_pass: Biquad,
+}
+
+impl KWeighting {
+    fn new(sample_rate: f64) -> Self {
+        // High shelf: +4 dB above ~1.7 kHz
+        let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+        let k = math::tan_f64(PI * f0 / sample_rate);
+        let vh = math::powf_f64(10.0, gain_db / 20.0);
+        let vb = math::powf_f64(vh, 0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Biquad::new(
+            [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        );
+
+        // High-pass at ~38 Hz
+        let (f0, q) = (38.13547087602444, 0.5003270373238773);
+        let k = math::tan_f64(PI * f0 / sample_rate);
+        let a0 = 1.0 + k / q + k * k;
+        let high_pass = Biquad::new(
+            [1.0, -2.0, 1.0],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        );
+
+        Self { shelf, high_pass }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        self.high_pass.process(self.shelf.process(x))
+    }
+}
+
+// Direct form I second-order section with normalized coefficients.
+pub(crate) struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    pub(crate) fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self {
+            b,
+            a,
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    pub(crate) fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
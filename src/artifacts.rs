@@ -0,0 +1,269 @@
+//! Discrete defects of the medium: pops, clicks, needle skips and tape splices.
+//!
+//! Continuous noise can't recreate the few big pops on every side of a record. The
+//! [`Artifacts`] of a config place such events at fixed times, scatter a number of them
+//! at random, or both. Random placement is seeded, and the resulting timeline can be
+//! exported and loaded back as fixed events.
+
+use crate::math;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+// One revolution of a 33 1/3 rpm record, the distance a needle skips back.
+const REVOLUTION_SECS: f64 = 1.8;
+
+// Length of the fades around a needle skip, in seconds.
+const SKIP_FADE_SECS: f64 = 0.005;
+
+// Length of the level dip at a tape splice, in seconds.
+const SPLICE_SECS: f64 = 0.04;
+
+/// The kind of an [`ArtifactEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum ArtifactKind {
+    Pop,        // A dull thump with a sharp edge, like dirt in the groove.
+    Click,      // A short tick, like a scratch.
+    NeedleSkip, // The needle falls back a groove and replays the last revolution.
+    TapeSplice, // A tick and a brief drop in level where two pieces of tape were joined.
+}
+
+impl ArtifactKind {
+    /// Every artifact kind.
+    pub const ALL: [ArtifactKind; 4] = [
+        ArtifactKind::Pop,
+        ArtifactKind::Click,
+        ArtifactKind::NeedleSkip,
+        ArtifactKind::TapeSplice,
+    ];
+
+    /// Returns the name used on the command line and in config files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArtifactKind::Pop => "pop",
+            ArtifactKind::Click => "click",
+            ArtifactKind::NeedleSkip => "needle-skip",
+            ArtifactKind::TapeSplice => "tape-splice",
+        }
+    }
+
+    pub fn try_from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown artifact: {} (use pop, click, needle-skip or tape-splice)",
+                    s
+                )
+            })
+    }
+}
+
+/// One artifact at a point in the render.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArtifactEvent {
+    pub kind: ArtifactKind,
+    /// Position from the start of the render, in seconds.
+    pub time_secs: f64,
+    /// Peak level of the tick or thump (linear, 0.0 to 1.0). For a tape splice, also
+    /// how deep the level dips.
+    pub amplitude: f32,
+}
+
+impl ArtifactEvent {
+    pub fn new(kind: ArtifactKind, time_secs: f64, amplitude: f32) -> Self {
+        Self {
+            kind,
+            time_secs,
+            amplitude,
+        }
+    }
+
+    /// Renders the event into interleaved stereo `samples`. Events past the end are
+    /// left out.
+    pub fn apply(&self, samples: &mut [f32], sample_rate: u32) {
+        let rate = sample_rate.max(1) as f64;
+        let frame = (self.time_secs.max(0.0) * rate).round() as usize;
+        if frame >= samples.len() / 2 {
+            return;
+        }
+
+        match self.kind {
+            ArtifactKind::Pop => {
+                // The thump and the edge peak together
+                ring(samples, frame, rate, 120.0, 6.0, self.amplitude * 0.6);
+                ring(samples, frame, rate, 2500.0, 0.6, self.amplitude * 0.4);
+            }
+            ArtifactKind::Click => ring(samples, frame, rate, 4000.0, 0.3, self.amplitude),
+            ArtifactKind::NeedleSkip => {
+                skip_back(samples, frame, rate);
+                ring(samples, frame, rate, 3000.0, 0.8, self.amplitude);
+            }
+            ArtifactKind::TapeSplice => {
+                dip(samples, frame, rate, self.amplitude * 0.8);
+                ring(samples, frame, rate, 5000.0, 0.2, self.amplitude);
+            }
+        }
+    }
+}
+
+/// A number of artifacts of one kind scattered over the render at random.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RandomArtifacts {
+    pub kind: ArtifactKind,
+    pub count: u32,
+    /// Smallest amplitude drawn. [default: 0.2]
+    #[cfg_attr(feature = "serde", serde(default = "RandomArtifacts::default_min"))]
+    pub min_amplitude: f32,
+    /// Largest amplitude drawn. [default: 0.6]
+    #[cfg_attr(feature = "serde", serde(default = "RandomArtifacts::default_max"))]
+    pub max_amplitude: f32,
+}
+
+impl RandomArtifacts {
+    pub fn new(kind: ArtifactKind, count: u32) -> Self {
+        Self {
+            kind,
+            count,
+            min_amplitude: Self::default_min(),
+            max_amplitude: Self::default_max(),
+        }
+    }
+
+    fn default_min() -> f32 {
+        0.2
+    }
+
+    fn default_max() -> f32 {
+        0.6
+    }
+}
+
+/// The artifacts of a render: events at fixed times and events placed at random.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Artifacts {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub events: Vec<ArtifactEvent>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub random: Vec<RandomArtifacts>,
+}
+
+impl Artifacts {
+    /// Fixed events only, e.g. a timeline from [`schedule`](Self::schedule).
+    pub fn fixed(events: Vec<ArtifactEvent>) -> Self {
+        Self {
+            events,
+            random: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if there are no artifacts at all.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty() && self.random.iter().all(|random| random.count == 0)
+    }
+
+    /// Places the random artifacts over `duration_secs` and returns them with the fixed
+    /// ones, sorted by time. A render with seed `seed` uses exactly this timeline.
+    pub fn schedule(&self, duration_secs: f64, seed: u64) -> Vec<ArtifactEvent> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut events = self.events.clone();
+
+        for random in &self.random {
+            let (low, high) = (
+                random.min_amplitude.min(random.max_amplitude),
+                random.min_amplitude.max(random.max_amplitude),
+            );
+            for _ in 0..random.count {
+                let time = duration_secs.max(0.0) * rng.random::<f64>();
+                let amplitude = low + (high - low) * rng.random::<f32>();
+                events.push(ArtifactEvent::new(random.kind, time, amplitude));
+            }
+        }
+
+        events.sort_by(|a, b| a.time_secs.total_cmp(&b.time_secs));
+        events
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Artifacts {
+    /// Loads artifacts from a `.toml` or `.json` file, chosen by extension.
+    pub fn load(path: impl AsRef<Path>) -> crate::error::Result<Self> {
+        crate::config::read_file(path.as_ref())
+    }
+
+    /// Writes the artifacts to a `.toml` or `.json` file, chosen by extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> crate::error::Result<()> {
+        crate::config::write_file(path.as_ref(), self)
+    }
+}
+
+// Adds a decaying sine burst at `frame` to both channels, starting at its peak.
+fn ring(samples: &mut [f32], frame: usize, rate: f64, hz: f64, decay_ms: f64, amplitude: f32) {
+    let decay = decay_ms / 1000.0 * rate;
+    // Five time constants take it below 1% of the peak
+    let length = (5.0 * decay).ceil() as usize;
+    let end = (frame + length).min(samples.len() / 2);
+
+    for i in frame..end {
+        let t = (i - frame) as f64;
+        let phase = std::f64::consts::TAU * hz * t / rate;
+        let value = amplitude * math::exp((-t / decay) as f32) * math::cos(phase as f32);
+        for sample in &mut samples[i * 2..i * 2 + 2] {
+            *sample += value;
+        }
+    }
+}
+
+// Replaces the revolution after `frame` with the one before it, fading over at both
+// seams. Near the start, whatever precedes `frame` is replayed.
+fn skip_back(samples: &mut [f32], frame: usize, rate: f64) {
+    let frames = samples.len() / 2;
+    let revolution = ((REVOLUTION_SECS * rate) as usize).min(frame);
+    let end = (frame + revolution).min(frames);
+    let fade = ((SKIP_FADE_SECS * rate) as usize).max(1);
+    if revolution == 0 {
+        return;
+    }
+
+    let replay = samples[(frame - revolution) * 2..(end - revolution) * 2].to_vec();
+    for (i, pair) in replay.chunks_exact(2).enumerate() {
+        let out = frame + i;
+        // Fade into the replay at the skip and back out where the original resumes
+        let weight = ((i + 1) as f32 / fade as f32)
+            .min((end - out) as f32 / fade as f32)
+            .min(1.0);
+        for (sample, replayed) in samples[out * 2..out * 2 + 2].iter_mut().zip(pair) {
+            *sample += (replayed - *sample) * weight;
+        }
+    }
+}
+
+// Briefly lowers the level after `frame` by up to `depth` with a raised-cosine dip.
+fn dip(samples: &mut [f32], frame: usize, rate: f64, depth: f32) {
+    let length = (SPLICE_SECS * rate).max(1.0) as usize;
+    let end = (frame + length).min(samples.len() / 2);
+
+    for i in frame..end {
+        let position = (i - frame) as f32 / length as f32;
+        let shape = (1.0 - math::cos(position * std::f32::consts::TAU)) / 2.0;
+        let gain = 1.0 - depth.clamp(0.0, 1.0) * shape;
+        for sample in &mut samples[i * 2..i * 2 + 2] {
+            *sample *= gain;
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! Time-varying parameters.
+//!
+//! An [`Automation`] is a list of breakpoints (time, value) that is interpolated into a
+//! curve. The pipeline evaluates it once per control block of
+//! [`CONTROL_BLOCK_FRAMES`] frames, so parameters follow the curve closely without
+//! being recomputed for every sample. The engine ramps from one block's value to the
+//! next (see [`Oxidizer::set_smoothing`](crate::Oxidizer::set_smoothing)), so the
+//! steps don't click.
+
+use crate::error::{OxidizerError, Result};
+use crate::math;
+
+/// Number of frames processed with the same automated parameter values.
+pub const CONTROL_BLOCK_FRAMES: usize = 256;
+
+/// A processing parameter that can be automated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum AutomationTarget {
+    Cutoff,    // Low-pass cutoff in Hz, replacing the level's fixed coefficient.
+    Intensity, // Noise and saturation intensity.
+    Mix,       // Dry/wet balance.
+}
+
+impl AutomationTarget {
+    /// Every automatable parameter.
+    pub const ALL: [AutomationTarget; 3] = [
+        AutomationTarget::Cutoff,
+        AutomationTarget::Intensity,
+        AutomationTarget::Mix,
+    ];
+
+    /// Returns the name used on the command line and in config files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AutomationTarget::Cutoff => "cutoff",
+            AutomationTarget::Intensity => "intensity",
+            AutomationTarget::Mix => "mix",
+        }
+    }
+
+    pub fn try_from_str(s: &str) -> std::result::Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|target| target.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown automation target: {} (use cutoff, intensity or mix)",
+                    s
+                )
+            })
+    }
+}
+
+/// How values are interpolated between two breakpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Interpolation {
+    Step, // Hold each value until the next breakpoint.
+    #[default]
+    Linear, // Straight ramps.
+    Smooth, // Cosine ease in and out, without corners at the breakpoints.
+}
+
+/// What breakpoint times are measured in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Timing {
+    #[default]
+    Seconds, // Seconds from the start of the render.
+    Fraction, // Fractions of the render length, from 0.0 to 1.0.
+}
+
+/// A parameter curve defined by breakpoints.
+///
+/// Before the first breakpoint the curve holds its first value, and after the last one
+/// its last value.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "AutomationSpec", into = "AutomationSpec")
+)]
+pub struct Automation {
+    points: Vec<(f32, f32)>,
+    interpolation: Interpolation,
+    timing: Timing,
+}
+
+impl Automation {
+    /// Builds a curve from `(time, value)` breakpoints, which are sorted by time.
+    ///
+    /// # Errors
+    /// Fails if there are no breakpoints, any time or value isn't finite, a time is
+    /// negative, or a fractional time lies outside 0.0 to 1.0.
+    pub fn new(
+        mut points: Vec<(f32, f32)>,
+        interpolation: Interpolation,
+        timing: Timing,
+    ) -> Result<Self> {
+        if points.is_empty() {
+            return Err(invalid("an automation needs at least one breakpoint"));
+        }
+        for &(time, value) in &points {
+            if !time.is_finite() || !value.is_finite() {
+                return Err(invalid("automation breakpoints must be finite numbers"));
+            }
+            if time < 0.0 || (timing == Timing::Fraction && time > 1.0) {
+                return Err(invalid(&format!(
+                    "automation time {} is outside the render",
+                    time
+                )));
+            }
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Ok(Self {
+            points,
+            interpolation,
+            timing,
+        })
+    }
+
+    /// A linear ramp from `from` at the start of the render to `to` at its end.
+    pub fn ramp(from: f32, to: f32) -> Result<Self> {
+        Self::new(
+            vec![(0.0, from), (1.0, to)],
+            Interpolation::Linear,
+            Timing::Fraction,
+        )
+    }
+
+    /// The breakpoints, sorted by time.
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points
+    }
+
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    pub fn timing(&self) -> Timing {
+        self.timing
+    }
+
+    /// Returns the value at `seconds` into a render that lasts `length` seconds.
+    pub fn value_at(&self, seconds: f32, length: f32) -> f32 {
+        let time = match self.timing {
+            Timing::Seconds => seconds,
+            Timing::Fraction if length > 0.0 => seconds / length,
+            Timing::Fraction => 0.0,
+        };
+
+        // Index of the first breakpoint after `time`
+        let next = self.points.partition_point(|&(t, _)| t <= time);
+        if next == 0 {
+            return self.points[0].1;
+        }
+        if next == self.points.len() {
+            return self.points[next - 1].1;
+        }
+
+        let (t0, v0) = self.points[next - 1];
+        let (t1, v1) = self.points[next];
+        let position = (time - t0) / (t1 - t0);
+        let weight = match self.interpolation {
+            Interpolation::Step => 0.0,
+            Interpolation::Linear => position,
+            Interpolation::Smooth => (1.0 - math::cos(position * std::f32::consts::PI)) / 2.0,
+        };
+        v0 + (v1 - v0) * weight
+    }
+}
+
+/// The automations applied to a render, one optional curve per parameter.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Automations {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub cutoff: Option<Automation>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub intensity: Option<Automation>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub mix: Option<Automation>,
+}
+
+impl Automations {
+    /// Returns `true` if no parameter is automated.
+    pub fn is_empty(&self) -> bool {
+        self.cutoff.is_none() && self.intensity.is_none() && self.mix.is_none()
+    }
+
+    pub fn get(&self, target: AutomationTarget) -> Option<&Automation> {
+        match target {
+            AutomationTarget::Cutoff => self.cutoff.as_ref(),
+            AutomationTarget::Intensity => self.intensity.as_ref(),
+            AutomationTarget::Mix => self.mix.as_ref(),
+        }
+    }
+
+    /// Automates `target`, replacing any curve it already had.
+    pub fn set(&mut self, target: AutomationTarget, automation: Automation) {
+        let slot = match target {
+            AutomationTarget::Cutoff => &mut self.cutoff,
+            AutomationTarget::Intensity => &mut self.intensity,
+            AutomationTarget::Mix => &mut self.mix,
+        };
+        *slot = Some(automation);
+    }
+}
+
+fn invalid(message: &str) -> OxidizerError {
+    OxidizerError::InvalidValue(format!("Invalid automation: {}", message))
+}
+
+// The serialized form of an `Automation`, validated when deserializing.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct AutomationSpec {
+    #[serde(default)]
+    timing: Timing,
+    #[serde(default)]
+    interpolation: Interpolation,
+    points: Vec<(f32, f32)>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<AutomationSpec> for Automation {
+    type Error = OxidizerError;
+
+    fn try_from(spec: AutomationSpec) -> Result<Self> {
+        Automation::new(spec.points, spec.interpolation, spec.timing)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Automation> for AutomationSpec {
+    fn from(automation: Automation) -> Self {
+        Self {
+            timing: automation.timing,
+            interpolation: automation.interpolation,
+            points: automation.points,
+        }
+    }
+}
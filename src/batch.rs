@@ -0,0 +1,291 @@
+//! Helpers for processing many files in one go.
+//!
+//! An input specification can be a single file, a directory (every audio file in it,
+//! non-recursively) or a glob pattern such as `album/*.mp3`. Output names are produced
+//! from a template in which `{stem}`, `{name}` and `{ext}` are replaced by the input's
+//! file stem, full file name and extension.
+//!
+//! A [`Manifest`] in the output directory records the inputs a batch has finished, so an
+//! interrupted run can be resumed where it stopped.
+
+use crate::config::OxidizerConfig;
+use crate::error::{OxidizerError, Result};
+use crate::io::{self, AudioFormat, TimeRange};
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// File extensions picked up when an input directory is scanned.
+pub const AUDIO_EXTENSIONS: &[&str] = &["mp3", "wav", "flac", "ogg", "m4a", "mp4", "mkv"];
+
+/// Output file name template used when only an output directory is given.
+pub const DEFAULT_TEMPLATE: &str = "{stem}_oxidized.wav";
+
+/// Returns the [`DEFAULT_TEMPLATE`] with the extension of `format`.
+pub fn default_template(format: AudioFormat) -> String {
+    format!("{{stem}}_oxidized.{}", format.extension())
+}
+
+/// What to do when an output file already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverwritePolicy {
+    #[default]
+    Refuse, // Fail with `OxidizerError::OutputExists`.
+    Overwrite, // Replace the existing file.
+    Rename,    // Pick a free name by appending `_1`, `_2`, ... to the file stem.
+}
+
+/// Returns `true` if `input` names more than a single file (a directory or a glob pattern).
+pub fn is_batch(input: &str) -> bool {
+    is_glob(input) || Path::new(input).is_dir()
+}
+
+/// Expands an input specification into the list of files to process, sorted by path.
+///
+/// # Errors
+/// Returns an error if the glob pattern is malformed or nothing matches.
+pub fn collect_inputs(input: &str) -> Result<Vec<PathBuf>> {
+    let path = Path::new(input);
+
+    let mut inputs = if is_glob(input) {
+        glob::glob(input)
+            .map_err(|e| OxidizerError::InvalidValue(format!("Invalid pattern {}: {}", input, e)))?
+            .filter_map(|entry| entry.ok())
+            .filter(|path| path.is_file())
+            .collect()
+    } else if path.is_dir() {
+        scan_dir(path)?
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    if inputs.is_empty() {
+        return Err(OxidizerError::InvalidValue(format!(
+            "No audio files found for {}",
+            input
+        )));
+    }
+
+    inputs.sort();
+    Ok(inputs)
+}
+
+/// Lists the audio files directly inside `dir` (non-recursively), in no particular order.
+pub fn scan_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    Ok(std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && is_audio_file(path))
+        .collect())
+}
+
+/// Splits an output specification into a directory and a file name template.
+///
+/// A specification containing a placeholder (e.g. `out/{stem}_lofi.wav`) is a path
+/// template; anything else is a directory that gets the [`DEFAULT_TEMPLATE`].
+pub fn split_output_spec(output: &str) -> (PathBuf, String) {
+    let path = Path::new(output);
+
+    if output.contains('{') {
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let template = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| DEFAULT_TEMPLATE.to_string());
+        (dir, template)
+    } else {
+        (path.to_path_buf(), DEFAULT_TEMPLATE.to_string())
+    }
+}
+
+/// Builds the output path for `input` inside `output_dir` by filling in `template`.
+pub fn output_path(input: &Path, output_dir: &Path, template: &str) -> PathBuf {
+    let part = |s: Option<&std::ffi::OsStr>| {
+        s.map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+
+    let name = template
+        .replace("{stem}", &part(input.file_stem()))
+        .replace("{name}", &part(input.file_name()))
+        .replace("{ext}", &part(input.extension()));
+
+    output_dir.join(name)
+}
+
+/// Applies `policy` to a single output path.
+///
+/// Paths in `taken` count as existing, so outputs planned for other inputs of the same
+/// batch aren't reused.
+///
+/// # Errors
+/// Returns [`OxidizerError::OutputExists`] if the path is taken and the policy is
+/// [`OverwritePolicy::Refuse`].
+pub fn resolve_output(
+    path: &Path,
+    policy: OverwritePolicy,
+    taken: &HashSet<PathBuf>,
+) -> Result<PathBuf> {
+    let is_taken = |path: &Path| path.exists() || taken.contains(path);
+    if !is_taken(path) {
+        return Ok(path.to_path_buf());
+    }
+
+    match policy {
+        OverwritePolicy::Refuse => Err(OxidizerError::OutputExists(path.to_path_buf())),
+        OverwritePolicy::Overwrite => Ok(path.to_path_buf()),
+        OverwritePolicy::Rename => {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let extension = path
+                .extension()
+                .map(|e| format!(".{}", e.to_string_lossy()))
+                .unwrap_or_default();
+
+            Ok((1..)
+                .map(|n| path.with_file_name(format!("{}_{}{}", stem, n, extension)))
+                .find(|candidate| !is_taken(candidate))
+                .expect("some numbered name is free"))
+        }
+    }
+}
+
+/// Builds the output path of every input, applying `policy` to existing files and to
+/// inputs that would end up with the same output.
+///
+/// # Errors
+/// Fails on the first conflict when the policy is [`OverwritePolicy::Refuse`].
+pub fn plan_outputs(
+    inputs: &[PathBuf],
+    output_dir: &Path,
+    template: &str,
+    policy: OverwritePolicy,
+) -> Result<Vec<PathBuf>> {
+    plan_outputs_with(inputs, output_dir, template, |_| policy)
+}
+
+/// Like [`plan_outputs`], with the policy picked for each input.
+pub fn plan_outputs_with(
+    inputs: &[PathBuf],
+    output_dir: &Path,
+    template: &str,
+    policy: impl Fn(&Path) -> OverwritePolicy,
+) -> Result<Vec<PathBuf>> {
+    let mut taken = HashSet::new();
+    inputs
+        .iter()
+        .map(|input| {
+            let output = output_path(input, output_dir, template);
+            // Even with Overwrite, two inputs of one batch must not share an output
+            let policy = match policy(input) {
+                OverwritePolicy::Overwrite if taken.contains(&output) => OverwritePolicy::Rename,
+                policy => policy,
+            };
+            let output = resolve_output(&output, policy, &taken)?;
+            taken.insert(output.clone());
+            Ok(output)
+        })
+        .collect()
+}
+
+/// Name of the [`Manifest`] file inside a batch's output directory.
+pub const MANIFEST_NAME: &str = ".oxidizer-state.json";
+
+/// The inputs of a batch that were finished, with the settings they were rendered with.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Manifest {
+    /// Finished inputs by their canonical path.
+    pub completed: BTreeMap<PathBuf, Completed>,
+}
+
+/// One finished input of a [`Manifest`].
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Completed {
+    pub output: PathBuf,
+    /// [`run_hash`] of the settings the output was rendered with.
+    pub config_hash: String,
+}
+
+#[cfg(feature = "serde")]
+impl Manifest {
+    /// Loads the manifest of `output_dir`, or an empty one if there is none yet.
+    pub fn load(output_dir: &Path) -> Result<Self> {
+        let path = output_dir.join(MANIFEST_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        crate::config::read_file(&path)
+    }
+
+    /// Writes the manifest to `output_dir`. The file is replaced in one step, so a crash
+    /// while saving leaves the previous version behind.
+    pub fn save(&self, output_dir: &Path) -> Result<()> {
+        let partial = output_dir.join(".oxidizer-state.partial.json");
+        crate::config::write_file(&partial, self)?;
+        std::fs::rename(partial, output_dir.join(MANIFEST_NAME))?;
+        Ok(())
+    }
+
+    /// Records `input` as rendered to `output` with the settings of `config_hash`, a
+    /// [`run_hash`].
+    pub fn record(&mut self, input: &Path, output: &Path, config_hash: &str) {
+        self.completed.insert(
+            key(input),
+            Completed {
+                output: key(output),
+                config_hash: config_hash.to_string(),
+            },
+        );
+    }
+
+    /// Returns `true` if `input` was recorded, whatever the settings it was rendered with.
+    pub fn contains(&self, input: &Path) -> bool {
+        self.completed.contains_key(&key(input))
+    }
+
+    /// Returns `true` if `input` was rendered with the settings of `config_hash` and its
+    /// output is still there.
+    pub fn is_done(&self, input: &Path, config_hash: &str) -> bool {
+        self.completed
+            .get(&key(input))
+            .is_some_and(|done| done.config_hash == config_hash && done.output.exists())
+    }
+}
+
+// Paths are recorded in canonical form, so resuming from another directory works.
+#[cfg(feature = "serde")]
+fn key(input: &Path) -> PathBuf {
+    input.canonicalize().unwrap_or_else(|_| input.to_path_buf())
+}
+
+/// Returns a fingerprint of every setting in `config`, to tell whether an output was
+/// rendered with the same settings. It is stable across runs of the same version.
+pub fn config_hash(config: &OxidizerConfig) -> String {
+    let hash = io::fnv1a(io::FNV_OFFSET, format!("{:?}", config).as_bytes());
+    format!("{:016x}", hash)
+}
+
+/// Returns a fingerprint of everything that shapes the outputs of a batch: the settings,
+/// the part of each input rendered, whether only the noise is kept and the output name
+/// template.
+pub fn run_hash(
+    config: &OxidizerConfig,
+    range: TimeRange,
+    noise_only: bool,
+    template: &str,
+) -> String {
+    let run = format!("{:?} {:?} {} {}", config, range, noise_only, template);
+    format!("{:016x}", io::fnv1a(io::FNV_OFFSET, run.as_bytes()))
+}
+
+fn is_glob(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
+
+fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| AUDIO_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
@@ -0,0 +1,251 @@
+//! Digital transmission codecs and the grit they leave behind.
+//!
+//! Band-limiting a signal makes it sound distant; sending it through the codec of the
+//! medium adds the quantization noise that rides on the signal itself, loudest on the
+//! loudest sounds.
+
+use crate::analysis::{from_dbfs, to_dbfs};
+use crate::eq::{BandKind, EqBand};
+use crate::fft;
+use crate::math;
+use crate::overlay::resample;
+
+// The μ of North American and Japanese telephony.
+const MU: f32 = 255.0;
+
+// The A of European telephony.
+const A: f32 = 87.6;
+
+// Magnitude steps of an 8-bit code: one bit of sign, seven of magnitude.
+const STEPS: f32 = 127.0;
+
+// Samples per transform of the lossy codec. Long frames are what smear the quantization
+// noise ahead of an attack into a pre-echo.
+const FRAME: usize = 2048;
+
+// Edges of the telephone voice band, in Hz.
+const VOICE_LOW_HZ: f32 = 300.0;
+const VOICE_HIGH_HZ: f32 = 3400.0;
+
+/// The companding law of an 8-bit G.711 codec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum Companding {
+    #[default]
+    MuLaw, // North America and Japan: a little cleaner on quiet sounds.
+    ALaw, // Europe and most of the rest: a little grainier near silence.
+}
+
+impl Companding {
+    /// Every companding law.
+    pub const ALL: [Companding; 2] = [Companding::MuLaw, Companding::ALaw];
+
+    /// Returns the name used on the command line and in config files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Companding::MuLaw => "mu-law",
+            Companding::ALaw => "a-law",
+        }
+    }
+
+    pub fn try_from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|law| law.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown companding law: {} (use mu-law or a-law)", s))
+    }
+
+    /// Encodes `sample` to 8 bits and decodes it again, clipping it to ±1.0 on the way.
+    pub fn round_trip(&self, sample: f32) -> f32 {
+        let level = sample.abs().min(1.0);
+        let code = (self.compress(level) * STEPS).round() / STEPS;
+        self.expand(code).copysign(sample)
+    }
+
+    // Maps a level from 0.0 to 1.0 onto the logarithmic scale of the code.
+    fn compress(&self, level: f32) -> f32 {
+        match self {
+            Companding::MuLaw => math::ln(1.0 + MU * level) / math::ln(1.0 + MU),
+            Companding::ALaw if level < 1.0 / A => A * level / (1.0 + math::ln(A)),
+            Companding::ALaw => (1.0 + math::ln(A * level)) / (1.0 + math::ln(A)),
+        }
+    }
+
+    // The inverse of `compress`.
+    fn expand(&self, code: f32) -> f32 {
+        let knee = 1.0 / (1.0 + math::ln(A));
+        match self {
+            Companding::MuLaw => (math::powf(1.0 + MU, code) - 1.0) / MU,
+            Companding::ALaw if code < knee => code / knee / A,
+            Companding::ALaw => math::exp(code / knee - 1.0) / A,
+        }
+    }
+}
+
+/// A telephone line: the voice band of 300 Hz to 3.4 kHz, sampled at 8 kHz and sent
+/// through an 8-bit companding codec, as on a landline or an early VoIP call.
+///
+/// The companding keeps the quantization noise proportional to the signal, so quiet
+/// passages stay clean while loud ones pick up a rough, gritty edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct TelephoneLine {
+    pub law: Companding,
+    /// Sample rate of the line, in Hz.
+    pub sample_rate: u32,
+}
+
+impl Default for TelephoneLine {
+    fn default() -> Self {
+        Self {
+            law: Companding::MuLaw,
+            sample_rate: 8000,
+        }
+    }
+}
+
+impl TelephoneLine {
+    /// Sends interleaved stereo `samples` down the line and back, in place. Both
+    /// channels make the trip, each on a line of its own.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        let line_rate = self.sample_rate.clamp(1, sample_rate.max(1));
+        // Below the Nyquist frequency of the line, so the trip down doesn't alias
+        let top = VOICE_HIGH_HZ.min(0.45 * line_rate as f32);
+        let band = [
+            EqBand::pass(BandKind::HighPass, VOICE_LOW_HZ),
+            EqBand::pass(BandKind::LowPass, top),
+            EqBand::pass(BandKind::LowPass, top),
+        ];
+        for filter in &band {
+            filter.process(samples, sample_rate);
+        }
+
+        let mut line = resample(samples, sample_rate, line_rate);
+        for sample in &mut line {
+            *sample = self.law.round_trip(*sample);
+        }
+        let received = resample(&line, line_rate, sample_rate);
+
+        // The trip can lose a frame to rounding; the last one is held
+        let frames = received.len() / 2;
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let frame = (i / 2).min(frames.saturating_sub(1));
+            *sample = received.get(frame * 2 + i % 2).copied().unwrap_or(0.0);
+        }
+        // The interpolation mirrors the band above the line's Nyquist frequency
+        for filter in &band[1..] {
+            filter.process(samples, sample_rate);
+        }
+    }
+}
+
+/// A starved perceptual codec, like an MP3 of the early 2000s at a low bitrate: the
+/// treble is cut, the quieter parts of each frame's spectrum are thrown away and the
+/// rest is coarsely quantized.
+///
+/// No encoder is involved; the spectrum is mangled the same ways. Frames of 2048
+/// samples leave the swirling, watery tones between the surviving partials and smear
+/// noise ahead of sharp attacks (pre-echo).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct LossyCodec {
+    /// Highest frequency kept, in Hz.
+    pub cutoff_hz: f32,
+    /// How far below the loudest partial of a frame the spectrum is kept, in dB.
+    pub floor_db: f32,
+    /// Quantization step of the levels of the partials, in dB.
+    pub step_db: f32,
+}
+
+impl Default for LossyCodec {
+    fn default() -> Self {
+        Self::at_bitrate(64)
+    }
+}
+
+impl LossyCodec {
+    /// Settings that sound roughly like a stereo MP3 at `kbps`: 32 is a dial-up stream,
+    /// 64 a file-sharing download, and by 128 the damage is hard to hear.
+    pub fn at_bitrate(kbps: u32) -> Self {
+        let kbps = kbps.max(8) as f32;
+        Self {
+            cutoff_hz: (4000.0 + 125.0 * kbps).min(16000.0),
+            floor_db: 20.0 + kbps / 4.0,
+            step_db: 192.0 / kbps,
+        }
+    }
+
+    /// Runs interleaved stereo `samples` through the codec in place, each channel on
+    /// its own.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        // Square roots of a Hann window on the way in and out sum back to unity
+        let window: Vec<f32> = fft::hann(FRAME).iter().map(|w| w.sqrt()).collect();
+        let hop = FRAME / 2;
+        let frames = samples.len() / 2;
+        let (mut re, mut im) = (vec![0.0; FRAME], vec![0.0; FRAME]);
+
+        for channel in 0..2 {
+            let input: Vec<f32> = samples.iter().skip(channel).step_by(2).copied().collect();
+            // Frames start half a window early, so the start is covered twice like the rest
+            let mut output = vec![0.0f32; frames + hop + FRAME];
+            for start in (0..frames + hop).step_by(hop) {
+                for (i, (re, im)) in re.iter_mut().zip(&mut im).enumerate() {
+                    let sample = (start + i).checked_sub(hop).and_then(|t| input.get(t));
+                    *re = sample.copied().unwrap_or(0.0) * window[i];
+                    *im = 0.0;
+                }
+                fft::forward(&mut re, &mut im);
+                self.quantize(&mut re, &mut im, sample_rate);
+                fft::inverse(&mut re, &mut im);
+                for (i, sample) in re.iter().enumerate() {
+                    output[start + i] += sample * window[i];
+                }
+            }
+            for (sample, coded) in samples
+                .iter_mut()
+                .skip(channel)
+                .step_by(2)
+                .zip(&output[hop..])
+            {
+                *sample = *coded;
+            }
+        }
+    }
+
+    // Cuts, thins out and quantizes the spectrum of one frame.
+    fn quantize(&self, re: &mut [f32], im: &mut [f32], sample_rate: u32) {
+        let half = re.len() / 2;
+        let magnitude = |re: &[f32], im: &[f32], k: usize| (re[k] * re[k] + im[k] * im[k]).sqrt();
+        let loudest = (0..=half).map(|k| magnitude(re, im, k)).fold(0.0, f32::max);
+        let floor = loudest * from_dbfs(-self.floor_db.max(0.0));
+        let step = self.step_db.max(0.01);
+        let hz_per_bin = sample_rate as f32 / re.len() as f32;
+
+        for k in 0..=half {
+            let level = magnitude(re, im, k);
+            let gain = match k as f32 * hz_per_bin > self.cutoff_hz || level <= floor {
+                true => 0.0,
+                false => from_dbfs((to_dbfs(level) / step).round() * step) / level,
+            };
+            re[k] *= gain;
+            im[k] *= gain;
+        }
+        // A real signal's spectrum mirrors around the middle
+        for k in 1..half {
+            re[re.len() - k] = re[k];
+            im[im.len() - k] = -im[k];
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use clap::Args;
+use oxidizer::analysis::{self, to_dbfs};
+use oxidizer::error::Result;
+use oxidizer::io;
+use std::path::Path;
+
+/// Arguments of the `analyze` command.
+#[derive(Args, Debug, Clone)]
+pub struct AnalyzeArgs {
+    /// Path to the audio file to analyze.
+    pub input: String,
+
+    /// Also measure how far the file's spectrum is from this one's, e.g. the original
+    /// of a render.
+    #[arg(long, value_name = "FILE")]
+    pub compare: Option<String>,
+}
+
+pub fn run(args: &AnalyzeArgs) -> Result<()> {
+    let path = Path::new(&args.input);
+    let info = io::probe(path)?;
+    let samples = io::load_audio(path)?;
+
+    let frames = samples.len() / 2;
+    println!("File:        {}", path.display());
+    if let Some(rate) = info.sample_rate {
+        println!("Sample rate: {} Hz", rate);
+        println!("Duration:    {:.2} s", frames as f64 / rate as f64);
+    }
+    if let Some(channels) = info.channels {
+        println!("Channels:    {}", channels);
+    }
+    println!("Frames:      {}", frames);
+    println!("Peak:        {:.2} dBFS", to_dbfs(analysis::peak(&samples)));
+    println!("RMS:         {:.2} dBFS", to_dbfs(analysis::rms(&samples)));
+    if let Some(other) = &args.compare {
+        let reference = io::load_audio(Path::new(other))?;
+        println!(
+            "Spectral distance: {:.2} dB from {}",
+            analysis::spectral_distance(&samples, &reference),
+            other
+        );
+    }
+    Ok(())
+}
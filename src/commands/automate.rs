@@ -0,0 +1,98 @@
+use super::time::parse_time;
+use oxidizer::automation::{Automation, AutomationTarget, Interpolation, Timing};
+use oxidizer::modulation::{Drift, EnvelopeResponse};
+
+/// Parses an `--automate` value: a parameter name, a colon, and comma-separated
+/// `time=value` breakpoints.
+///
+/// Times are either all positions (`0`, `1:30`, `2m`) or all percentages of the render
+/// length (`0%`, `100%`), e.g. `intensity:0%=0.02,100%=0.4` or `cutoff:0=8000,2m=400`.
+pub fn parse_automation(s: &str) -> Result<(AutomationTarget, Automation), String> {
+    let (target, points) = s
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid automation: {} (try e.g. mix:0%=1,100%=0.5)", s))?;
+    let target = AutomationTarget::try_from_str(target.trim())?;
+
+    let mut timing = None;
+    let points = points
+        .split(',')
+        .map(|point| {
+            let (time, value) = point
+                .rsplit_once('=')
+                .ok_or_else(|| format!("Invalid breakpoint: {} (expected time=value)", point))?;
+            let value: f32 = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid automation value: {}", value))?;
+
+            let (time, kind) = match time.trim().strip_suffix('%') {
+                Some(percent) => {
+                    let percent: f32 = percent
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("Invalid percentage: {}%", percent))?;
+                    (percent / 100.0, Timing::Fraction)
+                }
+                None => (parse_time(time)?.as_secs_f32(), Timing::Seconds),
+            };
+            if *timing.get_or_insert(kind) != kind {
+                return Err("Automation times must be all percentages or all positions".to_string());
+            }
+            Ok((time, value))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let automation = Automation::new(points, Interpolation::Linear, timing.unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+    Ok((target, automation))
+}
+
+/// Parses a `--follow` or `--sidechain-follow` value: a parameter name and an amount,
+/// e.g. `intensity:0.5`.
+pub fn parse_follow(s: &str) -> Result<EnvelopeResponse, String> {
+    let (target, amount) = s
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid envelope response: {} (try e.g. intensity:0.5)", s))?;
+    let target = AutomationTarget::try_from_str(target.trim())?;
+    let amount: f32 = amount
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid envelope response amount: {}", amount))?;
+    Ok(EnvelopeResponse::new(target, amount))
+}
+
+/// Parses a `--drift` value: a parameter name, its bounds and optionally the seconds
+/// between random points, e.g. `cutoff:2000..6000` or `intensity:0.02..0.1:20`.
+pub fn parse_drift(s: &str) -> Result<Drift, String> {
+    let (target, rest) = s
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid drift: {} (try e.g. cutoff:2000..6000)", s))?;
+    let target = AutomationTarget::try_from_str(target.trim())?;
+    let (bounds, period) = match rest.split_once(':') {
+        Some((bounds, period)) => (bounds, Some(period)),
+        None => (rest, None),
+    };
+
+    let (min, max) = bounds
+        .split_once("..")
+        .ok_or_else(|| format!("Invalid drift bounds: {} (expected MIN..MAX)", bounds))?;
+    let parse = |value: &str| {
+        value
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| format!("Invalid drift bound: {}", value))
+    };
+    let mut drift = Drift::new(target, parse(min)?, parse(max)?);
+
+    if let Some(period) = period {
+        drift.period_secs = period
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|period| *period > 0.0)
+            .ok_or_else(|| format!("Invalid drift period: {} (expected seconds > 0)", period))?;
+    }
+    Ok(drift)
+}
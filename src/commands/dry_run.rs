@@ -0,0 +1,382 @@
+use oxidizer::analysis::{Levels, to_dbfs};
+use oxidizer::automation::{Automation, AutomationTarget, Timing};
+use oxidizer::dynamics::GateMode;
+use oxidizer::eq::BandKind;
+use oxidizer::error::Result;
+use oxidizer::io::{self, BitDepth, TimeRange};
+use oxidizer::modulation::EnvelopeSource;
+use oxidizer::overlay::OverlaySource;
+use oxidizer::processor::IntensityCurve;
+use oxidizer::stereo::Channel;
+use oxidizer::{BranchInput, NoiseConfig, NoiseRouting, Normalization, OxidizerConfig, Pipeline};
+use std::path::Path;
+use std::time::Duration;
+
+/// Decodes and processes `input` in memory and prints what a real run would produce,
+/// without writing anything.
+///
+/// `source` describes where the base settings came from (see `SettingsArgs::source`).
+pub fn run(
+    input: &Path,
+    output: &Path,
+    config: &OxidizerConfig,
+    range: TimeRange,
+    source: &str,
+) -> Result<()> {
+    let info = io::probe(input)?;
+    let input_rate = info.sample_rate.unwrap_or(config.sample_rate);
+    let samples = io::load_audio_range(input, range)?;
+    let frames = samples.len() / 2;
+
+    println!("Input:          {}", input.display());
+    println!("  Sample rate:  {} Hz", input_rate);
+    println!("  Duration:     {}", format_duration(frames, input_rate));
+    print_levels(&samples, input_rate);
+
+    println!("Settings:       {}", source);
+    println!(
+        "  Level:        {} (alpha {}), {} pass(es), cutoff ~{:.0} Hz",
+        config.level,
+        config.level.alpha(),
+        config.passes,
+        config.level.cutoff_hz(input_rate, config.passes)
+    );
+    match config.noise {
+        NoiseConfig::White => println!("  Noise:        white, intensity {}", config.intensity),
+        NoiseConfig::Brown { damping, step } => println!(
+            "  Noise:        brown (damping {}, step {}), intensity {}",
+            damping, step, config.intensity
+        ),
+        NoiseConfig::Crackle { density } => println!(
+            "  Noise:        crackle (density {}), intensity {}",
+            density, config.intensity
+        ),
+    }
+    if config.noise_routing == NoiseRouting::PreFilter {
+        println!("  Routing:      noise before the filter");
+    }
+    if let Some(ducking) = config.noise_ducking {
+        println!(
+            "  Ducking:      noise {} dB down at {} dBFS, {} ms attack, {} ms release",
+            ducking.depth_db, ducking.threshold_db, ducking.attack_ms, ducking.release_ms
+        );
+    }
+    for (channel, settings) in Channel::ALL.iter().zip(config.channels.iter().flatten()) {
+        println!(
+            "  Channel:      {} cutoff {:+} octave(s), noise {:+} dB",
+            channel.name(),
+            settings.cutoff_octaves,
+            settings.noise_db
+        );
+    }
+    if config.riaa {
+        println!("  RIAA:         noise between pre- and de-emphasis");
+    }
+    match config.intensity_curve {
+        IntensityCurve::Log { base: 10.0 } => {}
+        IntensityCurve::Log { base } => println!("  Curve:        log, base {}", base),
+        IntensityCurve::Linear => println!("  Curve:        linear"),
+        IntensityCurve::Custom(_) => println!("  Curve:        custom"),
+    }
+    if config.mix < 1.0 {
+        println!("  Mix:          {:.0}% wet", config.mix * 100.0);
+    }
+    if let Some(lfo) = config.lfo {
+        println!(
+            "  LFO:          {} at {} Hz, cutoff +/-{} octave(s)",
+            lfo.shape.name(),
+            lfo.rate_hz,
+            lfo.depth
+        );
+    }
+    if let Some(sidechain) = &config.sidechain {
+        println!("  Sidechain:    {}", sidechain.display());
+    }
+    for response in &config.envelope {
+        let source = match response.source {
+            EnvelopeSource::Input => "",
+            EnvelopeSource::Sidechain => " (sidechain)",
+        };
+        println!(
+            "  Follow:       {}{} {:+} dB/dB relative to {} dBFS ({} ms attack, {} ms release)",
+            response.target.name(),
+            source,
+            response.amount,
+            response.threshold_db,
+            response.attack_ms,
+            response.release_ms
+        );
+    }
+    for drift in &config.drift {
+        println!(
+            "  Drift:        {} between {} and {}, every {} s",
+            drift.target.name(),
+            drift.min,
+            drift.max,
+            drift.period_secs
+        );
+    }
+    for target in AutomationTarget::ALL {
+        if let Some(automation) = config.automation.get(target) {
+            println!(
+                "  Automation:   {} {}",
+                target.name(),
+                describe_automation(automation)
+            );
+        }
+    }
+    for segment in &config.segments {
+        let end = match segment.end_secs {
+            Some(end) => format!("{:.1}s", end),
+            None => "end".to_string(),
+        };
+        println!(
+            "  Segment:      {:.1}s to {}: {}, {} pass(es), intensity {}",
+            segment.start_secs,
+            end,
+            segment.config.level,
+            segment.config.passes,
+            segment.config.intensity
+        );
+    }
+    for branch in &config.branches {
+        let input = match branch.input {
+            BranchInput::Input => "",
+            BranchInput::Silence => " (noise only)",
+        };
+        println!(
+            "  Branch:       {:+} dB{}: {}, {} pass(es), intensity {}",
+            branch.gain_db,
+            input,
+            branch.config.level,
+            branch.config.passes,
+            branch.config.intensity
+        );
+    }
+    for event in &config.artifacts.events {
+        println!(
+            "  Artifact:     {} at {:.1}s, amplitude {}",
+            event.kind.name(),
+            event.time_secs,
+            event.amplitude
+        );
+    }
+    for random in &config.artifacts.random {
+        println!(
+            "  Artifacts:    {} x{} at random, amplitude {} to {}",
+            random.kind.name(),
+            random.count,
+            random.min_amplitude,
+            random.max_amplitude
+        );
+    }
+    if let Some(haas) = config.haas {
+        println!(
+            "  Haas:         {} channel {} ms late, dulled above {} Hz",
+            haas.delayed.name(),
+            haas.delay_ms,
+            haas.damping_hz
+        );
+    }
+    if let Some(line) = config.telephone {
+        println!(
+            "  Telephone:    {} at {} Hz",
+            line.law.name(),
+            line.sample_rate
+        );
+    }
+    if let Some(codec) = config.lossy {
+        println!(
+            "  Lossy codec:  cut above {} Hz, {} dB floor, {:.1} dB steps",
+            codec.cutoff_hz, codec.floor_db, codec.step_db
+        );
+    }
+    if let Some(flutter) = config.flutter {
+        println!(
+            "  Flutter:      {} {} Hz, {}% speed deviation",
+            flutter.shape.name(),
+            flutter.rate_hz,
+            flutter.depth_percent
+        );
+    }
+    if let Some(wobble) = config.wobble {
+        println!(
+            "  Wobble:       off-center at {} rpm, {}% speed deviation",
+            wobble.speed.name(),
+            wobble.depth_percent
+        );
+    }
+    if let Some(chorus) = config.chorus {
+        println!(
+            "  Chorus:       {} ms ± {} ms at {} Hz, mix {}",
+            chorus.delay_ms, chorus.depth_ms, chorus.rate_hz, chorus.mix
+        );
+    }
+    for band in &config.eq {
+        match band.kind {
+            BandKind::Peak => println!(
+                "  EQ:           {:+} dB peak at {} Hz, Q {}",
+                band.gain_db, band.frequency_hz, band.q
+            ),
+            kind => println!(
+                "  EQ:           {} at {} Hz, Q {}",
+                kind.name(),
+                band.frequency_hz,
+                band.q
+            ),
+        }
+    }
+    if let Some(ground_loop) = config.ground_loop {
+        println!(
+            "  Ground loop:  {} Hz hum {} dB under the program, harmonics {} dB/octave",
+            ground_loop.frequency_hz, -ground_loop.level_db, ground_loop.tilt_db
+        );
+    }
+    if let Some(crossfeed) = config.crossfeed {
+        println!(
+            "  Crossfeed:    {} dB below {} Hz, {} ms late",
+            crossfeed.level_db, crossfeed.cutoff_hz, crossfeed.delay_ms
+        );
+    }
+    if let Some(ms) = config.loop_crossfade_ms {
+        println!("  Loop:         seamless, {} ms crossfade", ms);
+    }
+    if let Some(shaper) = config.transients {
+        println!(
+            "  Transients:   attack {:+} dB, sustain {:+} dB",
+            shaper.attack_db, shaper.sustain_db
+        );
+    }
+    if let Some(gate) = config.gate {
+        let mode = match gate.mode {
+            GateMode::Output => "output",
+            GateMode::Program => "program",
+        };
+        println!(
+            "  Gate:         {} below {} dBFS ({} ms attack, {} ms hold, {} ms release)",
+            mode, gate.threshold_db, gate.attack_ms, gate.hold_ms, gate.release_ms
+        );
+    }
+    if let Some(e) = config.expander {
+        println!(
+            "  Expander:     1:{} below {} dBFS ({} ms attack, {} ms release), up to {} dB",
+            e.ratio, e.threshold_db, e.attack_ms, e.release_ms, e.range_db
+        );
+    }
+    if let Some(c) = config.compressor {
+        println!(
+            "  Compressor:   {}:1 above {} dBFS ({} ms attack, {} ms release), makeup {:+} dB",
+            c.ratio, c.threshold_db, c.attack_ms, c.release_ms, c.makeup_db
+        );
+    }
+    if let Some(limiter) = config.limiter {
+        println!(
+            "  Limiter:      {} dBFS ceiling, {} ms release",
+            limiter.ceiling_db, limiter.release_ms
+        );
+    }
+    for overlay in &config.overlay {
+        let source = match &overlay.source {
+            OverlaySource::File(path) => path.display().to_string(),
+            OverlaySource::Buffer { .. } => "buffer".to_string(),
+        };
+        println!(
+            "  Overlay:      {} at {:+} dB{}",
+            source,
+            overlay.gain_db,
+            if overlay.loop_if_shorter {
+                ", looped"
+            } else {
+                ""
+            }
+        );
+    }
+    if !config.overlay.is_empty() {
+        println!(
+            "  Resampling:   {} (overlays at another rate)",
+            config.resample_quality.name()
+        );
+    }
+    match config.normalization {
+        Normalization::None => println!("  Normalize:    off"),
+        Normalization::Peak { ceiling } => {
+            println!("  Normalize:    peak to {:.2} dBFS", to_dbfs(ceiling))
+        }
+        Normalization::Smart { ceiling } => println!(
+            "  Normalize:    input staged to peak at {:.2} dBFS after the noise",
+            to_dbfs(ceiling)
+        ),
+        Normalization::Loudness {
+            target_lufs,
+            true_peak,
+        } => println!(
+            "  Normalize:    loudness to {} LUFS, true peak below {} dBTP",
+            target_lufs, true_peak
+        ),
+    }
+    match config.seed {
+        Some(seed) => println!("  Seed:         {}", seed),
+        None => println!("  Seed:         random"),
+    }
+
+    let processed = Pipeline::new(config.clone())
+        .set_range(range)
+        .process(samples, input_rate)?;
+
+    println!("Output:         {}", output.display());
+    println!(
+        "  Format:       {}, {}, stereo, {} Hz",
+        config.format.name().to_uppercase(),
+        describe(config.bit_depth),
+        config.sample_rate
+    );
+    println!(
+        "  Duration:     {}",
+        format_duration(frames, config.sample_rate)
+    );
+    if config.sample_rate != input_rate {
+        println!("                (the pitch shifts: output rate differs from the input's)");
+    }
+    print_levels(&processed, input_rate);
+
+    println!("Dry run: nothing was written.");
+    Ok(())
+}
+
+fn print_levels(samples: &[f32], sample_rate: u32) {
+    let levels = Levels::measure(samples, sample_rate);
+    println!("  Peak:         {:.2} dBFS", levels.peak_dbfs);
+    println!("  Loudness:     {:.1} LUFS", levels.loudness_lufs);
+}
+
+// Formats breakpoints as e.g. "0%=0.02 -> 100%=0.4 (linear)".
+fn describe_automation(automation: &Automation) -> String {
+    let points: Vec<String> = automation
+        .points()
+        .iter()
+        .map(|&(time, value)| match automation.timing() {
+            Timing::Seconds => format!("{}s={}", time, value),
+            Timing::Fraction => format!("{}%={}", time * 100.0, value),
+        })
+        .collect();
+    format!(
+        "{} ({})",
+        points.join(" -> "),
+        format!("{:?}", automation.interpolation()).to_lowercase()
+    )
+}
+
+fn describe(bit_depth: BitDepth) -> &'static str {
+    match bit_depth {
+        BitDepth::Int16 => "16-bit PCM",
+        BitDepth::Int24 => "24-bit PCM",
+        BitDepth::Float32 => "32-bit float",
+    }
+}
+
+// Formats a length as m:ss.ss.
+fn format_duration(frames: usize, sample_rate: u32) -> String {
+    let duration = Duration::from_secs_f64(frames as f64 / sample_rate.max(1) as f64);
+    let seconds = duration.as_secs_f64();
+    format!("{}:{:05.2}", (seconds / 60.0) as u64, seconds % 60.0)
+}
@@ -0,0 +1,44 @@
+use clap::Args;
+use oxidizer::error::Result;
+use oxidizer::io;
+use std::path::Path;
+
+/// Arguments of the `info` command.
+#[derive(Args, Debug, Clone)]
+pub struct InfoArgs {
+    /// Path to the audio file to inspect.
+    pub input: String,
+}
+
+pub fn run(args: &InfoArgs) -> Result<()> {
+    let path = Path::new(&args.input);
+    let details = io::inspect(path)?;
+    let stream = &details.stream;
+
+    println!("File:        {}", path.display());
+    println!("Container:   {}", details.container.unwrap_or("unknown"));
+    println!("Codec:       {}", details.codec);
+    match stream.duration() {
+        Some(duration) => println!("Duration:    {:.2} s", duration.as_secs_f64()),
+        None => println!("Duration:    unknown"),
+    }
+    if let Some(rate) = stream.sample_rate {
+        println!("Sample rate: {} Hz", rate);
+    }
+    if let Some(channels) = stream.channels {
+        println!("Channels:    {}", channels);
+    }
+    match details.bit_depth {
+        Some(bits) => println!("Bit depth:   {}", bits),
+        None => println!("Bit depth:   n/a"),
+    }
+
+    if !details.tags.is_empty() {
+        println!("Tags:");
+        let width = details.tags.iter().map(|(name, _)| name.len()).max();
+        for (name, value) in &details.tags {
+            println!("  {:width$}  {}", name, value, width = width.unwrap_or(0));
+        }
+    }
+    Ok(())
+}
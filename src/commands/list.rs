@@ -0,0 +1,68 @@
+use clap::ValueEnum;
+use oxidizer::batch::AUDIO_EXTENSIONS;
+use oxidizer::error::Result;
+use oxidizer::io::{AudioFormat, BitDepth};
+use oxidizer::{NoiseConfig, OxidationLevel, presets};
+
+/// What the `list` command enumerates.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListTopic {
+    /// Noise generators.
+    Algorithms,
+    /// Oxidation levels.
+    Levels,
+    /// Built-in presets.
+    Presets,
+    /// Input and output file formats.
+    Formats,
+}
+
+/// Prints one topic, or all of them when `topic` is `None`.
+pub fn run(topic: Option<ListTopic>) -> Result<()> {
+    let topics = match topic {
+        Some(topic) => vec![topic],
+        None => ListTopic::value_variants().to_vec(),
+    };
+
+    for (i, topic) in topics.iter().enumerate() {
+        if topics.len() > 1 {
+            if i > 0 {
+                println!();
+            }
+            println!("{:?}:", topic);
+        }
+        print_topic(*topic);
+    }
+    Ok(())
+}
+
+fn print_topic(topic: ListTopic) {
+    match topic {
+        ListTopic::Algorithms => {
+            for noise in NoiseConfig::all() {
+                println!("{:<12} {}", noise.name(), noise.description());
+            }
+        }
+        ListTopic::Levels => {
+            for level in OxidationLevel::ALL {
+                println!("{:<12} {}", level.name(), level.description());
+            }
+        }
+        ListTopic::Presets => {
+            for preset in presets::builtin() {
+                println!("{:<12} {}", preset.name, preset.description);
+            }
+        }
+        ListTopic::Formats => {
+            println!("Input:  {}", AUDIO_EXTENSIONS.join(", "));
+            println!("Output:");
+            for format in AudioFormat::ALL {
+                println!("  {:<10} {}", format.name(), format.description());
+            }
+            println!("Bit depths:");
+            for bit_depth in BitDepth::ALL {
+                println!("  {:<10} {}", bit_depth.name(), bit_depth.description());
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Leveled logging on stderr for the binary, built on the library's tracing spans.
+
+use clap::Args;
+use std::io::IsTerminal;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::prelude::*;
+
+// Target of the binary's own messages, which are shown by default.
+const CLI_TARGET: &str = "oxidizer::commands";
+
+/// How much the binary writes to stderr. Results printed to stdout are unaffected.
+#[derive(Args, Debug, Clone, Copy, Default)]
+pub struct Verbosity {
+    /// Only print errors: no progress bars or status messages, e.g. for scripts and cron jobs.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print more detail: -v logs what the library does, -vv adds debug messages and the
+    /// time spent in each stage.
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+}
+
+impl Verbosity {
+    /// Installs the global subscriber. Warnings are always shown, and the binary's own
+    /// status messages unless `--quiet` is given.
+    pub fn init(self) {
+        let filter = match (self.quiet, self.verbose) {
+            (true, _) => Targets::new().with_default(LevelFilter::ERROR),
+            (false, 0) => Targets::new()
+                .with_default(LevelFilter::WARN)
+                .with_target(CLI_TARGET, LevelFilter::INFO),
+            (false, 1) => Targets::new().with_default(LevelFilter::INFO),
+            (false, 2) => Targets::new().with_default(LevelFilter::DEBUG),
+            (false, _) => Targets::new().with_default(LevelFilter::TRACE),
+        };
+
+        let layer = tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .with_ansi(std::io::stderr().is_terminal())
+            .with_target(self.verbose > 1)
+            .with_level(self.verbose > 0);
+        // Closing spans report how long the stage inside them took
+        let layer = if self.verbose > 1 {
+            layer.with_span_events(FmtSpan::CLOSE).boxed()
+        } else {
+            layer.without_time().boxed()
+        };
+
+        tracing_subscriber::registry()
+            .with(layer.with_filter(filter))
+            .init();
+    }
+}
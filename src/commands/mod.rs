@@ -0,0 +1,90 @@
+//! Subcommands of the `oxidizer` binary.
+
+pub mod analyze;
+pub mod automate;
+pub mod dry_run;
+pub mod info;
+pub mod list;
+pub mod log;
+pub mod noise;
+pub mod oxidize;
+#[cfg(feature = "tui")]
+pub mod player;
+pub mod presets;
+pub mod preview;
+pub mod progress;
+pub mod project;
+pub mod settings;
+pub mod time;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod watch;
+
+use oxidizer::error::Result;
+use oxidizer::io;
+use oxidizer::pipeline::{Pipeline, RunReport};
+use progress::ProgressDisplay;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::Arc;
+
+// Sample rate assumed for throughput figures when the input doesn't declare one.
+const FALLBACK_SAMPLE_RATE: u32 = 44100;
+
+/// Formats of the machine-readable run summary.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+}
+
+// Runs the pipeline, showing progress bars on stderr when it's attached to a terminal.
+pub fn run_with_progress(pipeline: Pipeline, input: &Path, output: &Path) -> Result<RunReport> {
+    run_joined_with_progress(pipeline, &[input], output)
+}
+
+// Same as `run_with_progress` for several inputs joined into one program.
+pub fn run_joined_with_progress(
+    pipeline: Pipeline,
+    inputs: &[impl AsRef<Path>],
+    output: &Path,
+) -> Result<RunReport> {
+    with_progress(pipeline, inputs, |pipeline| {
+        pipeline.run_joined_with_report(inputs, output)
+    })
+}
+
+// Same as `run_with_progress` for a gapless album, one output per input.
+pub fn run_album_with_progress(
+    pipeline: Pipeline,
+    inputs: &[impl AsRef<Path>],
+    outputs: &[impl AsRef<Path>],
+) -> Result<Vec<RunReport>> {
+    with_progress(pipeline, inputs, |pipeline| {
+        pipeline.run_album(inputs, outputs)
+    })
+}
+
+fn with_progress<T>(
+    mut pipeline: Pipeline,
+    inputs: &[impl AsRef<Path>],
+    run: impl FnOnce(&Pipeline) -> Result<T>,
+) -> Result<T> {
+    // --quiet turns the CLI's messages off, and the bars with them
+    if !std::io::stderr().is_terminal() || !tracing::enabled!(tracing::Level::INFO) {
+        return run(&pipeline);
+    }
+
+    let first = inputs.first().map(|input| input.as_ref());
+    let sample_rate = first
+        .and_then(|input| io::probe(input).ok())
+        .and_then(|info| info.sample_rate)
+        .unwrap_or(FALLBACK_SAMPLE_RATE);
+    let display = Arc::new(ProgressDisplay::new(sample_rate, pipeline.config().passes));
+
+    let callback_display = Arc::clone(&display);
+    pipeline.set_progress_callback(move |progress| callback_display.update(progress));
+
+    run(&pipeline)
+        .inspect(|_| display.finish())
+        .inspect_err(|_| display.abandon())
+}
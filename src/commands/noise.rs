@@ -0,0 +1,57 @@
+use clap::Args;
+use oxidizer::error::Result;
+use oxidizer::io::{self, AudioFormat, BitDepth};
+use oxidizer::processor::noise::{self, NoiseGenerator};
+use oxidizer::{NoiseConfig, OxidizerError};
+use std::path::Path;
+
+/// Arguments of the `noise` command.
+#[derive(Args, Debug, Clone)]
+pub struct NoiseArgs {
+    /// Noise generator to render: 'brown', 'white' or 'crackle'.
+    #[arg(short = 'T', long = "type", default_value = "brown")]
+    pub noise_type: String,
+
+    /// Length of the rendered file, in seconds.
+    #[arg(short, long, default_value_t = 60.0)]
+    pub duration: f32,
+
+    /// Path where the file will be saved, as FLAC if it ends in .flac and WAV otherwise.
+    #[arg(short, long = "out", default_value = "noise.wav")]
+    pub output: String,
+
+    /// Sample rate of the rendered file.
+    #[arg(short = 's', long, default_value_t = 44100)]
+    pub sample_rate: u32,
+
+    /// Linear gain applied to the generator output (0.0 to 1.0).
+    #[arg(short, long, default_value_t = 0.5)]
+    pub gain: f32,
+
+    /// Seed for the generator, to render the exact same noise again.
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+pub fn run(args: &NoiseArgs) -> Result<()> {
+    let frames = (args.duration.max(0.0) * args.sample_rate as f32) as usize;
+
+    let mut generator = NoiseConfig::try_from_str(&args.noise_type)
+        .map_err(OxidizerError::InvalidValue)?
+        .generator()?;
+    if let Some(seed) = args.seed {
+        generator.reseed(seed);
+    }
+    let mut samples = noise::render(&mut generator, frames);
+    samples.iter_mut().for_each(|s| *s *= args.gain);
+
+    let output = Path::new(&args.output);
+    io::save_audio_as(
+        output,
+        samples,
+        args.sample_rate,
+        AudioFormat::from_path(output).unwrap_or_default(),
+        BitDepth::Int16,
+        |_, _| Ok(()),
+    )
+}
@@ -0,0 +1,585 @@
+use super::ReportFormat;
+use super::settings::SettingsArgs;
+use super::time::parse_time;
+use clap::Args;
+use oxidizer::artifacts::Artifacts;
+use oxidizer::batch::{self, Manifest, OverwritePolicy};
+use oxidizer::error::Result;
+use oxidizer::io::TimeRange;
+#[cfg(feature = "jack")]
+use oxidizer::jack_client::{self, JackSettings};
+use oxidizer::pipeline::{self, Sidecar};
+use oxidizer::presets::Preset;
+#[cfg(feature = "jack")]
+use oxidizer::processor::Oxidizer;
+#[cfg(feature = "jack")]
+use oxidizer::processor::noise::NoiseGenerator;
+use oxidizer::project::{Export, Project};
+use oxidizer::{OxidizerConfig, OxidizerError, Pipeline, RunReport};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Arguments of the `oxidize` command, also accepted without a subcommand.
+#[derive(Args, Debug, Clone)]
+pub struct OxidizeArgs {
+    /// Path to the input file (e.g., music.mp3). Supports multiple formats via Symphonia.
+    /// A directory or a quoted glob (e.g. 'album/*.mp3') processes every matching file.
+    #[arg(short, long)]
+    #[cfg_attr(
+        not(feature = "jack"),
+        arg(required_unless_present_any = ["save_preset", "watch"])
+    )]
+    #[cfg_attr(
+        feature = "jack",
+        arg(required_unless_present_any = ["jack", "save_preset", "watch"])
+    )]
+    pub input: Option<String>,
+
+    /// Path where the processed file will be saved. A .flac extension selects FLAC output.
+    /// [default: output.wav]
+    /// In batch mode: an output directory, or a path template like 'out/{stem}_oxidized.wav'.
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// Watch a directory and oxidize audio files as they are added or changed.
+    /// Results go to --output (a directory or template), by default '<dir>/oxidized'.
+    #[arg(short, long, conflicts_with = "input")]
+    pub watch: Option<String>,
+
+    /// Only process the input from this point on (e.g. 90, 1:23, 2m).
+    #[arg(long, value_parser = parse_time)]
+    pub start: Option<Duration>,
+
+    /// Only process this much of the input (e.g. 30s, 1:00).
+    #[arg(long, value_parser = parse_time)]
+    pub duration: Option<Duration>,
+
+    /// Join the files matched by a directory or glob --input (sorted by name) into one
+    /// continuous program, written to a single --output file.
+    #[arg(long, conflicts_with_all = ["watch", "dry_run"])]
+    pub concat: bool,
+
+    /// Process the files of a directory or glob --input (sorted by name) as one gapless
+    /// album: the filter and noise run on across tracks and normalization applies one
+    /// gain to all of them. Each track is still written to its own file.
+    #[arg(long, conflicts_with_all = ["watch", "dry_run", "concat", "start", "duration"])]
+    pub album: bool,
+
+    /// Overlap consecutive --concat inputs by this long (e.g. 2s, 500ms). [default: 0]
+    #[arg(long, value_parser = parse_time, requires = "concat")]
+    pub crossfade: Option<Duration>,
+
+    /// Analyze the input and print the effective settings and the predicted result,
+    /// without writing any file.
+    #[arg(long, conflicts_with = "watch")]
+    pub dry_run: bool,
+
+    /// Overwrite existing output files.
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Write to a numbered name (e.g. 'song_oxidized_1.wav') instead of overwriting
+    /// an existing output file.
+    #[arg(long, conflicts_with = "force")]
+    pub suffix: bool,
+
+    /// Print a machine-readable summary of the run to stdout (an array in batch mode).
+    #[arg(long, value_enum, conflicts_with_all = ["watch", "dry_run"])]
+    pub report: Option<ReportFormat>,
+
+    /// Skip the files of a batch that an earlier run with the same settings finished, as
+    /// recorded in the output directory's .oxidizer-state.json.
+    #[arg(long, conflicts_with_all = ["watch", "dry_run", "album", "concat"])]
+    pub resume: bool,
+
+    /// Write a record of the resolved settings, seed, input hashes and version next to
+    /// each output, e.g. 'song_oxidized.wav.json'. FORMAT is json or toml.
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        default_missing_value = "json",
+        value_parser = ["json", "toml"],
+        conflicts_with_all = ["watch", "dry_run"]
+    )]
+    pub sidecar: Option<String>,
+
+    /// Number of files processed concurrently in batch mode. 0 uses every CPU core.
+    #[arg(short, long, default_value_t = 1)]
+    pub jobs: usize,
+
+    #[command(flatten)]
+    pub settings: SettingsArgs,
+
+    /// Write only what the chain adds to the music (noise, artifacts, overlays) at the
+    /// level it has in the full render, to import as a separate stem in a DAW.
+    #[arg(long)]
+    pub noise_only: bool,
+
+    /// Write the timeline of the artifacts in the render (fixed and random ones) to a
+    /// .json or .toml file, which can be loaded back as the `artifacts` of a config.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["watch", "dry_run", "album"])]
+    pub export_artifacts: Option<String>,
+
+    /// Also write the signal after the filter, after the noise is added and after the
+    /// saturation to filtered.wav, noise.wav and saturated.wav in this directory.
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["watch", "dry_run", "album", "noise_only"]
+    )]
+    pub export_stages: Option<PathBuf>,
+
+    /// Print the peak and RMS level after every stage of the chain, to find the one
+    /// that clips or squashes the signal. Also added to the JSON --report.
+    #[arg(long, conflicts_with_all = ["watch", "dry_run", "album", "noise_only"])]
+    pub meter: bool,
+
+    /// Save the resolved settings as a shareable preset file (.toml or .json).
+    /// Processing is skipped when no input is given.
+    #[arg(long)]
+    pub save_preset: Option<String>,
+
+    /// Save the input, settings and output as a project file (.oxproj), which
+    /// 'oxidizer project render' renders again. With --dry-run, nothing else is written.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["watch", "album"])]
+    pub save_project: Option<PathBuf>,
+
+    /// Run as a real-time JACK client instead of processing a file.
+    /// Exposes in_l/in_r and out_l/out_r ports until Enter is pressed.
+    #[cfg(feature = "jack")]
+    #[arg(long)]
+    pub jack: bool,
+}
+
+pub fn run(args: &OxidizeArgs) -> Result<()> {
+    let mut config = args.settings.resolve()?;
+
+    #[cfg(feature = "jack")]
+    if args.jack {
+        return run_jack(&config);
+    }
+
+    if let Some(path) = &args.save_preset {
+        save_preset(path, &config)?;
+        if args.input.is_none() && args.watch.is_none() {
+            return Ok(());
+        }
+    }
+
+    if let Some(dir) = &args.watch {
+        return super::watch::run(
+            Path::new(dir),
+            args.output.as_deref(),
+            &args.settings,
+            config,
+        );
+    }
+
+    let input = args
+        .input
+        .as_deref()
+        .ok_or_else(|| OxidizerError::InvalidValue("An input file is required".to_string()))?;
+
+    if batch::is_batch(input) && !args.concat {
+        if args.export_artifacts.is_some() {
+            return Err(OxidizerError::InvalidValue(
+                "--export-artifacts needs a single file or --concat".to_string(),
+            ));
+        }
+        if args.export_stages.is_some() {
+            return Err(OxidizerError::InvalidValue(
+                "--export-stages needs a single file or --concat".to_string(),
+            ));
+        }
+        if args.meter {
+            return Err(OxidizerError::InvalidValue(
+                "--meter needs a single file or --concat".to_string(),
+            ));
+        }
+        if args.save_project.is_some() {
+            return Err(OxidizerError::InvalidValue(
+                "--save-project needs a single file or --concat".to_string(),
+            ));
+        }
+        return run_batch(args, input, config);
+    }
+
+    let output = match &args.output {
+        Some(output) => PathBuf::from(output),
+        None => PathBuf::from(format!("output.{}", config.format.extension())),
+    };
+    args.settings.apply_output_format(&mut config, &output)?;
+
+    if let Some(path) = &args.save_project {
+        save_project(path, args, input, &output, &config)?;
+    }
+    if args.dry_run {
+        let source = args.settings.source();
+        return super::dry_run::run(Path::new(input), &output, &config, args.range(), &source);
+    }
+
+    let output = batch::resolve_output(&output, args.overwrite_policy(), &HashSet::new())
+        .inspect_err(print_overwrite_hint)?;
+
+    if args.export_artifacts.is_some() || args.sidecar.is_some() {
+        // The exported timeline has to be the one rendered, and a sidecar has to be
+        // enough to render it again
+        pin_seed(&mut config);
+    }
+    let artifacts = config.artifacts.clone();
+    let seed = config.seed.unwrap_or_default();
+
+    let mut pipeline = Pipeline::new(config);
+    pipeline
+        .set_range(args.range())
+        .set_noise_only(args.noise_only)
+        .set_metering(args.meter);
+    if let Some(dir) = &args.export_stages {
+        pipeline.set_stage_export(dir);
+    }
+    let report = if args.concat {
+        let inputs = batch::collect_inputs(input)?;
+        tracing::info!("Joining {} files into {}", inputs.len(), output.display());
+        pipeline.set_crossfade(args.crossfade.unwrap_or_default());
+        super::run_joined_with_progress(pipeline, &inputs, &output)?
+    } else {
+        super::run_with_progress(pipeline, Path::new(input), &output)?
+    };
+    args.write_sidecar(&report)?;
+
+    if let Some(path) = &args.export_artifacts {
+        let events = artifacts.schedule(report.input_duration_secs, seed);
+        Artifacts::fixed(events).save(path)?;
+    }
+    if let Some(ReportFormat::Json) = args.report {
+        print_json(&report)?;
+    } else if args.meter {
+        print_meters(&report);
+    }
+    Ok(())
+}
+
+fn print_meters(report: &RunReport) {
+    println!("{:<14} {:>11} {:>11}", "Stage", "Peak", "RMS");
+    for levels in &report.stages {
+        // Past full scale, something downstream has to catch it
+        let over = if levels.peak_dbfs > 0.0 { "  over" } else { "" };
+        println!(
+            "{:<14} {:>6.1} dBFS {:>6.1} dBFS{}",
+            levels.stage, levels.peak_dbfs, levels.rms_dbfs, over
+        );
+    }
+}
+
+fn print_json(value: &impl serde::Serialize) -> Result<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| OxidizerError::Encoding(format!("Couldn't write the report: {}", e)))?;
+    println!("{}", json);
+    Ok(())
+}
+
+impl OxidizeArgs {
+    // The part of each input selected with --start and --duration.
+    fn range(&self) -> TimeRange {
+        TimeRange::new(self.start.unwrap_or_default(), self.duration)
+    }
+
+    // Writes the --sidecar of a finished run next to its output.
+    fn write_sidecar(&self, report: &RunReport) -> Result<()> {
+        let Some(extension) = &self.sidecar else {
+            return Ok(());
+        };
+        Sidecar::new(report)?.save(Sidecar::path_for(&report.output, extension))
+    }
+
+    fn overwrite_policy(&self) -> OverwritePolicy {
+        if self.force {
+            OverwritePolicy::Overwrite
+        } else if self.suffix {
+            OverwritePolicy::Rename
+        } else {
+            OverwritePolicy::Refuse
+        }
+    }
+}
+
+// Gives an unseeded config the seed it would have been rendered with, so it can be
+// recorded.
+fn pin_seed(config: &mut OxidizerConfig) {
+    config.seed.get_or_insert_with(pipeline::fallback_seed);
+}
+
+fn print_overwrite_hint(error: &OxidizerError) {
+    if let OxidizerError::OutputExists(_) = error {
+        tracing::info!("Pass --force to overwrite it, or --suffix to write to a new name.");
+    }
+}
+
+// Outcome of one file in the batch --report.
+#[derive(serde::Serialize)]
+#[serde(untagged)]
+enum BatchEntry {
+    Done(Box<RunReport>),
+    Failed {
+        input: PathBuf,
+        output: PathBuf,
+        error: String,
+    },
+}
+
+// Processes every file matched by `input` on `--jobs` worker threads, carrying on past
+// individual failures and summarizing them at the end.
+fn run_batch(args: &OxidizeArgs, input: &str, mut config: OxidizerConfig) -> Result<()> {
+    let inputs = batch::collect_inputs(input)?;
+    let (output_dir, template) = batch::split_output_spec(args.output.as_deref().unwrap_or("."));
+    let template = args.settings.apply_output_template(&mut config, template)?;
+    let config = &config;
+    let range = args.range();
+
+    if args.dry_run {
+        let source = args.settings.source();
+        for input in &inputs {
+            let output = batch::output_path(input, &output_dir, &template);
+            super::dry_run::run(input, &output, config, range, &source)?;
+            println!();
+        }
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(&output_dir)?;
+
+    let config_hash = batch::run_hash(config, range, args.noise_only, &template);
+    let manifest = Manifest::load(&output_dir)?;
+    let inputs = if args.resume {
+        let (done, pending): (Vec<_>, Vec<_>) = inputs
+            .into_iter()
+            .partition(|input| manifest.is_done(input, &config_hash));
+        if !done.is_empty() {
+            tracing::info!("Skipping {} files finished by an earlier run", done.len());
+        }
+        if pending.is_empty() {
+            return Ok(());
+        }
+        pending
+    } else {
+        inputs
+    };
+
+    let policy = args.overwrite_policy();
+    let outputs = batch::plan_outputs_with(&inputs, &output_dir, &template, |input| {
+        match policy {
+            // An output the manifest doesn't know of was left half written by the run
+            // being resumed
+            OverwritePolicy::Refuse if args.resume && !manifest.contains(input) => {
+                OverwritePolicy::Overwrite
+            }
+            policy => policy,
+        }
+    })
+    .inspect_err(print_overwrite_hint)?;
+
+    if args.album {
+        tracing::info!("Processing {} files as one album", inputs.len());
+        let mut config = config.clone();
+        if args.sidecar.is_some() {
+            pin_seed(&mut config);
+        }
+        let mut pipeline = Pipeline::new(config);
+        pipeline.set_noise_only(args.noise_only);
+        let reports = super::run_album_with_progress(pipeline, &inputs, &outputs)?;
+        for report in &reports {
+            args.write_sidecar(report)?;
+            tracing::info!(
+                "  {} -> {}",
+                report.input.display(),
+                report.output.display()
+            );
+        }
+        if let Some(ReportFormat::Json) = args.report {
+            print_json(&reports)?;
+        }
+        return Ok(());
+    }
+
+    let jobs = match args.jobs {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+    .min(inputs.len());
+
+    let total = inputs.len();
+    let next = AtomicUsize::new(0);
+    let failures = Mutex::new(Vec::new());
+    let manifest = Mutex::new(manifest);
+    // One entry per input, in input order, for --report
+    let entries = Mutex::new(Vec::from_iter((0..total).map(|_| None)));
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                // Each worker takes the next unclaimed file until none are left
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(input) = inputs.get(i) else { break };
+
+                    let position = format!("[{}/{}]", i + 1, total);
+                    let output = &outputs[i];
+                    tracing::info!("{} {} -> {}", position, input.display(), output.display());
+
+                    let mut config = config.clone();
+                    if args.sidecar.is_some() {
+                        pin_seed(&mut config);
+                    }
+                    let mut pipeline = Pipeline::new(config);
+                    pipeline.set_range(range).set_noise_only(args.noise_only);
+                    let started = Instant::now();
+                    // Live progress only makes sense while a single file is being processed
+                    let result = if jobs == 1 {
+                        super::run_with_progress(pipeline, input, output)
+                    } else {
+                        pipeline.run_with_report(input, output)
+                    };
+
+                    let result =
+                        result.and_then(|report| args.write_sidecar(&report).map(|_| report));
+                    let entry = match result {
+                        Ok(report) => {
+                            // Written after every file, so a crash loses at most the ones
+                            // in progress
+                            let mut manifest = manifest.lock().unwrap();
+                            manifest.record(input, output, &config_hash);
+                            if let Err(e) = manifest.save(&output_dir) {
+                                tracing::warn!("Couldn't update {}: {}", batch::MANIFEST_NAME, e);
+                            }
+                            drop(manifest);
+                            if jobs > 1 {
+                                tracing::info!(
+                                    "{} Finished {} in {:.1}s",
+                                    position,
+                                    output.display(),
+                                    started.elapsed().as_secs_f32()
+                                );
+                            }
+                            BatchEntry::Done(Box::new(report))
+                        }
+                        Err(e) => {
+                            tracing::error!("{} Failed: {}", position, e);
+                            let entry = BatchEntry::Failed {
+                                input: input.clone(),
+                                output: output.clone(),
+                                error: e.to_string(),
+                            };
+                            failures.lock().unwrap().push((input, e));
+                            entry
+                        }
+                    };
+                    entries.lock().unwrap()[i] = Some(entry);
+                }
+            });
+        }
+    });
+
+    let failures = failures.into_inner().unwrap();
+    tracing::info!(
+        "Processed {} files: {} succeeded, {} failed",
+        total,
+        total - failures.len(),
+        failures.len()
+    );
+    for (input, error) in &failures {
+        tracing::error!("  {}: {}", input.display(), error);
+    }
+
+    if let Some(ReportFormat::Json) = args.report {
+        print_json(&entries.into_inner().unwrap())?;
+    }
+
+    if !failures.is_empty() {
+        return Err(OxidizerError::Job(format!(
+            "{} of {} files failed",
+            failures.len(),
+            total
+        )));
+    }
+    Ok(())
+}
+
+// Stores the invocation as a project file that renders the same output.
+fn save_project(
+    path: &Path,
+    args: &OxidizeArgs,
+    input: &str,
+    output: &Path,
+    config: &OxidizerConfig,
+) -> Result<()> {
+    let inputs = match args.concat {
+        true => batch::collect_inputs(input)?,
+        false => vec![PathBuf::from(input)],
+    };
+    // The project reads its paths relative to itself, not to where it was made
+    let project = Project {
+        inputs: inputs
+            .iter()
+            .map(std::path::absolute)
+            .collect::<std::io::Result<_>>()?,
+        crossfade_secs: args.crossfade.unwrap_or_default().as_secs_f64(),
+        preset: None,
+        settings: config.clone(),
+        export: Export {
+            output: std::path::absolute(output)?,
+            start_secs: args.start.unwrap_or_default().as_secs_f64(),
+            duration_secs: args.duration.map(|d| d.as_secs_f64()),
+            ..Default::default()
+        },
+    };
+
+    project.save(path)?;
+    tracing::info!("Saved project to {}", path.display());
+    Ok(())
+}
+
+// Stores the settings as a preset named after the file.
+fn save_preset(path: &str, config: &OxidizerConfig) -> Result<()> {
+    let path = Path::new(path);
+    let preset = Preset {
+        name: path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        description: String::new(),
+        config: config.clone(),
+    };
+
+    preset.save(path)?;
+    tracing::info!("Saved preset '{}' to {}", preset.name, path.display());
+    Ok(())
+}
+
+// Runs the engine as a JACK client until the user presses Enter.
+#[cfg(feature = "jack")]
+fn run_jack(config: &OxidizerConfig) -> Result<()> {
+    let mut noise = config.noise.generator()?;
+    if let Some(seed) = config.seed {
+        noise.reseed(seed);
+    }
+
+    let settings = JackSettings {
+        level: config.level,
+        passes: config.passes,
+        intensity: config.intensity,
+    };
+    let client = jack_client::start("oxidizer", Oxidizer::new(noise), settings)?;
+    println!(
+        "JACK client '{}' running at {} Hz. Press Enter to stop.",
+        client.name(),
+        client.sample_rate()
+    );
+
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    client.stop()
+}
@@ -0,0 +1,107 @@
+use oxidizer::error::Result;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+// Command-line players tried in order, with the arguments that make them play a WAV
+// file once and exit quietly.
+const PLAYERS: &[&[&str]] = &[
+    &["pw-play"],
+    &["paplay"],
+    &["aplay", "-q"],
+    &["afplay"],
+    &["ffplay", "-nodisp", "-autoexit", "-loglevel", "quiet"],
+];
+
+/// Plays a WAV file on a loop through whichever command-line audio player is installed.
+///
+/// Shelling out keeps the binary free of audio device libraries; the file is simply
+/// started again each time the player exits.
+pub struct Player {
+    command: Option<&'static [&'static str]>,
+    path: PathBuf,
+    child: Option<Child>,
+    started: Instant,
+}
+
+impl Player {
+    /// Looks up an installed player for `path`. Playback is unavailable if none is found.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            command: PLAYERS.iter().copied().find(|command| on_path(command[0])),
+            path,
+            child: None,
+            started: Instant::now(),
+        }
+    }
+
+    /// Name of the player program, if one was found.
+    pub fn name(&self) -> Option<&'static str> {
+        self.command.map(|command| command[0])
+    }
+
+    /// File the player loops.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.child.is_some()
+    }
+
+    /// Starts playing from the top, restarting if already playing.
+    pub fn play(&mut self) -> Result<()> {
+        self.stop();
+        let Some(command) = self.command else {
+            return Ok(());
+        };
+
+        let child = Command::new(command[0])
+            .args(&command[1..])
+            .arg(&self.path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        self.child = Some(child);
+        self.started = Instant::now();
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            // The player may have exited on its own already
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+
+    /// Restarts the file once the player reaches its end. Call this regularly.
+    pub fn tick(&mut self) -> Result<()> {
+        let finished = match &mut self.child {
+            Some(child) => child.try_wait()?.is_some(),
+            None => false,
+        };
+        if finished {
+            self.play()?;
+        }
+        Ok(())
+    }
+
+    /// Time since the file was last started, an estimate of the playback position.
+    pub fn position(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+// Returns `true` if `program` is an executable file in one of the PATH directories.
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
@@ -0,0 +1,16 @@
+use super::list::ListTopic;
+use clap::Subcommand;
+use oxidizer::error::Result;
+
+/// Subcommands of the `presets` command.
+#[derive(Subcommand, Debug, Clone)]
+pub enum PresetsCommand {
+    /// List the built-in presets.
+    List,
+}
+
+pub fn run(command: &PresetsCommand) -> Result<()> {
+    match command {
+        PresetsCommand::List => super::list::run(Some(ListTopic::Presets)),
+    }
+}
@@ -0,0 +1,43 @@
+use super::settings::SettingsArgs;
+use super::time::parse_time;
+use clap::Args;
+use oxidizer::Pipeline;
+use oxidizer::error::Result;
+use oxidizer::io::TimeRange;
+use std::path::Path;
+use std::time::Duration;
+
+/// Arguments of the `preview` command.
+#[derive(Args, Debug, Clone)]
+pub struct PreviewArgs {
+    /// Path to the input file.
+    #[arg(short, long)]
+    pub input: String,
+
+    /// Path where the preview file will be saved. A .flac extension selects FLAC output.
+    #[arg(short, long, default_value = "preview.wav")]
+    pub output: String,
+
+    /// Length of the rendered excerpt (e.g. 15, 30s, 1:00).
+    #[arg(long, value_parser = parse_time, default_value = "15")]
+    pub seconds: Duration,
+
+    /// Where the excerpt starts in the input (e.g. 90, 1:23, 2m).
+    #[arg(long, value_parser = parse_time, default_value = "0")]
+    pub start: Duration,
+
+    #[command(flatten)]
+    pub settings: SettingsArgs,
+}
+
+pub fn run(args: &PreviewArgs) -> Result<()> {
+    let mut config = args.settings.resolve()?;
+    args.settings
+        .apply_output_format(&mut config, Path::new(&args.output))?;
+
+    // Only the excerpt is decoded, so previews stay fast on long recordings
+    let mut pipeline = Pipeline::new(config);
+    pipeline.set_range(TimeRange::new(args.start, Some(args.seconds)));
+    super::run_with_progress(pipeline, Path::new(&args.input), Path::new(&args.output))?;
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use indicatif::{ProgressBar, ProgressState, ProgressStyle};
+use oxidizer::pipeline::{Progress, Stage};
+use std::fmt::Write;
+use std::sync::Mutex;
+
+/// Renders pipeline progress on stderr as one bar per stage, with an ETA and the
+/// throughput as a multiple of realtime.
+pub struct ProgressDisplay {
+    sample_rate: u32,
+    passes: u32,
+    current: Mutex<Option<(Stage, ProgressBar)>>,
+}
+
+impl ProgressDisplay {
+    /// `sample_rate` is the input's rate, used to express throughput in realtime multiples.
+    /// `passes` is the number of filter passes the processing stage runs.
+    pub fn new(sample_rate: u32, passes: u32) -> Self {
+        Self {
+            sample_rate,
+            passes,
+            current: Mutex::new(None),
+        }
+    }
+
+    /// Updates the bar of the reported stage, starting a new one when the stage changes.
+    pub fn update(&self, progress: Progress) {
+        let mut current = self.current.lock().unwrap();
+
+        let stage_changed = current
+            .as_ref()
+            .is_none_or(|(stage, _)| *stage != progress.stage);
+        if stage_changed {
+            if let Some((_, bar)) = current.take() {
+                bar.finish();
+            }
+            *current = Some((progress.stage, self.stage_bar(&progress)));
+        }
+
+        if let Some((_, bar)) = current.as_ref() {
+            bar.set_position(progress.processed_frames);
+        }
+    }
+
+    /// Completes the last bar, leaving it on screen.
+    pub fn finish(&self) {
+        if let Some((_, bar)) = self.current.lock().unwrap().take() {
+            bar.finish();
+        }
+    }
+
+    /// Stops the last bar where it is, e.g. after an error.
+    pub fn abandon(&self) {
+        if let Some((_, bar)) = self.current.lock().unwrap().take() {
+            bar.abandon();
+        }
+    }
+
+    fn stage_bar(&self, progress: &Progress) -> ProgressBar {
+        // Processing counts every filter pass plus the noise stage over the buffer
+        let frames_per_unit = match progress.stage {
+            Stage::Processing => 1.0 / (self.passes as f64 + 1.0),
+            _ => 1.0,
+        };
+        let seconds_per_unit = frames_per_unit / self.sample_rate.max(1) as f64;
+        let realtime = move |state: &ProgressState, w: &mut dyn Write| {
+            let _ = write!(w, "{:.1}x realtime", state.per_sec() * seconds_per_unit);
+        };
+
+        let (bar, template) = if progress.total_frames > 0 {
+            (
+                ProgressBar::new(progress.total_frames),
+                "{prefix:>10} [{bar:30}] {percent:>3}%  {realtime}  ETA {eta}",
+            )
+        } else {
+            // Unsized streams: no bar or ETA, just the throughput
+            (
+                ProgressBar::new_spinner(),
+                "{prefix:>10} {spinner}  {realtime}",
+            )
+        };
+
+        let style = ProgressStyle::with_template(template)
+            .expect("progress template is valid")
+            .progress_chars("=> ")
+            .with_key("realtime", realtime);
+        bar.set_style(style);
+        bar.set_prefix(format!("{:?}", progress.stage));
+        bar
+    }
+}
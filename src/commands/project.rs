@@ -0,0 +1,56 @@
+use clap::Subcommand;
+use oxidizer::OxidizerError;
+use oxidizer::batch::{self, OverwritePolicy};
+use oxidizer::error::Result;
+use oxidizer::project::Project;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Subcommands of the `project` command.
+#[derive(Subcommand, Debug, Clone)]
+pub enum ProjectCommand {
+    /// Render a project file (.oxproj) to the output it names.
+    Render {
+        /// The project file.
+        file: PathBuf,
+
+        /// Overwrite the output file if it exists.
+        #[arg(short, long)]
+        force: bool,
+    },
+}
+
+pub fn run(command: &ProjectCommand) -> Result<()> {
+    match command {
+        ProjectCommand::Render { file, force } => render(file, *force),
+    }
+}
+
+fn render(file: &Path, force: bool) -> Result<()> {
+    let project = Project::load(file)?;
+    let policy = match force {
+        true => OverwritePolicy::Overwrite,
+        false => OverwritePolicy::Refuse,
+    };
+    if project.inputs.is_empty() {
+        return Err(OxidizerError::InvalidValue(format!(
+            "{} has no inputs",
+            file.display()
+        )));
+    }
+    let output = batch::resolve_output(&project.export.output, policy, &HashSet::new())
+        .inspect_err(|error| {
+            if let OxidizerError::OutputExists(_) = error {
+                tracing::info!("Pass --force to overwrite it.");
+            }
+        })?;
+
+    tracing::info!(
+        "Rendering {} ({} input(s)) to {}",
+        file.display(),
+        project.inputs.len(),
+        output.display()
+    );
+    super::run_joined_with_progress(project.pipeline(), &project.inputs, &output)?;
+    Ok(())
+}
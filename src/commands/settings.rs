@@ -0,0 +1,843 @@
+use super::automate::{parse_automation, parse_drift, parse_follow};
+use super::time::parse_time;
+use clap::Args;
+use oxidizer::OxidizerError;
+use oxidizer::artifacts::{ArtifactEvent, ArtifactKind, RandomArtifacts};
+use oxidizer::automation::{Automation, AutomationTarget};
+use oxidizer::batch;
+use oxidizer::codec::{Companding, LossyCodec, TelephoneLine};
+use oxidizer::dynamics::{
+    Compressor, Expander, Gate, GateMode, Limiter, NoiseDucking, TransientShaper,
+};
+use oxidizer::eq::{BandKind, EqBand};
+use oxidizer::error::Result;
+use oxidizer::hum::GroundLoop;
+use oxidizer::io::{AudioFormat, BitDepth};
+use oxidizer::modulation::{Drift, EnvelopeResponse, EnvelopeSource, Lfo, LfoShape};
+use oxidizer::overlay::Overlay;
+use oxidizer::presets::{self, Preset};
+use oxidizer::processor::IntensityCurve;
+use oxidizer::resample::ResampleQuality;
+use oxidizer::speed::{Chorus, Flutter, RecordSpeed, Wobble};
+use oxidizer::stereo::{Channel, Crossfeed, Haas};
+use oxidizer::{
+    ChannelConfig, NoiseConfig, NoiseRouting, Normalization, OxidationLevel, OxidizerConfig,
+    Segment,
+};
+use std::path::{Path, PathBuf};
+
+// True-peak ceiling used with --target-lufs, as most streaming platforms ask for.
+const DEFAULT_TRUE_PEAK: f32 = -1.0;
+
+// Ceiling of --smart-normalize when the settings don't have a peak ceiling to keep.
+const DEFAULT_SMART_CEILING: f32 = 0.95;
+
+// Amplitude of an --artifact without one.
+const DEFAULT_ARTIFACT_AMPLITUDE: f32 = 0.5;
+
+/// Processing settings shared by every command that renders audio.
+#[derive(Args, Debug, Clone)]
+pub struct SettingsArgs {
+    /// Load settings from a .toml or .json file. Flags given on the command line override it.
+    #[arg(short, long)]
+    pub config: Option<String>,
+
+    /// Start from a built-in preset: vinyl, gramophone, cassette, am-radio, telephone,
+    /// underwater.
+    /// Flags given on the command line override it.
+    #[arg(long, conflicts_with = "config")]
+    pub preset: Option<String>,
+
+    /// Start from a preset file (.toml or .json) saved with --save-preset.
+    #[arg(long, conflicts_with_all = ["config", "preset"])]
+    pub preset_file: Option<String>,
+
+    /// The level of oxidation: clear, dusty, worn, deep, buried, muffled, or an amount from 0.0 (clear) to 1.0 (muffled) in between. [default: deep]
+    #[arg(short, long, value_parser = str::parse::<OxidationLevel>)]
+    pub level: Option<OxidationLevel>,
+
+    /// The characteristic of the background hiss. 'brown' (bass-heavy), 'white' (full-spectrum) or 'crackle' (sparse ticks). [default: brown]
+    #[arg(short, long)]
+    pub noise: Option<String>,
+
+    /// Where the noise goes in: 'post-filter' keeps it bright over the muffled music, 'pre-filter' muffles it along with the music. [default: post-filter]
+    #[arg(long, value_name = "ROUTING", value_parser = NoiseRouting::try_from_str)]
+    pub noise_routing: Option<NoiseRouting>,
+
+    /// Duck the noise under the music, so the hiss swells in the gaps. Optionally how far
+    /// it drops while the music is loud, in dB. [default: 12]
+    #[arg(long, value_name = "DEPTH_DB", num_args = 0..=1, default_missing_value = "12")]
+    pub duck: Option<f32>,
+
+    /// How long the ducked noise takes to swell back after the music drops, in
+    /// milliseconds. [default: 500]
+    #[arg(long, value_name = "MS")]
+    pub duck_release: Option<f32>,
+
+    /// Scale of the noise and saturation effect. Typically 0.0 (subtle) to 1.0 (crushed). [default: 0.05]
+    #[arg(short = 't', long)]
+    pub intensity: Option<f32>,
+
+    /// How the intensity maps to the amount of noise: 'linear', 'log' or 'log:BASE'. Larger bases keep more of the range subtle. [default: log:10]
+    #[arg(long, value_name = "CURVE", value_parser = IntensityCurve::try_from_str)]
+    pub intensity_curve: Option<IntensityCurve>,
+
+    /// Sample rate for the output file. Should match the input for pitch consistency. Lower rates may result in a slowed down audio (pitch-shift). [default: 44100]
+    #[arg(short = 's', long)]
+    pub sample_rate: Option<u32>,
+
+    /// How inputs and overlays at another sample rate are converted: 'fast' interpolates
+    /// linearly, 'balanced' and 'best' use a windowed sinc that filters out aliasing. [default: fast]
+    #[arg(long, value_name = "QUALITY", value_parser = ResampleQuality::try_from_str)]
+    pub resample_quality: Option<ResampleQuality>,
+
+    /// Dry/wet mix: 0.0 keeps the original, 1.0 is fully oxidized, 0.6 blends the two. [default: 1.0]
+    #[arg(short, long)]
+    pub mix: Option<f32>,
+
+    /// Vary a parameter over the render: cutoff (Hz), intensity or mix, followed by
+    /// time=value breakpoints, e.g. 'intensity:0%=0.02,100%=0.4' or 'cutoff:0=8000,2m=400'.
+    /// Can be repeated for different parameters.
+    #[arg(long, value_name = "PARAM:POINTS", value_parser = parse_automation)]
+    pub automate: Vec<(AutomationTarget, Automation)>,
+
+    /// Make a parameter (cutoff, intensity or mix) react to the input level: scaled up by
+    /// AMOUNT dB for every dB the input is below -30 dBFS, and down above it.
+    /// E.g. 'intensity:0.5' brings the hiss up in quiet passages. Can be repeated.
+    #[arg(long, value_name = "PARAM:AMOUNT", value_parser = parse_follow, allow_hyphen_values = true)]
+    pub follow: Vec<EnvelopeResponse>,
+
+    /// Audio file whose level drives the --sidechain-follow responses, e.g. a voiceover.
+    #[arg(long, value_name = "FILE")]
+    pub sidechain: Option<PathBuf>,
+
+    /// Like --follow, but reacting to the --sidechain file. E.g. 'intensity:0.5' ducks
+    /// the crackle while the voiceover speaks. Can be repeated.
+    #[arg(long, value_name = "PARAM:AMOUNT", value_parser = parse_follow, allow_hyphen_values = true)]
+    pub sidechain_follow: Vec<EnvelopeResponse>,
+
+    /// Let a parameter (cutoff, intensity or mix) wander randomly between two bounds,
+    /// gliding to a new point every 8 seconds or the given SECONDS, e.g.
+    /// 'cutoff:2000..6000' or 'intensity:0.02..0.1:20'. Follows --seed. Can be repeated.
+    #[arg(long, value_name = "PARAM:MIN..MAX[:SECONDS]", value_parser = parse_drift)]
+    pub drift: Vec<Drift>,
+
+    /// Reshape the transients of the filtered program: gains in dB on attacks and on
+    /// sustains, e.g. '6:-3' for punchier, tighter drums.
+    #[arg(
+        long,
+        value_name = "ATTACK_DB:SUSTAIN_DB",
+        value_parser = parse_transients,
+        allow_hyphen_values = true
+    )]
+    pub transients: Option<TransientShaper>,
+
+    /// Add the noise between RIAA pre- and de-emphasis, like the surface of a record:
+    /// darker hiss, more rumble, and highs that saturate first.
+    #[arg(long)]
+    pub riaa: bool,
+
+    /// Gate the output while the input is below this level in dBFS, e.g. -50, so no hiss
+    /// plays over true silence. Opens in 1 ms, holds 50 ms and closes over 100 ms.
+    #[arg(long, value_name = "THRESHOLD_DB", allow_negative_numbers = true)]
+    pub gate: Option<f32>,
+
+    /// Gate the program before the noise is added instead, so quiet passages leave
+    /// only the noise.
+    #[arg(long, requires = "gate")]
+    pub gate_program: bool,
+
+    /// Compress the dynamics: threshold in dBFS and ratio, e.g. '-18:4'. Attack and
+    /// release default to 10 and 100 ms; set them in a config file.
+    #[arg(long, value_name = "THRESHOLD:RATIO", value_parser = parse_compressor, allow_hyphen_values = true)]
+    pub compress: Option<Compressor>,
+
+    /// Gain added after the compressor, in dB. [default: 0, or the config file's]
+    #[arg(long, allow_negative_numbers = true, requires = "compress")]
+    pub makeup: Option<f32>,
+
+    /// Expand the dynamics below a threshold in dBFS by a ratio, e.g. '-30:2' turns
+    /// every dB under -30 dBFS into two, restoring contrast after heavy oxidation.
+    #[arg(long, value_name = "THRESHOLD:RATIO", value_parser = parse_expander, allow_hyphen_values = true)]
+    pub expand: Option<Expander>,
+
+    /// Limit peaks to this ceiling in dBFS, e.g. -1.
+    #[arg(long, value_name = "CEILING_DB", allow_negative_numbers = true)]
+    pub limit: Option<f32>,
+
+    /// Add a pop, click, needle-skip or tape-splice at a point in the render, optionally
+    /// with its amplitude from 0 to 1, e.g. 'pop@1:23' or 'needle-skip@2m=0.8'. Can be
+    /// repeated.
+    #[arg(long, value_name = "KIND@TIME[=AMPLITUDE]", value_parser = parse_artifact)]
+    pub artifact: Vec<ArtifactEvent>,
+
+    /// Scatter this many artifacts of a kind over the render at random, e.g. 'pop:3'
+    /// for the few big pops of a record side. Follows --seed. Can be repeated.
+    #[arg(long, value_name = "KIND:COUNT", value_parser = parse_random_artifacts)]
+    pub random_artifacts: Vec<RandomArtifacts>,
+
+    /// Layer a recording (rain, room tone, a run-out groove) over the output, optionally
+    /// followed by its gain in dB, e.g. 'rain.wav:-18'. Looped to the length of the render.
+    /// Can be repeated.
+    #[arg(long, value_name = "FILE[:GAIN_DB]", value_parser = parse_overlay)]
+    pub overlay: Vec<Overlay>,
+
+    /// Play the --overlay recordings once instead of looping them.
+    #[arg(long, requires = "overlay")]
+    pub overlay_once: bool,
+
+    /// Process a section of the input with other settings: START..END (END may be left
+    /// out), '=' and a level or preset name, e.g. '0..30s=muffled' for a muffled intro
+    /// or '2:10..=am-radio'. A level keeps every other setting. Can be repeated.
+    #[arg(long, value_name = "START..END=SETTINGS", value_parser = parse_segment)]
+    pub segment: Vec<(Segment, String)>,
+
+    /// Send the render down a telephone line: the voice band, sampled at 8 kHz through an
+    /// 8-bit codec. Optionally the companding law, 'mu-law' or 'a-law'. [default: mu-law]
+    #[arg(
+        long,
+        value_name = "LAW",
+        num_args = 0..=1,
+        default_missing_value = "mu-law",
+        value_parser = Companding::try_from_str
+    )]
+    pub telephone: Option<Companding>,
+
+    /// Sound like a starved MP3 of the early 2000s: cut treble, swirling artifacts and
+    /// pre-echo. Optionally the bitrate it should sound like, in kbps. [default: 64]
+    #[arg(long, value_name = "KBPS", num_args = 0..=1, default_missing_value = "64")]
+    pub lossy: Option<u32>,
+
+    /// Wear one channel differently from the other, like a single worn tape head: the
+    /// channel, its cutoff shift in octaves (negative is darker) and optionally a gain on
+    /// its noise in dB, e.g. 'left:-1:6'. Can be given once per channel.
+    #[arg(long, value_name = "CHANNEL:OCTAVES[:NOISE_DB]", value_parser = parse_channel, allow_negative_numbers = true)]
+    pub channel: Vec<(Channel, ChannelConfig)>,
+
+    /// Fake stereo from a mono source before degrading it: the right channel is delayed
+    /// by this many milliseconds and dulled. [default: 15]
+    #[arg(long, value_name = "DELAY_MS", num_args = 0..=1, default_missing_value = "15")]
+    pub haas: Option<f32>,
+
+    /// Waver the playback speed like a worn turntable or tape transport, heard as a
+    /// shimmer on sustained notes. Optionally the largest speed deviation in percent.
+    /// [default: 0.1]
+    #[arg(long, value_name = "DEPTH_PERCENT", num_args = 0..=1, default_missing_value = "0.1")]
+    pub flutter: Option<f32>,
+
+    /// Swing the pitch once per revolution, like a record pressed with its hole off
+    /// center. Optionally the speed of the record: 33, 45 or 78 rpm. [default: 33]
+    #[arg(long, value_name = "RPM", num_args = 0..=1, default_missing_value = "33", value_parser = RecordSpeed::try_from_str)]
+    pub wobble: Option<RecordSpeed>,
+
+    /// Mix in a slightly late copy of the input whose delay slowly sweeps, blurring the
+    /// pitch like sound through water. Optionally the share of the copy from 0 to 1.
+    /// [default: 0.3]
+    #[arg(long, value_name = "MIX", num_args = 0..=1, default_missing_value = "0.3")]
+    pub chorus: Option<f32>,
+
+    /// Shape the tone with a filter band: 'high-pass:HZ', 'low-pass:HZ' or
+    /// 'peak:HZ:GAIN_DB', each optionally followed by a Q, e.g. 'peak:1250:6:4'. Can be
+    /// repeated; the bands are applied in order.
+    #[arg(long, value_name = "KIND:HZ[:GAIN_DB][:Q]", value_parser = parse_eq_band)]
+    pub eq: Vec<EqBand>,
+
+    /// Bleed each channel into the other, low-passed and slightly late, so hard-panned
+    /// mixes are comfortable on headphones. Optionally the level of the bleed in dB.
+    /// [default: -6]
+    #[arg(
+        long,
+        value_name = "LEVEL_DB",
+        num_args = 0..=1,
+        default_missing_value = "-6",
+        allow_negative_numbers = true
+    )]
+    pub crossfeed: Option<f32>,
+
+    /// Add mains hum that buzzes louder with the program, like a ground loop through a
+    /// dirty amp. Optionally its level below the program in dB. [default: -30]
+    #[arg(
+        long,
+        value_name = "LEVEL_DB",
+        num_args = 0..=1,
+        default_missing_value = "-30",
+        allow_negative_numbers = true
+    )]
+    pub ground_loop: Option<f32>,
+
+    /// Mains frequency of the hum in Hz, e.g. 60 for American mains. [default: 50]
+    #[arg(long, value_name = "HZ")]
+    pub hum_frequency: Option<f32>,
+
+    /// Level change of the hum's harmonics per octave in dB: near 0 buzzes, -12 and
+    /// below hums. [default: -6]
+    #[arg(long, value_name = "DB", allow_negative_numbers = true)]
+    pub hum_tilt: Option<f32>,
+
+    /// Render a seamless loop: the end carries on into the start, crossfaded over this
+    /// many milliseconds, so the file repeats without a click. [default: 50]
+    #[arg(
+        long = "loop",
+        value_name = "CROSSFADE_MS",
+        num_args = 0..=1,
+        default_missing_value = "50"
+    )]
+    pub loop_crossfade: Option<f32>,
+
+    /// Sweep the filter cutoff with an LFO: sine, triangle or random-walk.
+    #[arg(long)]
+    pub lfo: Option<String>,
+
+    /// LFO speed in cycles per second. [default: 0.5]
+    #[arg(long)]
+    pub lfo_rate: Option<f32>,
+
+    /// How far the LFO moves the cutoff either way, in octaves. [default: 1]
+    #[arg(long)]
+    pub lfo_depth: Option<f32>,
+
+    /// Output format: 'wav' or 'flac'. [default: from the output extension, else wav]
+    #[arg(long)]
+    pub format: Option<String>,
+
+    /// Output bit depth: 16, 24 or 32f (float, WAV only). [default: 16]
+    #[arg(long)]
+    pub bit_depth: Option<String>,
+
+    /// Number of filter iterations.
+    /// Each pass doubles the filter slope (e.g., from 6dB/oct to 12dB/oct). [default: 1]
+    #[arg(short, long)]
+    pub passes: Option<u32>,
+
+    /// Normalize to this integrated loudness (LUFS) instead of peak level, e.g. -16 for podcasts.
+    #[arg(long, allow_negative_numbers = true)]
+    pub target_lufs: Option<f32>,
+
+    /// True-peak ceiling (dBTP) for --target-lufs. [default: -1]
+    #[arg(long, allow_negative_numbers = true, requires = "target_lufs")]
+    pub true_peak: Option<f32>,
+
+    /// Reach the peak ceiling by setting the input level instead of scaling the output,
+    /// so the noise keeps its level. Costs an extra render to estimate the headroom.
+    #[arg(long, conflicts_with = "target_lufs")]
+    pub smart_normalize: bool,
+
+    /// Seed for the noise generator. Renders with the same seed and settings are byte-identical.
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+impl SettingsArgs {
+    /// Describes where the base settings come from, e.g. "preset 'vinyl'".
+    pub fn source(&self) -> String {
+        if let Some(path) = &self.config {
+            format!("config file {}", path)
+        } else if let Some(path) = &self.preset_file {
+            format!("preset file {}", path)
+        } else if let Some(name) = &self.preset {
+            format!("preset '{}'", name.to_lowercase())
+        } else {
+            "defaults".to_string()
+        }
+    }
+
+    /// Starts from the config file, preset or defaults and applies every flag given explicitly.
+    pub fn resolve(&self) -> Result<OxidizerConfig> {
+        // The base settings flags are applied on top of (the sources are mutually exclusive)
+        let mut config = if let Some(path) = &self.config {
+            OxidizerConfig::load(path)?
+        } else if let Some(path) = &self.preset_file {
+            Preset::load(path)?.config
+        } else if let Some(name) = &self.preset {
+            presets::find(name)
+                .ok_or_else(|| {
+                    OxidizerError::InvalidValue(format!(
+                        "Unknown preset: {} (available: {})",
+                        name,
+                        presets::names().collect::<Vec<_>>().join(", ")
+                    ))
+                })?
+                .config
+        } else {
+            OxidizerConfig::default()
+        };
+
+        if let Some(level) = self.level {
+            config.level = level;
+        }
+        if let Some(noise) = &self.noise {
+            config.noise = NoiseConfig::try_from_str(noise).map_err(OxidizerError::InvalidValue)?;
+        }
+        if let Some(routing) = self.noise_routing {
+            config.noise_routing = routing;
+        }
+        if let Some(depth_db) = self.duck {
+            config.noise_ducking = Some(NoiseDucking {
+                depth_db,
+                ..config.noise_ducking.unwrap_or_default()
+            });
+        }
+        if let Some(release_ms) = self.duck_release {
+            // The release adjusts the ducking of the flags, config file or preset
+            let ducking = config.noise_ducking.as_mut().ok_or_else(|| {
+                OxidizerError::InvalidValue("--duck-release needs ducking: add --duck".to_string())
+            })?;
+            ducking.release_ms = release_ms;
+        }
+        if let Some(intensity) = self.intensity {
+            config.intensity = intensity;
+        }
+        if let Some(curve) = &self.intensity_curve {
+            config.intensity_curve = curve.clone();
+        }
+        if let Some(mix) = self.mix {
+            config.mix = mix;
+        }
+        for (target, automation) in &self.automate {
+            config.automation.set(*target, automation.clone());
+        }
+        if !self.follow.is_empty() || !self.sidechain_follow.is_empty() {
+            let sidechain = self
+                .sidechain_follow
+                .iter()
+                .map(|response| EnvelopeResponse {
+                    source: EnvelopeSource::Sidechain,
+                    ..*response
+                });
+            config.envelope = self.follow.iter().copied().chain(sidechain).collect();
+        }
+        if let Some(sidechain) = &self.sidechain {
+            config.sidechain = Some(sidechain.clone());
+        }
+        if !self.drift.is_empty() {
+            config.drift = self.drift.clone();
+        }
+        if !self.artifact.is_empty() {
+            config.artifacts.events = self.artifact.clone();
+        }
+        if !self.random_artifacts.is_empty() {
+            config.artifacts.random = self.random_artifacts.clone();
+        }
+        if !self.channel.is_empty() {
+            // Channels without a flag keep what the config file or preset gave them
+            let mut channels = config.channels.unwrap_or_default();
+            for (channel, settings) in &self.channel {
+                channels[channel.index()] = *settings;
+            }
+            config.channels = Some(channels);
+        }
+        if let Some(law) = self.telephone {
+            config.telephone = Some(TelephoneLine {
+                law,
+                ..config.telephone.unwrap_or_default()
+            });
+        }
+        if let Some(kbps) = self.lossy {
+            config.lossy = Some(LossyCodec::at_bitrate(kbps));
+        }
+        if let Some(delay_ms) = self.haas {
+            config.haas = Some(Haas {
+                delay_ms,
+                ..config.haas.unwrap_or_default()
+            });
+        }
+        if let Some(depth_percent) = self.flutter {
+            config.flutter = Some(Flutter {
+                depth_percent,
+                ..config.flutter.unwrap_or_default()
+            });
+        }
+        if let Some(speed) = self.wobble {
+            config.wobble = Some(Wobble {
+                speed,
+                ..config.wobble.unwrap_or_default()
+            });
+        }
+        if let Some(mix) = self.chorus {
+            config.chorus = Some(Chorus {
+                mix,
+                ..config.chorus.unwrap_or_default()
+            });
+        }
+        if !self.eq.is_empty() {
+            config.eq = self.eq.clone();
+        }
+        if let Some(level_db) = self.crossfeed {
+            config.crossfeed = Some(Crossfeed {
+                level_db,
+                ..config.crossfeed.unwrap_or_default()
+            });
+        }
+        if let Some(level_db) = self.ground_loop {
+            config.ground_loop = Some(GroundLoop {
+                level_db,
+                ..config.ground_loop.unwrap_or_default()
+            });
+        }
+        if self.hum_frequency.is_some() || self.hum_tilt.is_some() {
+            // Frequency and tilt adjust the ground loop of the flags, config file or preset
+            let ground_loop = config.ground_loop.as_mut().ok_or_else(|| {
+                OxidizerError::InvalidValue(
+                    "--hum-frequency and --hum-tilt need a ground loop: add --ground-loop"
+                        .to_string(),
+                )
+            })?;
+            if let Some(frequency_hz) = self.hum_frequency {
+                ground_loop.frequency_hz = frequency_hz;
+            }
+            if let Some(tilt_db) = self.hum_tilt {
+                ground_loop.tilt_db = tilt_db;
+            }
+        }
+        if let Some(ms) = self.loop_crossfade {
+            config.loop_crossfade_ms = Some(ms);
+        }
+        if self.riaa {
+            config.riaa = true;
+        }
+        if let Some(shaper) = self.transients {
+            config.transients = Some(shaper);
+        }
+        if let Some(threshold_db) = self.gate {
+            let mode = match self.gate_program {
+                true => GateMode::Program,
+                false => GateMode::Output,
+            };
+            config.gate = Some(Gate {
+                threshold_db,
+                mode,
+                ..config.gate.unwrap_or_default()
+            });
+        }
+        if let Some(Compressor {
+            threshold_db,
+            ratio,
+            ..
+        }) = self.compress
+        {
+            let compressor = config.compressor.unwrap_or_default();
+            config.compressor = Some(Compressor {
+                threshold_db,
+                ratio,
+                makeup_db: self.makeup.unwrap_or(compressor.makeup_db),
+                ..compressor
+            });
+        }
+        if let Some(Expander {
+            threshold_db,
+            ratio,
+            ..
+        }) = self.expand
+        {
+            config.expander = Some(Expander {
+                threshold_db,
+                ratio,
+                ..config.expander.unwrap_or_default()
+            });
+        }
+        if let Some(ceiling_db) = self.limit {
+            config.limiter = Some(Limiter {
+                ceiling_db,
+                ..config.limiter.unwrap_or_default()
+            });
+        }
+        if !self.overlay.is_empty() {
+            config.overlay = self
+                .overlay
+                .iter()
+                .map(|overlay| Overlay {
+                    loop_if_shorter: !self.overlay_once,
+                    ..overlay.clone()
+                })
+                .collect();
+        }
+        if let Some(shape) = &self.lfo {
+            let shape = LfoShape::try_from_str(shape).map_err(OxidizerError::InvalidValue)?;
+            config.lfo = Some(Lfo {
+                shape,
+                ..config.lfo.unwrap_or_default()
+            });
+        }
+        if self.lfo_rate.is_some() || self.lfo_depth.is_some() {
+            // Rate and depth adjust the LFO of the flags, config file or preset
+            let lfo = config.lfo.as_mut().ok_or_else(|| {
+                OxidizerError::InvalidValue(
+                    "--lfo-rate and --lfo-depth need an LFO: \
+                     add --lfo sine, triangle or random-walk"
+                        .to_string(),
+                )
+            })?;
+            if let Some(rate) = self.lfo_rate {
+                lfo.rate_hz = rate;
+            }
+            if let Some(depth) = self.lfo_depth {
+                lfo.depth = depth;
+            }
+        }
+        if let Some(sample_rate) = self.sample_rate {
+            config.sample_rate = sample_rate;
+        }
+        if let Some(quality) = self.resample_quality {
+            config.resample_quality = quality;
+        }
+        if let Some(format) = &self.format {
+            config.format =
+                AudioFormat::try_from_str(format).map_err(OxidizerError::InvalidValue)?;
+        }
+        if let Some(bit_depth) = &self.bit_depth {
+            config.bit_depth =
+                BitDepth::try_from_str(bit_depth).map_err(OxidizerError::InvalidValue)?;
+        }
+        if let Some(passes) = self.passes {
+            config.passes = passes;
+        }
+        if let Some(target_lufs) = self.target_lufs {
+            config.normalization = Normalization::Loudness {
+                target_lufs,
+                true_peak: self.true_peak.unwrap_or(DEFAULT_TRUE_PEAK),
+            };
+        }
+        if self.smart_normalize {
+            let ceiling = match config.normalization {
+                Normalization::Peak { ceiling } | Normalization::Smart { ceiling } => ceiling,
+                _ => DEFAULT_SMART_CEILING,
+            };
+            config.normalization = Normalization::Smart { ceiling };
+        }
+        if let Some(seed) = self.seed {
+            config.seed = Some(seed);
+        }
+        if !self.segment.is_empty() {
+            // Resolved last, so a level segment inherits every other flag
+            config.segments = self
+                .segment
+                .iter()
+                .map(|(segment, settings)| {
+                    Ok(Segment {
+                        config: Box::new(segment_settings(&config, settings)?),
+                        ..segment.clone()
+                    })
+                })
+                .collect::<Result<_>>()?;
+        }
+
+        Ok(config)
+    }
+
+    /// Picks the output format from the output path's extension when --format wasn't
+    /// given, and checks that the format can store the configured bit depth.
+    pub fn apply_output_format(&self, config: &mut OxidizerConfig, output: &Path) -> Result<()> {
+        if self.format.is_none()
+            && let Some(format) = AudioFormat::from_path(output)
+        {
+            config.format = format;
+        }
+
+        config.format.check(config.bit_depth)?;
+        config.validate()
+    }
+
+    /// Like [`Self::apply_output_format`] for a batch output template. The default
+    /// template takes the extension of the configured format.
+    pub fn apply_output_template(
+        &self,
+        config: &mut OxidizerConfig,
+        template: String,
+    ) -> Result<String> {
+        if template == batch::DEFAULT_TEMPLATE {
+            config.format.check(config.bit_depth)?;
+            return Ok(batch::default_template(config.format));
+        }
+
+        self.apply_output_format(config, Path::new(&template))?;
+        Ok(template)
+    }
+}
+
+// Parses a `--compress` value: a threshold in dBFS and a ratio.
+fn parse_compressor(s: &str) -> std::result::Result<Compressor, String> {
+    let (threshold_db, ratio) = parse_threshold_ratio(s, "compressor")?;
+    Ok(Compressor {
+        threshold_db,
+        ratio,
+        ..Default::default()
+    })
+}
+
+// Parses an `--expand` value: a threshold in dBFS and a ratio.
+fn parse_expander(s: &str) -> std::result::Result<Expander, String> {
+    let (threshold_db, ratio) = parse_threshold_ratio(s, "expander")?;
+    Ok(Expander {
+        threshold_db,
+        ratio,
+        ..Default::default()
+    })
+}
+
+// Parses a `--transients` value: gains in dB on attacks and sustains.
+fn parse_transients(s: &str) -> std::result::Result<TransientShaper, String> {
+    let invalid = || format!("Invalid transient gains: {} (try e.g. 6:-3)", s);
+    let (attack, sustain) = s.split_once(':').ok_or_else(invalid)?;
+    let attack_db: f32 = attack.trim().parse().map_err(|_| invalid())?;
+    let sustain_db: f32 = sustain.trim().parse().map_err(|_| invalid())?;
+    if !attack_db.is_finite() || !sustain_db.is_finite() {
+        return Err(invalid());
+    }
+    Ok(TransientShaper::new(attack_db, sustain_db))
+}
+
+fn parse_threshold_ratio(s: &str, what: &str) -> std::result::Result<(f32, f32), String> {
+    let invalid = || format!("Invalid {}: {} (try e.g. -18:4)", what, s);
+    let (threshold, ratio) = s.split_once(':').ok_or_else(invalid)?;
+    let threshold_db: f32 = threshold.trim().parse().map_err(|_| invalid())?;
+    let ratio: f32 = ratio.trim().parse().map_err(|_| invalid())?;
+    if ratio < 1.0 || ratio.is_nan() || !threshold_db.is_finite() {
+        return Err(format!(
+            "Invalid {}: {} (the ratio must be at least 1)",
+            what, s
+        ));
+    }
+    Ok((threshold_db, ratio))
+}
+
+// Parses an `--overlay` value: a path, optionally followed by a colon and a gain in dB.
+fn parse_overlay(s: &str) -> std::result::Result<Overlay, String> {
+    // A colon followed by something other than a number belongs to the path
+    let (path, gain_db) = match s
+        .rsplit_once(':')
+        .map(|(p, g)| (p, g.trim().parse::<f32>()))
+    {
+        Some((path, Ok(gain))) if !path.is_empty() => (path, gain),
+        _ => (s, 0.0),
+    };
+    if !gain_db.is_finite() {
+        return Err(format!("Invalid overlay gain: {}", gain_db));
+    }
+    Ok(Overlay::new(PathBuf::from(path), gain_db, true))
+}
+
+// Parses an `--eq` value: a band kind, a frequency, a gain for peaks and optionally a Q.
+fn parse_eq_band(s: &str) -> std::result::Result<EqBand, String> {
+    let invalid = || {
+        format!(
+            "Invalid EQ band: {} (try e.g. high-pass:200 or peak:1250:6:4)",
+            s
+        )
+    };
+    let mut parts = s.split(':');
+    let kind = BandKind::try_from_str(parts.next().unwrap_or_default().trim())?;
+    let mut numbers = parts.map(|p| p.trim().parse::<f32>().map_err(|_| invalid()));
+    let frequency_hz = numbers.next().ok_or_else(invalid)??;
+    let mut band = match kind {
+        BandKind::Peak => EqBand {
+            gain_db: numbers.next().ok_or_else(invalid)??,
+            ..EqBand::pass(kind, frequency_hz)
+        },
+        _ => EqBand::pass(kind, frequency_hz),
+    };
+    if let Some(q) = numbers.next() {
+        band.q = q?;
+    }
+    if numbers.next().is_some() || !band.frequency_hz.is_finite() || !band.gain_db.is_finite() {
+        return Err(invalid());
+    }
+    Ok(band)
+}
+
+// Parses a `--channel` value: a channel, a cutoff shift and optionally a noise gain.
+fn parse_channel(s: &str) -> std::result::Result<(Channel, ChannelConfig), String> {
+    let invalid = || format!("Invalid channel settings: {} (try e.g. left:-1:6)", s);
+    let (channel, settings) = s.split_once(':').ok_or_else(invalid)?;
+    let (octaves, noise_db) = settings.split_once(':').unwrap_or((settings, "0"));
+    let cutoff_octaves: f32 = octaves.trim().parse().map_err(|_| invalid())?;
+    let noise_db: f32 = noise_db.trim().parse().map_err(|_| invalid())?;
+    if !cutoff_octaves.is_finite() || !noise_db.is_finite() {
+        return Err(invalid());
+    }
+    Ok((
+        Channel::try_from_str(channel.trim())?,
+        ChannelConfig {
+            cutoff_octaves,
+            noise_db,
+        },
+    ))
+}
+
+// Parses an `--artifact` value: a kind, a time and optionally an amplitude.
+fn parse_artifact(s: &str) -> std::result::Result<ArtifactEvent, String> {
+    let invalid = || {
+        format!(
+            "Invalid artifact: {} (try e.g. pop@1:23 or click@90=0.3)",
+            s
+        )
+    };
+    let (kind, at) = s.split_once('@').ok_or_else(invalid)?;
+    let (time, amplitude) = match at.split_once('=') {
+        Some((time, amplitude)) => (time, amplitude.trim().parse().map_err(|_| invalid())?),
+        None => (at, DEFAULT_ARTIFACT_AMPLITUDE),
+    };
+    Ok(ArtifactEvent::new(
+        ArtifactKind::try_from_str(kind.trim())?,
+        parse_time(time)?.as_secs_f64(),
+        amplitude,
+    ))
+}
+
+// Parses a `--random-artifacts` value: a kind and a count.
+fn parse_random_artifacts(s: &str) -> std::result::Result<RandomArtifacts, String> {
+    let invalid = || format!("Invalid random artifacts: {} (try e.g. pop:3)", s);
+    let (kind, count) = s.split_once(':').ok_or_else(invalid)?;
+    let count = count.trim().parse().map_err(|_| invalid())?;
+    Ok(RandomArtifacts::new(
+        ArtifactKind::try_from_str(kind.trim())?,
+        count,
+    ))
+}
+
+// Parses a `--segment` value into its time span and the name of its settings, which are
+// resolved once the main settings are known.
+fn parse_segment(s: &str) -> std::result::Result<(Segment, String), String> {
+    let invalid = || format!("Invalid segment: {} (try e.g. 0..30s=muffled)", s);
+    let (span, settings) = s.rsplit_once('=').ok_or_else(invalid)?;
+    let (start, end) = span.split_once("..").ok_or_else(invalid)?;
+
+    let start = match start.trim() {
+        "" => 0.0,
+        start => parse_time(start)?.as_secs_f64(),
+    };
+    let end = match end.trim() {
+        "" => None,
+        end => Some(parse_time(end)?.as_secs_f64()),
+    };
+    if end.is_some_and(|end| end <= start) {
+        return Err(format!("Invalid segment: {} ends before it starts", s));
+    }
+
+    let segment = Segment {
+        start_secs: start,
+        end_secs: end,
+        config: Box::default(),
+    };
+    Ok((segment, settings.trim().to_string()))
+}
+
+// The settings of a segment: the main ones at another level, or a preset.
+fn segment_settings(config: &OxidizerConfig, name: &str) -> Result<OxidizerConfig> {
+    if let Ok(level) = name.parse::<OxidationLevel>() {
+        return Ok(OxidizerConfig {
+            level,
+            segments: Vec::new(),
+            ..config.clone()
+        });
+    }
+
+    presets::find(name)
+        .map(|preset| preset.config)
+        .ok_or_else(|| {
+            OxidizerError::InvalidValue(format!(
+                "Unknown segment settings: {} (use a level, an amount or a preset: {})",
+                name,
+                presets::names().collect::<Vec<_>>().join(", ")
+            ))
+        })
+}
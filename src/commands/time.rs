@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+/// Parses a point or length in time for the command line.
+///
+/// Accepts plain seconds (`90`, `12.5`), a unit suffix (`500ms`, `30s`, `2m`, `1h`)
+/// or a clock position (`1:23`, `1:02:03.5`).
+pub fn parse_time(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let invalid = || format!("Invalid time: {} (try e.g. 90, 30s, 1:23)", s);
+
+    let seconds = if s.contains(':') {
+        // [h:]m:s, each part scaled by 60 relative to the next one
+        let parts: Vec<&str> = s.split(':').collect();
+        if parts.len() > 3 {
+            return Err(invalid());
+        }
+        parts.iter().try_fold(0.0, |total, part| {
+            part.parse::<f64>()
+                .map(|value| total * 60.0 + value)
+                .map_err(|_| invalid())
+        })?
+    } else {
+        let (number, scale) = if let Some(n) = s.strip_suffix("ms") {
+            (n, 0.001)
+        } else if let Some(n) = s.strip_suffix('s') {
+            (n, 1.0)
+        } else if let Some(n) = s.strip_suffix('m') {
+            (n, 60.0)
+        } else if let Some(n) = s.strip_suffix('h') {
+            (n, 3600.0)
+        } else {
+            (s, 1.0)
+        };
+        number.trim().parse::<f64>().map_err(|_| invalid())? * scale
+    };
+
+    Duration::try_from_secs_f64(seconds).map_err(|_| invalid())
+}
@@ -0,0 +1,547 @@
+use super::player::Player;
+use super::settings::SettingsArgs;
+use super::time::parse_time;
+use clap::Args;
+use oxidizer::analysis::Levels;
+use oxidizer::error::Result;
+use oxidizer::io::{self, AudioFormat, BitDepth, TimeRange};
+use oxidizer::{
+    CancellationToken, NoiseConfig, OxidationLevel, OxidizerConfig, OxidizerError, Pipeline,
+    Progress, RunReport, Stage,
+};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::symbols::Marker;
+use ratatui::text::Line;
+use ratatui::widgets::canvas::{self, Canvas};
+use ratatui::widgets::{Block, LineGauge, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+// How long the event loop waits for a key before refreshing the screen.
+const TICK: Duration = Duration::from_millis(50);
+
+// Number of columns the waveform overview is reduced to.
+const WAVEFORM_COLUMNS: usize = 240;
+
+// Lowest level shown on the meters.
+const METER_FLOOR_DB: f32 = -60.0;
+
+const INTENSITY_STEP: f32 = 0.01;
+const MIX_STEP: f32 = 0.05;
+const MAX_PASSES: u32 = 8;
+
+/// Arguments of the `tui` command.
+#[derive(Args, Debug, Clone)]
+pub struct TuiArgs {
+    /// Path to the input file.
+    #[arg(short, long)]
+    pub input: String,
+
+    /// Path the export is written to. A .flac extension selects FLAC output.
+    #[arg(short, long, default_value = "output.wav")]
+    pub output: String,
+
+    /// Where the auditioned excerpt starts in the input (e.g. 90, 1:23, 2m).
+    #[arg(long, value_parser = parse_time, default_value = "0")]
+    pub start: Duration,
+
+    /// Length of the auditioned excerpt (e.g. 10, 30s, 1:00).
+    #[arg(long, value_parser = parse_time, default_value = "10")]
+    pub seconds: Duration,
+
+    #[command(flatten)]
+    pub settings: SettingsArgs,
+}
+
+pub fn run(args: &TuiArgs) -> Result<()> {
+    let mut config = args.settings.resolve()?;
+    let output = PathBuf::from(&args.output);
+    args.settings.apply_output_format(&mut config, &output)?;
+
+    let input = PathBuf::from(&args.input);
+    let info = io::probe(&input)?;
+    let length = args.seconds.max(Duration::from_millis(100));
+    let range = TimeRange::new(args.start, Some(length));
+    let excerpt = io::load_audio_range(&input, range)?;
+    if excerpt.is_empty() {
+        return Err(OxidizerError::InvalidValue(format!(
+            "Nothing to audition: {} ends before {:.1}s",
+            input.display(),
+            args.start.as_secs_f32()
+        )));
+    }
+
+    let mut app = App::new(
+        input,
+        output,
+        config,
+        info.sample_rate.unwrap_or(44100),
+        excerpt,
+        range,
+    )?;
+    ratatui::run(|terminal| app.run(terminal))
+}
+
+// The settings that can be adjusted from the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Param {
+    Level,
+    Intensity,
+    Noise,
+    Mix,
+    Passes,
+}
+
+impl Param {
+    const ALL: [Param; 5] = [
+        Param::Level,
+        Param::Intensity,
+        Param::Noise,
+        Param::Mix,
+        Param::Passes,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Param::Level => "Level",
+            Param::Intensity => "Intensity",
+            Param::Noise => "Noise",
+            Param::Mix => "Mix",
+            Param::Passes => "Passes",
+        }
+    }
+}
+
+// An export running on a background thread.
+struct Export {
+    handle: JoinHandle<Result<RunReport>>,
+    progress: Arc<Mutex<Option<Progress>>>,
+    cancellation: CancellationToken,
+}
+
+struct App {
+    input: PathBuf,
+    output: PathBuf,
+    config: OxidizerConfig,
+    sample_rate: u32,
+    // Noise settings cycled through; a brown preset keeps its own damping and step
+    noises: [NoiseConfig; 3],
+    excerpt: Vec<f32>,
+    // Where the excerpt lies in the input, so a sidechain lines up with it
+    range: TimeRange,
+    // Per-column (min, max) of the processed excerpt
+    waveform: Vec<(f32, f32)>,
+    before: Levels,
+    after: Levels,
+    selected: usize,
+    player: Player,
+    export: Option<Export>,
+    confirm_overwrite: bool,
+    status: String,
+    quit: bool,
+}
+
+impl App {
+    fn new(
+        input: PathBuf,
+        output: PathBuf,
+        config: OxidizerConfig,
+        sample_rate: u32,
+        excerpt: Vec<f32>,
+        range: TimeRange,
+    ) -> Result<Self> {
+        let preview = format!("oxidizer-tui-{}.wav", std::process::id());
+        let player = Player::new(std::env::temp_dir().join(preview));
+        let status = match player.name() {
+            Some(name) => format!("Press space to loop the excerpt (plays through {})", name),
+            None => "No audio player found (pw-play, paplay, aplay, afplay or ffplay), \
+                     playback is disabled"
+                .to_string(),
+        };
+        let noises = NoiseConfig::all().map(|noise| {
+            if noise.name() == config.noise.name() {
+                config.noise
+            } else {
+                noise
+            }
+        });
+
+        let mut app = Self {
+            input,
+            output,
+            before: Levels::measure(&excerpt, sample_rate),
+            after: Levels::measure(&excerpt, sample_rate),
+            config,
+            sample_rate,
+            noises,
+            excerpt,
+            range,
+            waveform: Vec::new(),
+            selected: 0,
+            player,
+            export: None,
+            confirm_overwrite: false,
+            status,
+            quit: false,
+        };
+        app.render()?;
+        Ok(app)
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if event::poll(TICK)?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_key(key.code)?;
+            }
+
+            self.player.tick()?;
+            self.poll_export();
+        }
+
+        self.player.stop();
+        let _ = std::fs::remove_file(self.player.path());
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyCode) -> Result<()> {
+        if let Some(export) = &self.export {
+            // Only cancelling is allowed while exporting
+            if matches!(key, KeyCode::Char('q') | KeyCode::Esc) {
+                export.cancellation.cancel();
+            }
+            return Ok(());
+        }
+
+        if key != KeyCode::Char('e') {
+            self.confirm_overwrite = false;
+        }
+
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = (self.selected + Param::ALL.len() - 1) % Param::ALL.len();
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1) % Param::ALL.len();
+            }
+            KeyCode::Left | KeyCode::Char('h') => self.adjust(-1)?,
+            KeyCode::Right | KeyCode::Char('l') => self.adjust(1)?,
+            KeyCode::Char(' ') => self.toggle_playback()?,
+            KeyCode::Char('e') => self.start_export(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Moves the selected setting one step up or down and renders the excerpt again.
+    fn adjust(&mut self, direction: i32) -> Result<()> {
+        let config = &mut self.config;
+        match Param::ALL[self.selected] {
+            Param::Level => {
+                config.level = cycle(&OxidationLevel::ALL, &config.level, direction);
+            }
+            Param::Intensity => {
+                config.intensity =
+                    (config.intensity + direction as f32 * INTENSITY_STEP).clamp(0.0, 1.0);
+            }
+            Param::Noise => {
+                config.noise = cycle(&self.noises, &config.noise, direction);
+            }
+            Param::Mix => {
+                config.mix = (config.mix + direction as f32 * MIX_STEP).clamp(0.0, 1.0);
+            }
+            Param::Passes => {
+                config.passes = config
+                    .passes
+                    .saturating_add_signed(direction)
+                    .min(MAX_PASSES);
+            }
+        }
+
+        self.render()
+    }
+
+    // Processes the excerpt with the current settings and restarts playback with it.
+    fn render(&mut self) -> Result<()> {
+        let rendered = Pipeline::new(self.config.clone())
+            .set_range(self.range)
+            .process(self.excerpt.clone(), self.sample_rate)?;
+        self.after = Levels::measure(&rendered, self.sample_rate);
+        self.waveform = waveform(&rendered, WAVEFORM_COLUMNS);
+
+        // The preview uses the output sample rate, so a pitch shift is audible too
+        io::save_audio_as(
+            self.player.path(),
+            rendered,
+            self.config.sample_rate,
+            AudioFormat::Wav,
+            BitDepth::Int16,
+            |_, _| Ok(()),
+        )?;
+        if self.player.is_playing() {
+            self.player.play()?;
+        }
+        Ok(())
+    }
+
+    fn toggle_playback(&mut self) -> Result<()> {
+        if self.player.is_playing() {
+            self.player.stop();
+        } else if self.player.name().is_some() {
+            self.player.play()?;
+        }
+        Ok(())
+    }
+
+    // Processes the whole input with the current settings on a background thread.
+    fn start_export(&mut self) {
+        if self.output.exists() && !self.confirm_overwrite {
+            self.confirm_overwrite = true;
+            self.status = format!(
+                "{} already exists. Press e again to overwrite it",
+                self.output.display()
+            );
+            return;
+        }
+        self.confirm_overwrite = false;
+
+        let progress = Arc::new(Mutex::new(None));
+        let cancellation = CancellationToken::new();
+
+        let mut pipeline = Pipeline::new(self.config.clone());
+        let sink = Arc::clone(&progress);
+        pipeline.set_progress_callback(move |update| *sink.lock().unwrap() = Some(update));
+        pipeline.set_cancellation_token(cancellation.clone());
+
+        let (input, output) = (self.input.clone(), self.output.clone());
+        let handle = std::thread::spawn(move || pipeline.run_with_report(&input, &output));
+
+        self.export = Some(Export {
+            handle,
+            progress,
+            cancellation,
+        });
+    }
+
+    fn poll_export(&mut self) {
+        if !self
+            .export
+            .as_ref()
+            .is_some_and(|export| export.handle.is_finished())
+        {
+            return;
+        }
+
+        let export = self.export.take().expect("checked above");
+        self.status = match export.handle.join() {
+            Ok(Ok(report)) => format!(
+                "Exported {} in {:.1}s ({:.1} LUFS, peak {:.1} dBFS)",
+                report.output.display(),
+                report.elapsed_secs,
+                report.after.loudness_lufs,
+                report.after.peak_dbfs
+            ),
+            Ok(Err(OxidizerError::Cancelled)) => "Export cancelled".to_string(),
+            Ok(Err(e)) => format!("Export failed: {}", e),
+            Err(_) => "Export failed: the worker thread panicked".to_string(),
+        };
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [waveform_area, controls_area, status_area, help_area] = Layout::vertical([
+            Constraint::Min(6),
+            Constraint::Length(Param::ALL.len() as u16 + 2),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [params_area, meters_area] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .areas(controls_area);
+
+        self.draw_waveform(frame, waveform_area);
+        self.draw_params(frame, params_area);
+        self.draw_meters(frame, meters_area);
+        self.draw_status(frame, status_area);
+
+        let help = if self.export.is_some() {
+            "q/esc cancel export"
+        } else {
+            "↑↓ select  ←→ adjust  space play/stop  e export  q quit"
+        };
+        frame.render_widget(Paragraph::new(help).dark_gray(), help_area);
+    }
+
+    fn draw_waveform(&self, frame: &mut Frame, area: Rect) {
+        let columns = self.waveform.len() as f64;
+        let excerpt_secs = (self.excerpt.len() / 2) as f64 / self.config.sample_rate.max(1) as f64;
+        // Where the looping preview currently is, if it's playing
+        let playhead = self.player.is_playing().then(|| {
+            let position = self.player.position().as_secs_f64() % excerpt_secs.max(f64::EPSILON);
+            position / excerpt_secs * columns
+        });
+
+        let title = format!(" {} → {} ", file_name(&self.input), file_name(&self.output));
+        let waveform = Canvas::default()
+            .block(Block::bordered().title(title))
+            .marker(Marker::Braille)
+            .x_bounds([0.0, columns])
+            .y_bounds([-1.0, 1.0])
+            .paint(|ctx| {
+                for (x, &(low, high)) in self.waveform.iter().enumerate() {
+                    ctx.draw(&canvas::Line::new(
+                        x as f64,
+                        low as f64,
+                        x as f64,
+                        high as f64,
+                        Color::Yellow,
+                    ));
+                }
+                if let Some(x) = playhead {
+                    ctx.draw(&canvas::Line::new(x, -1.0, x, 1.0, Color::Cyan));
+                }
+            });
+        frame.render_widget(waveform, area);
+    }
+
+    fn draw_params(&self, frame: &mut Frame, area: Rect) {
+        let lines: Vec<Line> = Param::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, param)| {
+                let text = format!(" {:<10} {}", param.label(), self.value(*param));
+                if i == self.selected {
+                    Line::from(text).style(Style::new().reversed())
+                } else {
+                    Line::from(text)
+                }
+            })
+            .collect();
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Settings ")),
+            area,
+        );
+    }
+
+    fn value(&self, param: Param) -> String {
+        let config = &self.config;
+        match param {
+            Param::Level => format!(
+                "{} (~{:.0} Hz)",
+                config.level,
+                config.level.cutoff_hz(self.sample_rate, config.passes)
+            ),
+            Param::Intensity => format!("{:.2}", config.intensity),
+            Param::Noise => config.noise.name().to_string(),
+            Param::Mix => format!("{:.0}% wet", config.mix * 100.0),
+            Param::Passes => config.passes.to_string(),
+        }
+    }
+
+    fn draw_meters(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title(" Levels (excerpt) ");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let meters = [
+            ("Peak in", self.before.peak_dbfs, "dBFS"),
+            ("Peak out", self.after.peak_dbfs, "dBFS"),
+            ("Loud in", self.before.loudness_lufs, "LUFS"),
+            ("Loud out", self.after.loudness_lufs, "LUFS"),
+        ];
+        let rows = Layout::vertical(meters.iter().map(|_| Constraint::Length(1))).split(inner);
+
+        for ((label, db, unit), row) in meters.into_iter().zip(rows.iter()) {
+            let ratio = ((db - METER_FLOOR_DB) / -METER_FLOOR_DB).clamp(0.0, 1.0);
+            let reading = if db.is_finite() {
+                format!("{:<9}{:>6.1} {} ", label, db, unit)
+            } else {
+                format!("{:<9}{:>6} {} ", label, "-inf", unit)
+            };
+            let gauge = LineGauge::default()
+                .ratio(ratio as f64)
+                .label(reading)
+                .filled_style(Style::new().fg(meter_color(db)));
+            frame.render_widget(gauge, *row);
+        }
+    }
+
+    fn draw_status(&self, frame: &mut Frame, area: Rect) {
+        let Some(export) = &self.export else {
+            frame.render_widget(Paragraph::new(self.status.as_str()), area);
+            return;
+        };
+
+        let progress = *export.progress.lock().unwrap();
+        let (stage, ratio) = match progress {
+            Some(progress) => (
+                stage_name(progress.stage),
+                progress.fraction().unwrap_or(0.0),
+            ),
+            None => ("Starting", 0.0),
+        };
+        let gauge = LineGauge::default()
+            .ratio(ratio as f64)
+            .label(format!("Exporting: {:<10}", stage))
+            .filled_style(Style::new().fg(Color::Green).add_modifier(Modifier::BOLD));
+        frame.render_widget(gauge, area);
+    }
+}
+
+// Steps through `values` from `current`, wrapping around at both ends.
+fn cycle<T: Copy + PartialEq>(values: &[T], current: &T, direction: i32) -> T {
+    let index = values.iter().position(|v| v == current).unwrap_or(0) as i32;
+    let len = values.len() as i32;
+    values[(index + direction).rem_euclid(len) as usize]
+}
+
+// Reduces interleaved stereo samples to the (min, max) of their mono sum per column.
+fn waveform(samples: &[f32], columns: usize) -> Vec<(f32, f32)> {
+    let frames = samples.len() / 2;
+    let per_column = frames.div_ceil(columns).max(1);
+
+    samples
+        .chunks(per_column * 2)
+        .map(|chunk| {
+            chunk
+                .chunks_exact(2)
+                .map(|frame| (frame[0] + frame[1]) / 2.0)
+                .fold((0.0f32, 0.0f32), |(low, high), s| (low.min(s), high.max(s)))
+        })
+        .collect()
+}
+
+fn meter_color(db: f32) -> Color {
+    if db > -1.0 {
+        Color::Red
+    } else if db > -9.0 {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
+
+fn stage_name(stage: Stage) -> &'static str {
+    match stage {
+        Stage::Decoding => "decoding",
+        Stage::Processing => "processing",
+        Stage::Encoding => "encoding",
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
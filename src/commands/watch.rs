@@ -0,0 +1,90 @@
+use super::settings::SettingsArgs;
+use oxidizer::batch;
+use oxidizer::error::Result;
+use oxidizer::{OxidizerConfig, Pipeline};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// How often the watched directory is scanned for changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Output directory used when none is given, relative to the watched directory.
+const DEFAULT_OUTPUT_DIR: &str = "oxidized";
+
+// Modification time and size of a file, used to detect changes.
+type Signature = (SystemTime, u64);
+
+/// Watches `dir` and oxidizes every audio file that appears or changes in it, until the
+/// process is interrupted.
+///
+/// Files already present when watching starts are left alone. A file is only picked up
+/// once its size and modification time stay the same between two scans, so exports that
+/// are still being written aren't processed half-way.
+pub fn run(
+    dir: &Path,
+    output: Option<&str>,
+    settings: &SettingsArgs,
+    mut config: OxidizerConfig,
+) -> Result<()> {
+    let (output_dir, template) = match output {
+        Some(output) => batch::split_output_spec(output),
+        None => (
+            dir.join(DEFAULT_OUTPUT_DIR),
+            batch::DEFAULT_TEMPLATE.to_string(),
+        ),
+    };
+    let template = settings.apply_output_template(&mut config, template)?;
+    std::fs::create_dir_all(&output_dir)?;
+
+    let mut processed = scan(dir)?;
+    let mut pending: HashMap<PathBuf, Signature> = HashMap::new();
+
+    tracing::info!(
+        "Watching {} for new audio files, writing to {} (Ctrl+C to stop)",
+        dir.display(),
+        output_dir.display()
+    );
+
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+
+        for (input, signature) in scan(dir)? {
+            if processed.get(&input) == Some(&signature) {
+                continue;
+            }
+            // Wait for one more scan without changes before touching the file
+            if pending.get(&input) != Some(&signature) {
+                pending.insert(input, signature);
+                continue;
+            }
+            pending.remove(&input);
+
+            let output = batch::output_path(&input, &output_dir, &template);
+            tracing::info!("{} -> {}", input.display(), output.display());
+            if let Err(e) = super::run_with_progress(Pipeline::new(config.clone()), &input, &output)
+            {
+                tracing::error!("Failed: {}", e);
+            }
+            processed.insert(input, signature);
+
+            // Outputs written into the watched directory must not be picked up again
+            if let Some(signature) = signature_of(&output) {
+                processed.insert(output, signature);
+            }
+        }
+    }
+}
+
+// Returns the signature of every audio file in `dir`.
+fn scan(dir: &Path) -> Result<HashMap<PathBuf, Signature>> {
+    Ok(batch::scan_dir(dir)?
+        .into_iter()
+        .filter_map(|path| signature_of(&path).map(|signature| (path, signature)))
+        .collect())
+}
+
+fn signature_of(path: &Path) -> Option<Signature> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
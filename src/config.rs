@@ -0,0 +1,790 @@
+use crate::analysis;
+use crate::artifacts::Artifacts;
+use crate::automation::Automations;
+use crate::codec::{LossyCodec, TelephoneLine};
+use crate::dynamics::{Compressor, Expander, Gate, Limiter, NoiseDucking, TransientShaper};
+use crate::eq::EqBand;
+#[cfg(doc)]
+use crate::eq::Riaa;
+#[cfg(feature = "serde")]
+use crate::error::OxidizerError;
+use crate::error::{Result, check_range};
+use crate::hum::GroundLoop;
+use crate::io::{AudioFormat, BitDepth};
+use crate::math;
+use crate::modulation::{Drift, EnvelopeResponse, Lfo};
+use crate::overlay::Overlay;
+use crate::processor::noise::{BrownianNoise, CrackleNoise, NoiseGenerator, WhiteNoise};
+use crate::processor::{IntensityCurve, MAX_PASSES, OxidationLevel};
+use crate::resample::ResampleQuality;
+use crate::speed::{Chorus, Flutter, Wobble};
+use crate::stereo::{Crossfeed, Haas};
+#[cfg(feature = "serde")]
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Selects and parametrizes the noise generator that textures the signal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename_all = "lowercase")
+)]
+pub enum NoiseConfig {
+    /// Flat, full-spectrum "radio static".
+    White,
+    /// Bass-heavy, organic "whoosh" (leaky random walk).
+    Brown {
+        /// How much of the previous state is retained (0.0 to 1.0).
+        damping: f32,
+        /// The maximum change applied by the random walk in each sample.
+        step: f32,
+    },
+    /// Sparse ticks and pops, like the surface of a shellac disc.
+    Crackle {
+        /// Chance that a tick starts on any one sample (0.0 to 1.0).
+        density: f32,
+    },
+}
+
+impl Default for NoiseConfig {
+    fn default() -> Self {
+        NoiseConfig::Brown {
+            damping: 0.98,
+            step: 0.1,
+        }
+    }
+}
+
+impl NoiseConfig {
+    /// Every noise generator, with its default parameters.
+    pub fn all() -> [NoiseConfig; 3] {
+        [
+            NoiseConfig::default(),
+            NoiseConfig::White,
+            NoiseConfig::Crackle { density: 0.0005 },
+        ]
+    }
+
+    /// Returns the name used on the command line and in config files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            NoiseConfig::White => "white",
+            NoiseConfig::Brown { .. } => "brown",
+            NoiseConfig::Crackle { .. } => "crackle",
+        }
+    }
+
+    /// Returns a short description of how the generator sounds.
+    pub fn description(&self) -> &'static str {
+        match self {
+            NoiseConfig::White => "Flat, full-spectrum radio static",
+            NoiseConfig::Brown { .. } => "Bass-heavy, organic whoosh (leaky random walk)",
+            NoiseConfig::Crackle { .. } => "Sparse ticks and pops of a worn record surface",
+        }
+    }
+
+    /// Builds the generator, boxed so engines can swap it for another type with
+    /// [`Oxidizer::set_noise_generator`](crate::Oxidizer::set_noise_generator).
+    ///
+    /// # Errors
+    /// Returns [`InvalidParameter`](crate::OxidizerError::InvalidParameter) for generator
+    /// parameters out of range.
+    pub fn generator(&self) -> Result<Box<dyn NoiseGenerator + Send>> {
+        Ok(match *self {
+            NoiseConfig::White => Box::new(WhiteNoise::default()),
+            NoiseConfig::Brown { damping, step } => Box::new(BrownianNoise::new(damping, step)?),
+            NoiseConfig::Crackle { density } => Box::new(CrackleNoise::new(density)?),
+        })
+    }
+
+    /// Attempts to parse a noise name into a `NoiseConfig` with default parameters.
+    ///
+    /// # Errors
+    /// Returns an error string if the input does not match any known noise type.
+    pub fn try_from_str(s: &str) -> std::result::Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "brown" | "brownian" => Ok(NoiseConfig::default()),
+            "white" => Ok(NoiseConfig::White),
+            "crackle" => Ok(NoiseConfig::Crackle { density: 0.0005 }),
+            _ => Err(format!(
+                "Unknown noise type: {} (use brown, white or crackle)",
+                s
+            )),
+        }
+    }
+}
+
+/// Where the noise texture enters the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum NoiseRouting {
+    #[default]
+    PostFilter, // Over the filtered program: the hiss stays bright while the music is muffled.
+    PreFilter, // Into the input, so the filter dulls the noise along with the program.
+}
+
+impl NoiseRouting {
+    /// Every routing.
+    pub const ALL: [NoiseRouting; 2] = [NoiseRouting::PostFilter, NoiseRouting::PreFilter];
+
+    /// Returns the name used on the command line and in config files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            NoiseRouting::PostFilter => "post-filter",
+            NoiseRouting::PreFilter => "pre-filter",
+        }
+    }
+
+    pub fn try_from_str(s: &str) -> std::result::Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|routing| routing.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown noise routing: {} (use pre-filter or post-filter)",
+                    s
+                )
+            })
+    }
+}
+
+/// How one channel departs from the shared settings, so the two sides can wear
+/// differently, like a single worn tape head or a stylus damaged on one flank.
+///
+/// The adjustments are relative, so they follow automation, drift and the LFO.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ChannelConfig {
+    /// Shift of the channel's filter cutoff, in octaves: negative values are darker.
+    pub cutoff_octaves: f32,
+    /// Gain on the channel's noise texture, in dB.
+    pub noise_db: f32,
+}
+
+/// How the processed signal is brought to its final level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "mode", rename_all = "lowercase")
+)]
+pub enum Normalization {
+    /// Leave the level untouched.
+    None,
+    /// Scale the signal so its highest peak reaches `ceiling` (linear amplitude).
+    Peak { ceiling: f32 },
+    /// Scale the signal to an integrated loudness of `target_lufs`, as long as its true
+    /// peak stays below `true_peak` (dBTP). When the peak limit wins, the result is quieter
+    /// than the target.
+    Loudness { target_lufs: f32, true_peak: f32 },
+    /// Scale the input, before anything else, so the finished render peaks at `ceiling`
+    /// (linear amplitude). A first render at the original level estimates how much of the
+    /// headroom the noise and the `tanh()` saturation take; whatever the estimate misses
+    /// is trimmed off the output like [`Peak`](Self::Peak). Unlike peak normalization of
+    /// the output alone, the noise stays at the level the intensity sets and the
+    /// saturation bites as hard as it will at the final level.
+    Smart { ceiling: f32 },
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        // Approx. -0.5 dBFS
+        Normalization::Peak { ceiling: 0.95 }
+    }
+}
+
+/// Processing settings for a complete oxidation run.
+///
+/// With the `serde` feature, missing fields fall back to their [`Default`] values,
+/// so partial configurations can be stored and merged.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct OxidizerConfig {
+    /// Low-pass character of the filter stage.
+    pub level: OxidationLevel,
+    /// Number of filter iterations. Each pass steepens the slope by 6dB/oct.
+    pub passes: u32,
+    /// Generator used for the background texture.
+    pub noise: NoiseConfig,
+    /// Whether the noise goes in before or after the filter passes.
+    pub noise_routing: NoiseRouting,
+    /// Lowers the noise while the program it's added to is loud.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub noise_ducking: Option<NoiseDucking>,
+    /// Adjustments of the left and the right channel, in that order.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub channels: Option<[ChannelConfig; 2]>,
+    /// Scale of the noise and saturation effect, typically 0.0 to 1.0.
+    pub intensity: f32,
+    /// How the intensity maps to the amount of noise. Custom curves can't be saved.
+    pub intensity_curve: IntensityCurve,
+    /// Dry/wet balance: 0.0 keeps the original signal, 1.0 only the oxidized one.
+    pub mix: f32,
+    /// Curves that vary the cutoff, intensity or mix over the course of the render,
+    /// overriding the fixed values above.
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "Automations::is_empty")
+    )]
+    pub automation: Automations,
+    /// Oscillator sweeping the filter cutoff around its base value.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub lfo: Option<Lfo>,
+    /// Parameters that follow the level of the input (see [`EnvelopeResponse`]).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub envelope: Vec<EnvelopeResponse>,
+    /// File whose level drives the responses with a `sidechain` source. It is decoded
+    /// over the same time range as the input.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub sidechain: Option<PathBuf>,
+    /// Parameters that wander randomly within bounds (see [`Drift`]).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub drift: Vec<Drift>,
+    /// Spreads the input across the stereo field before anything else, e.g. to fake
+    /// stereo from a mono sample.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub haas: Option<Haas>,
+    /// Wavers the speed of the input (and with it the pitch) after the Haas spreader.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub flutter: Option<Flutter>,
+    /// Swings the speed of the input once per revolution of an off-center record, after
+    /// the flutter.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub wobble: Option<Wobble>,
+    /// Mixes in a swept, slightly late copy of the input after the flutter.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub chorus: Option<Chorus>,
+    /// Restores (or softens) the attacks of the filtered program, before the noise.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub transients: Option<TransientShaper>,
+    /// Adds the noise between RIAA pre- and de-emphasis (see [`Riaa`]), like the surface
+    /// of a record: the hiss comes out darker and the saturation bites the highs first.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "std::ops::Not::not"))]
+    pub riaa: bool,
+    /// Silences the output, or the program under the noise, while the input is quiet.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub gate: Option<Gate>,
+    /// Mains hum riding on the program after the mix, before the dynamics.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub ground_loop: Option<GroundLoop>,
+    /// Widens the dynamics after the noise stage and the mix.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub expander: Option<Expander>,
+    /// Squashes the dynamics after the expander.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub compressor: Option<Compressor>,
+    /// Catches the peaks left after the compressor.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub limiter: Option<Limiter>,
+    /// Pops, clicks and other discrete defects added after the noise stage.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Artifacts::is_empty"))]
+    pub artifacts: Artifacts,
+    /// Filter bands shaping the tone after the noise and the artifacts, in order, like
+    /// the horn or speaker of a playback device.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub eq: Vec<EqBand>,
+    /// Sends the render through a telephone codec after the EQ bands.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub telephone: Option<TelephoneLine>,
+    /// Mangles the spectrum like a low-bitrate lossy codec, after the telephone line.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub lossy: Option<LossyCodec>,
+    /// Recordings layered over the output, after the noise stage and the mix.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub overlay: Vec<Overlay>,
+    /// Headphone crossfeed applied last, after the overlays.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub crossfeed: Option<Crossfeed>,
+    /// Parallel renders of the input summed into the output (see [`Branch`]).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub branches: Vec<Branch>,
+    /// Sections of the render processed with other settings (see [`Segment`]).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub segments: Vec<Segment>,
+    /// Renders a seamless loop: the end of the render carries on into its start, faded
+    /// in over this many milliseconds, so the file repeats without a click or a jump in
+    /// the noise floor.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub loop_crossfade_ms: Option<f32>,
+    /// Final level adjustment applied after the noise stage and the mix.
+    pub normalization: Normalization,
+    /// Sample rate written to the output file.
+    pub sample_rate: u32,
+    /// How inputs and overlays at another sample rate are converted to the rate of the
+    /// render.
+    pub resample_quality: ResampleQuality,
+    /// Container and codec of the output file.
+    pub format: AudioFormat,
+    /// Sample format of the output file.
+    pub bit_depth: BitDepth,
+    /// Seed for the noise generator. Renders with the same seed and settings are
+    /// byte-identical; without one, every render gets fresh noise (or seed 0, with the
+    /// `deterministic` feature).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub seed: Option<u64>,
+}
+
+/// A section of the render processed with its own settings, e.g. a heavily muffled
+/// intro before the track opens up.
+///
+/// The section is rendered separately and spliced into the main render with short
+/// crossfades. Its normalization and segments are ignored (the whole render is
+/// normalized once), and it inherits the main seed unless it sets its own.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Segment {
+    /// Position in the input where the segment starts, in seconds.
+    pub start_secs: f64,
+    /// Where it ends, or `None` to run to the end of the input.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub end_secs: Option<f64>,
+    pub config: Box<OxidizerConfig>,
+}
+
+/// What a [`Branch`] processes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum BranchInput {
+    #[default]
+    Input, // The same input as the main chain.
+    Silence, // Nothing, so the branch carries only its noise: a noise bus with its own filter.
+}
+
+/// A parallel chain: the input is split, run through the branch's settings alongside the
+/// main chain, and summed back into the output at `gain_db`.
+///
+/// With the main chain fully dry (`mix = 0.0`), a heavily crushed branch a few dB down
+/// gives parallel processing; a [`BranchInput::Silence`] branch with pre-filter noise
+/// routing is a noise bus filtered separately from the music. Branches can have branches
+/// of their own. Their normalization and segments are ignored (the sum is normalized
+/// once), and each gets a seed derived from its parent's unless it sets its own.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Branch {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub input: BranchInput,
+    /// Level of the branch in the sum, in dB.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub gain_db: f32,
+    pub config: Box<OxidizerConfig>,
+}
+
+impl Branch {
+    /// A branch processing the input with `config`, summed in at `gain_db`.
+    pub fn new(config: OxidizerConfig, gain_db: f32) -> Self {
+        Self {
+            input: BranchInput::Input,
+            gain_db,
+            config: Box::new(config),
+        }
+    }
+
+    /// Linear gain the branch is summed in at.
+    pub fn gain(&self) -> f32 {
+        analysis::from_dbfs(self.gain_db)
+    }
+}
+
+impl Default for OxidizerConfig {
+    fn default() -> Self {
+        Self {
+            level: OxidationLevel::Deep,
+            passes: 1,
+            noise: NoiseConfig::default(),
+            noise_routing: NoiseRouting::default(),
+            noise_ducking: None,
+            channels: None,
+            intensity: 0.05,
+            intensity_curve: IntensityCurve::default(),
+            mix: 1.0,
+            automation: Automations::default(),
+            lfo: None,
+            envelope: Vec::new(),
+            sidechain: None,
+            drift: Vec::new(),
+            haas: None,
+            flutter: None,
+            wobble: None,
+            chorus: None,
+            transients: None,
+            riaa: false,
+            gate: None,
+            ground_loop: None,
+            expander: None,
+            compressor: None,
+            limiter: None,
+            artifacts: Artifacts::default(),
+            eq: Vec::new(),
+            telephone: None,
+            lossy: None,
+            overlay: Vec::new(),
+            crossfeed: None,
+            branches: Vec::new(),
+            segments: Vec::new(),
+            loop_crossfade_ms: None,
+            normalization: Normalization::default(),
+            sample_rate: 44100,
+            resample_quality: ResampleQuality::default(),
+            format: AudioFormat::Wav,
+            bit_depth: BitDepth::Int16,
+            seed: None,
+        }
+    }
+}
+
+impl OxidizerConfig {
+    /// Checks that every setting is within the range it can take, including those of
+    /// segments and branches. [`Pipeline`](crate::Pipeline) runs this before processing.
+    ///
+    /// # Errors
+    /// Returns [`InvalidParameter`](crate::OxidizerError::InvalidParameter) naming the
+    /// first setting out of range.
+    pub fn validate(&self) -> Result<()> {
+        if let OxidationLevel::Amount(amount) = self.level {
+            check_range("level amount", amount as f64, 0.0..=1.0, "0.0 to 1.0")?;
+        }
+        check_range("intensity", self.intensity as f64, 0.0..=1.0, "0.0 to 1.0")?;
+        check_range("mix", self.mix as f64, 0.0..=1.0, "0.0 (dry) to 1.0 (wet)")?;
+        check_range(
+            "passes",
+            self.passes as f64,
+            0.0..=MAX_PASSES as f64,
+            // Only formatted on failure, so validating doesn't allocate
+            format_args!("0 to {}", MAX_PASSES),
+        )?;
+        check_range(
+            "sample rate",
+            self.sample_rate as f64,
+            1.0..,
+            "at least 1 Hz",
+        )?;
+        if let Some(ms) = self.loop_crossfade_ms {
+            check_range("loop crossfade", ms as f64, 0.0.., "0 ms or more")?;
+        }
+        if let Some(haas) = self.haas {
+            check_range("Haas delay", haas.delay_ms as f64, 0.0.., "0 ms or more")?;
+            check_range(
+                "Haas damping",
+                haas.damping_hz as f64,
+                f64::MIN_POSITIVE..,
+                "above 0 Hz",
+            )?;
+        }
+        if let Some(crossfeed) = self.crossfeed {
+            check_range(
+                "crossfeed level",
+                crossfeed.level_db as f64,
+                ..=0.0,
+                "0 dB or less",
+            )?;
+            check_range(
+                "crossfeed cutoff",
+                crossfeed.cutoff_hz as f64,
+                f64::MIN_POSITIVE..,
+                "above 0 Hz",
+            )?;
+            check_range(
+                "crossfeed delay",
+                crossfeed.delay_ms as f64,
+                0.0..,
+                "0 ms or more",
+            )?;
+        }
+        if let Some(ducking) = self.noise_ducking {
+            check_range(
+                "ducking depth",
+                ducking.depth_db as f64,
+                0.0..,
+                "0 dB or more",
+            )?;
+            check_range(
+                "ducking attack",
+                ducking.attack_ms as f64,
+                0.0..,
+                "0 ms or more",
+            )?;
+            check_range(
+                "ducking release",
+                ducking.release_ms as f64,
+                0.0..,
+                "0 ms or more",
+            )?;
+        }
+        for channel in self.channels.iter().flatten() {
+            check_range(
+                "channel cutoff shift",
+                channel.cutoff_octaves as f64,
+                -10.0..=10.0,
+                "-10 to 10 octaves",
+            )?;
+            check_range(
+                "channel noise gain",
+                channel.noise_db as f64,
+                -96.0..=24.0,
+                "-96 to 24 dB",
+            )?;
+        }
+        if let Some(flutter) = self.flutter {
+            check_range(
+                "flutter rate",
+                flutter.rate_hz as f64,
+                f64::MIN_POSITIVE..,
+                "above 0 Hz",
+            )?;
+            check_range(
+                "flutter depth",
+                flutter.depth_percent as f64,
+                0.0..=10.0,
+                "0 to 10 %",
+            )?;
+        }
+        if let Some(wobble) = self.wobble {
+            check_range(
+                "wobble depth",
+                wobble.depth_percent as f64,
+                0.0..=10.0,
+                "0 to 10 %",
+            )?;
+        }
+        if let Some(line) = self.telephone {
+            check_range(
+                "line sample rate",
+                line.sample_rate as f64,
+                2000.0..=48000.0,
+                "2000 to 48000 Hz",
+            )?;
+        }
+        if let Some(codec) = self.lossy {
+            check_range(
+                "codec cutoff",
+                codec.cutoff_hz as f64,
+                f64::MIN_POSITIVE..,
+                "above 0 Hz",
+            )?;
+            check_range("codec floor", codec.floor_db as f64, 0.0.., "0 dB or more")?;
+            check_range(
+                "codec step",
+                codec.step_db as f64,
+                f64::MIN_POSITIVE..,
+                "above 0 dB",
+            )?;
+        }
+        if let Some(chorus) = self.chorus {
+            check_range(
+                "chorus rate",
+                chorus.rate_hz as f64,
+                f64::MIN_POSITIVE..,
+                "above 0 Hz",
+            )?;
+            check_range(
+                "chorus delay",
+                chorus.delay_ms as f64,
+                0.0..=100.0,
+                "0 to 100 ms",
+            )?;
+            check_range(
+                "chorus depth",
+                chorus.depth_ms as f64,
+                0.0..=chorus.delay_ms as f64,
+                "0 ms up to the chorus delay",
+            )?;
+            check_range("chorus mix", chorus.mix as f64, 0.0..=1.0, "0.0 to 1.0")?;
+        }
+        for band in &self.eq {
+            check_range(
+                "EQ frequency",
+                band.frequency_hz as f64,
+                f64::MIN_POSITIVE..,
+                "above 0 Hz",
+            )?;
+            check_range("EQ Q", band.q as f64, f64::MIN_POSITIVE.., "above 0")?;
+        }
+        if let Some(ground_loop) = self.ground_loop {
+            check_range(
+                "hum frequency",
+                ground_loop.frequency_hz as f64,
+                1.0..=1000.0,
+                "1 to 1000 Hz",
+            )?;
+            check_range(
+                "hum level",
+                ground_loop.level_db as f64,
+                ..=0.0,
+                "0 dB or less",
+            )?;
+        }
+        for event in &self.artifacts.events {
+            check_range("artifact time", event.time_secs, 0.0.., "0 s or more")?;
+            check_range(
+                "artifact amplitude",
+                event.amplitude as f64,
+                0.0..=1.0,
+                "0.0 to 1.0",
+            )?;
+        }
+        for random in &self.artifacts.random {
+            for amplitude in [random.min_amplitude, random.max_amplitude] {
+                check_range(
+                    "artifact amplitude",
+                    amplitude as f64,
+                    0.0..=1.0,
+                    "0.0 to 1.0",
+                )?;
+            }
+        }
+        match self.noise {
+            NoiseConfig::White => {}
+            NoiseConfig::Brown { damping, step } => BrownianNoise::check(damping, step)?,
+            NoiseConfig::Crackle { density } => CrackleNoise::check(density)?,
+        }
+        if let Normalization::Peak { ceiling } | Normalization::Smart { ceiling } =
+            self.normalization
+        {
+            check_range(
+                "ceiling",
+                ceiling as f64,
+                f64::MIN_POSITIVE..=1.0,
+                "above 0.0 up to 1.0",
+            )?;
+        }
+
+        for segment in &self.segments {
+            segment.config.validate()?;
+        }
+        for branch in &self.branches {
+            branch.config.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Gain of the filter chain at each of `frequencies` (in Hz), in dB at the output
+    /// sample rate, e.g. for a frontend to draw the tone curve before rendering: the
+    /// passes of the low pass, then the EQ bands, mixed with the dry signal.
+    ///
+    /// It's the curve of the settings as they stand. Automation, drift, the LFO,
+    /// envelope responses and per-channel adjustments move it while rendering, and the
+    /// noise and the saturation aren't filters at all.
+    pub fn response_at(&self, frequencies: &[f32]) -> Vec<f32> {
+        let sample_rate = self.sample_rate.max(1);
+        let alpha = self.level.alpha() as f64;
+        let passes = self.passes.min(MAX_PASSES) as i32;
+        let mix = self.mix.clamp(0.0, 1.0) as f64;
+
+        frequencies
+            .iter()
+            .map(|&hz| {
+                let w = std::f64::consts::TAU * hz as f64 / sample_rate as f64;
+                // One pass: alpha / (1 - (1 - alpha) z^-1)
+                let (re, im) = (
+                    1.0 - (1.0 - alpha) * math::cos_f64(w),
+                    (1.0 - alpha) * math::sin_f64(w),
+                );
+                let norm = re * re + im * im;
+                let pass = (alpha * re / norm, -alpha * im / norm);
+                let filter = (0..passes).fold((1.0, 0.0), |chain, _| multiply(chain, pass));
+                let (re, im) = self.eq.iter().fold(filter, |chain, band| {
+                    multiply(chain, band.transfer(hz, sample_rate))
+                });
+
+                let (re, im) = (1.0 - mix + mix * re, mix * im);
+                (10.0 * math::log10_f64(re * re + im * im)) as f32
+            })
+            .collect()
+    }
+}
+
+// Complex product of (real, imaginary) pairs.
+fn multiply((a, b): (f64, f64), (c, d): (f64, f64)) -> (f64, f64) {
+    (a * c - b * d, a * d + b * c)
+}
+
+#[cfg(feature = "serde")]
+impl OxidizerConfig {
+    /// Loads a configuration from a `.toml` or `.json` file, chosen by extension.
+    ///
+    /// Fields missing from the file keep their default values.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        read_file(path.as_ref())
+    }
+
+    /// Writes the configuration to a `.toml` or `.json` file, chosen by extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        write_file(path.as_ref(), self)
+    }
+}
+
+/// Deserializes a `.toml` or `.json` file, chosen by extension.
+#[cfg(feature = "serde")]
+pub(crate) fn read_file<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    let text = std::fs::read_to_string(path)?;
+
+    match ConfigFormat::from_path(path)? {
+        ConfigFormat::Toml => toml::from_str(&text).map_err(|e| config_error(path, e.to_string())),
+        ConfigFormat::Json => {
+            serde_json::from_str(&text).map_err(|e| config_error(path, e.to_string()))
+        }
+    }
+}
+
+/// Serializes `value` into a `.toml` or `.json` file, chosen by extension.
+#[cfg(feature = "serde")]
+pub(crate) fn write_file<T: serde::Serialize>(path: &Path, value: &T) -> Result<()> {
+    let text = match ConfigFormat::from_path(path)? {
+        ConfigFormat::Toml => toml::to_string_pretty(value).map_err(|e| e.to_string()),
+        ConfigFormat::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| config_error(path, e))?;
+
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+#[cfg(feature = "serde")]
+impl ConfigFormat {
+    fn from_path(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+
+        match extension.as_deref() {
+            Some("toml") => Ok(ConfigFormat::Toml),
+            Some("json") => Ok(ConfigFormat::Json),
+            _ => Err(config_error(
+                path,
+                "expected a .toml or .json extension".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn config_error(path: &Path, message: String) -> OxidizerError {
+    OxidizerError::Config(format!("{}: {}", path.display(), message))
+}
@@ -0,0 +1,381 @@
+//! Dynamics processors.
+//!
+//! Every processor detects the level of both channels together (the louder one wins),
+//! so the stereo image doesn't shift, and smooths the gain it applies with separate
+//! attack and release times.
+//!
+//! Some processors can be keyed by another signal than the one they process, e.g. a
+//! [`Gate`] on the output that listens to the clean input.
+
+use crate::analysis::{from_dbfs, to_dbfs};
+use crate::modulation::{EnvelopeFollower, smoothing};
+
+// Detected levels are clamped to this, in dBFS, so silence stays finite.
+const LEVEL_FLOOR_DB: f32 = -120.0;
+
+/// A feed-forward compressor with a hard knee.
+///
+/// Above `threshold_db`, every `ratio` dB of input rise come out as 1 dB. `makeup_db`
+/// raises the result to win back the level lost.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Compressor {
+    pub threshold_db: f32,
+    pub ratio: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    pub makeup_db: f32,
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self {
+            threshold_db: -18.0,
+            ratio: 4.0,
+            attack_ms: 10.0,
+            release_ms: 100.0,
+            makeup_db: 0.0,
+        }
+    }
+}
+
+impl Compressor {
+    /// Compresses interleaved stereo `samples` in place.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        let slope = 1.0 - 1.0 / self.ratio.max(1.0);
+        let mut gain = GainSmoother::new(self.attack_ms, self.release_ms, sample_rate);
+        let makeup = from_dbfs(self.makeup_db);
+
+        for frame in samples.chunks_mut(2) {
+            let over = (frame_level_db(frame) - self.threshold_db).max(0.0);
+            let gain = from_dbfs(-gain.reduce(over * slope)) * makeup;
+            frame.iter_mut().for_each(|s| *s *= gain);
+        }
+    }
+}
+
+/// A downward expander, the counterpart of the [`Compressor`]: below `threshold_db`,
+/// every dB the level falls comes out as `ratio` dB, restoring contrast to audio that
+/// heavy processing has flattened.
+///
+/// The expansion stops at `range_db` of reduction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Expander {
+    pub threshold_db: f32,
+    pub ratio: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    pub range_db: f32,
+}
+
+impl Default for Expander {
+    fn default() -> Self {
+        Self {
+            threshold_db: -30.0,
+            ratio: 2.0,
+            attack_ms: 5.0,
+            release_ms: 100.0,
+            range_db: 40.0,
+        }
+    }
+}
+
+impl Expander {
+    /// Expands interleaved stereo `samples` in place.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        let slope = self.ratio.max(1.0) - 1.0;
+        // The level falling adds reduction, so that runs at the smoother's attack speed
+        let mut gain = GainSmoother::new(self.release_ms, self.attack_ms, sample_rate);
+
+        for frame in samples.chunks_mut(2) {
+            let under = (self.threshold_db - frame_level_db(frame)).max(0.0);
+            let reduction = (under * slope).min(self.range_db.max(0.0));
+            let gain = from_dbfs(-gain.reduce(reduction));
+            frame.iter_mut().for_each(|s| *s *= gain);
+        }
+    }
+}
+
+/// A peak limiter: nothing gets past `ceiling_db`.
+///
+/// The gain drops instantly on a peak over the ceiling and recovers over `release_ms`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Limiter {
+    pub ceiling_db: f32,
+    pub release_ms: f32,
+}
+
+impl Default for Limiter {
+    fn default() -> Self {
+        Self {
+            ceiling_db: -1.0,
+            release_ms: 50.0,
+        }
+    }
+}
+
+impl Limiter {
+    /// Limits interleaved stereo `samples` in place.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        let mut gain = GainSmoother::new(0.0, self.release_ms, sample_rate);
+
+        for frame in samples.chunks_mut(2) {
+            let over = (frame_level_db(frame) - self.ceiling_db).max(0.0);
+            let gain = from_dbfs(-gain.reduce(over));
+            frame.iter_mut().for_each(|s| *s *= gain);
+        }
+    }
+}
+
+/// Ducks the noise texture under the program: the louder the signal the noise is
+/// added to, the quieter the noise, so hiss and crackle sit behind the music and swell
+/// in the gaps like on worn media.
+///
+/// The noise drops by `depth_db` once the program's envelope reaches `threshold_db`,
+/// and proportionally less below (by amplitude, so half as much 6 dB under it).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct NoiseDucking {
+    pub depth_db: f32,
+    pub threshold_db: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+}
+
+impl Default for NoiseDucking {
+    fn default() -> Self {
+        Self {
+            depth_db: 12.0,
+            threshold_db: -20.0,
+            attack_ms: 10.0,
+            release_ms: 500.0,
+        }
+    }
+}
+
+impl NoiseDucking {
+    /// The gain of the noise while the program's envelope is `level` (linear).
+    pub fn gain(&self, level: f32) -> f32 {
+        let amount = (level / from_dbfs(self.threshold_db)).clamp(0.0, 1.0);
+        from_dbfs(-self.depth_db.max(0.0) * amount)
+    }
+
+    /// A follower of the program's envelope with the ducking's attack and release.
+    pub fn follower(&self, sample_rate: u32) -> EnvelopeFollower {
+        EnvelopeFollower::new(self.attack_ms, self.release_ms, sample_rate)
+    }
+}
+
+/// What a [`Gate`] silences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum GateMode {
+    #[default]
+    Output, // The whole result, keyed by the input: no hiss where the source is silent.
+    Program, // The filtered program before the noise: quiet passages leave only the noise.
+}
+
+/// A noise gate: silences the signal while its key stays below `threshold_db`.
+///
+/// The gate opens over `attack_ms` as soon as the key crosses the threshold, stays open
+/// for `hold_ms` after it falls back below, then closes over `release_ms` down to
+/// `floor_db`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Gate {
+    pub threshold_db: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    pub hold_ms: f32,
+    /// Gain while closed, in dB.
+    pub floor_db: f32,
+    pub mode: GateMode,
+}
+
+impl Default for Gate {
+    fn default() -> Self {
+        Self::new(-50.0, 1.0, 100.0, 50.0)
+    }
+}
+
+impl Gate {
+    /// An output gate closing fully (down to -80 dB).
+    pub fn new(threshold_db: f32, attack_ms: f32, release_ms: f32, hold_ms: f32) -> Self {
+        Self {
+            threshold_db,
+            attack_ms,
+            release_ms,
+            hold_ms,
+            floor_db: -80.0,
+            mode: GateMode::Output,
+        }
+    }
+
+    /// Gates interleaved stereo `samples` in place, opening while `key` (interleaved
+    /// stereo, aligned with `samples`) is above the threshold. Past the end of the key
+    /// the gate stays closed.
+    pub fn process(&self, samples: &mut [f32], key: &[f32], sample_rate: u32) {
+        self.run(samples, Some(key), sample_rate);
+    }
+
+    /// Gates interleaved stereo `samples` in place, keyed by the samples themselves.
+    pub fn process_self_keyed(&self, samples: &mut [f32], sample_rate: u32) {
+        self.run(samples, None, sample_rate);
+    }
+
+    // Without a key, each frame is measured before its gain is applied, so no copy of
+    // the samples is needed.
+    fn run(&self, samples: &mut [f32], key: Option<&[f32]>, sample_rate: u32) {
+        // Closing adds reduction, so it runs at the smoother's attack speed
+        let mut gain = GainSmoother::new(self.release_ms, self.attack_ms, sample_rate);
+        let hold = (self.hold_ms.max(0.0) / 1000.0 * sample_rate as f32) as usize;
+        let closed = -self.floor_db.min(0.0);
+        // Nothing has come through the key yet
+        gain.reduction = closed;
+        let mut held = 0;
+
+        for (i, frame) in samples.chunks_mut(2).enumerate() {
+            let level = match key {
+                Some(key) => frame_level_db(key.get(i * 2..i * 2 + 2).unwrap_or(&[])),
+                None => frame_level_db(frame),
+            };
+            let above = level >= self.threshold_db;
+            held = if above { hold } else { held.saturating_sub(1) };
+
+            let target = if above || held > 0 { 0.0 } else { closed };
+            let gain = from_dbfs(-gain.reduce(target));
+            frame.iter_mut().for_each(|s| *s *= gain);
+        }
+    }
+}
+
+// Envelope differences, in dB, over which a transient shaper reaches its full gains.
+const TRANSIENT_RANGE_DB: f32 = 6.0;
+
+/// A transient shaper: boosts or cuts the attack and the sustain of sounds separately,
+/// regardless of their level, e.g. to give drums back the punch a heavy low-pass took.
+///
+/// Onsets are found where a fast envelope follower runs ahead of a slow one, and tails
+/// where a slowly releasing follower lingers above a quickly releasing one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct TransientShaper {
+    /// Gain on attacks, in dB.
+    pub attack_db: f32,
+    /// Gain on sustains, in dB.
+    pub sustain_db: f32,
+    /// How long an attack lasts, as the attack time of the slow follower.
+    pub attack_ms: f32,
+    /// How long a sustain lasts, as the release time of the slow follower.
+    pub sustain_ms: f32,
+}
+
+impl Default for TransientShaper {
+    fn default() -> Self {
+        Self {
+            attack_db: 0.0,
+            sustain_db: 0.0,
+            attack_ms: 20.0,
+            sustain_ms: 300.0,
+        }
+    }
+}
+
+impl TransientShaper {
+    pub fn new(attack_db: f32, sustain_db: f32) -> Self {
+        Self {
+            attack_db,
+            sustain_db,
+            ..Default::default()
+        }
+    }
+
+    /// Shapes interleaved stereo `samples` in place.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        let mut fast = EnvelopeFollower::new(1.0, 20.0, sample_rate);
+        let mut slow_attack = EnvelopeFollower::new(self.attack_ms, 20.0, sample_rate);
+        let mut slow_release = EnvelopeFollower::new(1.0, self.sustain_ms, sample_rate);
+
+        for frame in samples.chunks_mut(2) {
+            let peak = frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+            let level = |follower: &mut EnvelopeFollower| {
+                to_dbfs(follower.process(peak)).max(LEVEL_FLOOR_DB)
+            };
+            let (fast, slow_attack, slow_release) = (
+                level(&mut fast),
+                level(&mut slow_attack),
+                level(&mut slow_release),
+            );
+
+            let attack = ((fast - slow_attack) / TRANSIENT_RANGE_DB).clamp(0.0, 1.0);
+            let sustain = ((slow_release - fast) / TRANSIENT_RANGE_DB).clamp(0.0, 1.0);
+            let gain = from_dbfs(attack * self.attack_db + sustain * self.sustain_db);
+            frame.iter_mut().for_each(|s| *s *= gain);
+        }
+    }
+}
+
+// Level of the louder channel of a frame, in dBFS.
+fn frame_level_db(frame: &[f32]) -> f32 {
+    let peak = frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    to_dbfs(peak).max(LEVEL_FLOOR_DB)
+}
+
+// Smooths a gain reduction in dB: it moves towards a larger reduction at the attack
+// speed and back towards none at the release speed.
+struct GainSmoother {
+    attack: f32,
+    release: f32,
+    reduction: f32,
+}
+
+impl GainSmoother {
+    fn new(attack_ms: f32, release_ms: f32, sample_rate: u32) -> Self {
+        Self {
+            attack: smoothing(attack_ms, sample_rate),
+            release: smoothing(release_ms, sample_rate),
+            reduction: 0.0,
+        }
+    }
+
+    // Feeds the reduction wanted for the current frame and returns the one to apply.
+    fn reduce(&mut self, target: f32) -> f32 {
+        let coefficient = if target > self.reduction {
+            self.attack
+        } else {
+            self.release
+        };
+        self.reduction = target + coefficient * (self.reduction - target);
+        self.reduction
+    }
+}
@@ -0,0 +1,225 @@
+//! Equalization curves of recording media, and bands to shape the tone of playback
+//! equipment with.
+
+use crate::analysis::Biquad;
+use crate::math;
+use std::f64::consts::PI;
+
+// RIAA time constants in seconds: bass turnover, bass shelf and treble roll-off.
+const RIAA_T1: f64 = 3180e-6;
+const RIAA_T2: f64 = 318e-6;
+const RIAA_T3: f64 = 75e-6;
+
+// Frequency both curves are normalized to, in Hz.
+const REFERENCE_HZ: f64 = 1000.0;
+
+/// The RIAA equalization of vinyl records.
+///
+/// Records are cut with the treble boosted and the bass cut (pre-emphasis), and the
+/// phono stage undoes it on playback (de-emphasis). Whatever happens to the groove in
+/// between, like surface noise or a saturating cutter head, comes out through the
+/// playback curve: hiss is tamed, rumble brought up, and loud highs distort first.
+/// Both curves pass 1 kHz unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum Riaa {
+    PreEmphasis, // Cutting: about +20 dB at 20 kHz and -20 dB at 20 Hz.
+    DeEmphasis,  // Playback: the mirror image.
+}
+
+impl Riaa {
+    /// Filters interleaved stereo `samples` in place.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        let (b, a) = self.coefficients(sample_rate);
+        let mut filters = [Biquad::new(b, a), Biquad::new(b, a)];
+
+        for frame in samples.chunks_mut(2) {
+            for (sample, filter) in frame.iter_mut().zip(&mut filters) {
+                *sample = filter.process(*sample as f64) as f32;
+            }
+        }
+    }
+
+    /// Gain of the curve at `hz` in dB, as rendered at `sample_rate`.
+    pub fn response_db(&self, hz: f32, sample_rate: u32) -> f32 {
+        let (b, a) = self.coefficients(sample_rate);
+        let gain = magnitude(&b, &[1.0, a[0], a[1]], hz as f64, sample_rate);
+        (20.0 * math::log10_f64(gain)) as f32
+    }
+
+    // Biquad coefficients with the poles and zeros of the analog curve mapped by the
+    // matched z-transform, which keeps the treble within a dB of the standard up to
+    // 10 kHz at 44.1 kHz. Scaled to unity gain at the reference frequency.
+    fn coefficients(&self, sample_rate: u32) -> ([f64; 3], [f64; 2]) {
+        let fs = sample_rate.max(1) as f64;
+        let root = |t: f64| math::exp_f64(-1.0 / (t * fs));
+        let (p1, z2, p3) = (root(RIAA_T1), root(RIAA_T2), root(RIAA_T3));
+
+        let one_root = [1.0, -z2, 0.0];
+        let two_roots = [1.0, -(p1 + p3), p1 * p3];
+        let (b, a) = match self {
+            Riaa::DeEmphasis => (one_root, two_roots),
+            // The exact inverse, so the two cancel out
+            Riaa::PreEmphasis => (two_roots, one_root),
+        };
+
+        let reference = magnitude(&b, &a, REFERENCE_HZ, sample_rate);
+        (b.map(|b| b / reference), [a[1], a[2]])
+    }
+}
+
+/// The shape of an [`EqBand`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum BandKind {
+    HighPass, // Removes everything below the frequency, 12 dB per octave.
+    LowPass,  // Removes everything above the frequency, 12 dB per octave.
+    Peak,     // Raises or lowers the frequencies around it, like a resonance.
+}
+
+impl BandKind {
+    /// Every band kind.
+    pub const ALL: [BandKind; 3] = [BandKind::HighPass, BandKind::LowPass, BandKind::Peak];
+
+    /// Returns the name used on the command line and in config files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BandKind::HighPass => "high-pass",
+            BandKind::LowPass => "low-pass",
+            BandKind::Peak => "peak",
+        }
+    }
+
+    pub fn try_from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown EQ band: {} (use high-pass, low-pass or peak)", s))
+    }
+}
+
+/// One second-order filter band (after the RBJ audio EQ cookbook).
+///
+/// Bands of a config run one after another, so a high-pass listed twice falls off at
+/// 24 dB per octave. A few narrow peaks give the honky resonances of a horn or a small
+/// cabinet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EqBand {
+    pub kind: BandKind,
+    pub frequency_hz: f32,
+    /// Sharpness of the band: 0.71 is a flat pass band, higher values resonate. [default: 0.71]
+    #[cfg_attr(feature = "serde", serde(default = "EqBand::default_q"))]
+    pub q: f32,
+    /// Boost (or cut) of a peak, in dB. Ignored by the passes. [default: 0]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub gain_db: f32,
+}
+
+impl EqBand {
+    /// A high- or low-pass with a flat pass band.
+    pub fn pass(kind: BandKind, frequency_hz: f32) -> Self {
+        Self {
+            kind,
+            frequency_hz,
+            q: Self::default_q(),
+            gain_db: 0.0,
+        }
+    }
+
+    /// A peak of `gain_db` at `frequency_hz`, `q` wide.
+    pub fn peak(frequency_hz: f32, gain_db: f32, q: f32) -> Self {
+        Self {
+            kind: BandKind::Peak,
+            frequency_hz,
+            q,
+            gain_db,
+        }
+    }
+
+    fn default_q() -> f32 {
+        std::f32::consts::FRAC_1_SQRT_2
+    }
+
+    /// Filters interleaved stereo `samples` in place.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        let (b, a) = self.coefficients(sample_rate);
+        let mut filters = [Biquad::new(b, a), Biquad::new(b, a)];
+
+        for frame in samples.chunks_mut(2) {
+            for (sample, filter) in frame.iter_mut().zip(&mut filters) {
+                *sample = filter.process(*sample as f64) as f32;
+            }
+        }
+    }
+
+    /// Gain of the band at `hz` in dB, as rendered at `sample_rate`.
+    pub fn response_db(&self, hz: f32, sample_rate: u32) -> f32 {
+        let (b, a) = self.coefficients(sample_rate);
+        let gain = magnitude(&b, &[1.0, a[0], a[1]], hz as f64, sample_rate);
+        (20.0 * math::log10_f64(gain)) as f32
+    }
+
+    // Complex response of the band at `hz`, as (real, imaginary).
+    pub(crate) fn transfer(&self, hz: f32, sample_rate: u32) -> (f64, f64) {
+        let (b, a) = self.coefficients(sample_rate);
+        let w = 2.0 * PI * hz as f64 / sample_rate.max(1) as f64;
+        divide(evaluate(&b, w), evaluate(&[1.0, a[0], a[1]], w))
+    }
+
+    fn coefficients(&self, sample_rate: u32) -> ([f64; 3], [f64; 2]) {
+        let fs = sample_rate.max(1) as f64;
+        // Just below Nyquist, where the filter is still stable
+        let hz = (self.frequency_hz as f64).clamp(1.0, 0.49 * fs);
+        let w = 2.0 * PI * hz / fs;
+        let (sin, cos) = (math::sin_f64(w), math::cos_f64(w));
+        let alpha = sin / (2.0 * (self.q as f64).max(0.01));
+
+        let (b, a) = match self.kind {
+            BandKind::HighPass => (
+                [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+                [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+            ),
+            BandKind::LowPass => (
+                [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0],
+                [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+            ),
+            BandKind::Peak => {
+                let gain = math::powf_f64(10.0, self.gain_db as f64 / 40.0);
+                (
+                    [1.0 + alpha * gain, -2.0 * cos, 1.0 - alpha * gain],
+                    [1.0 + alpha / gain, -2.0 * cos, 1.0 - alpha / gain],
+                )
+            }
+        };
+        (b.map(|b| b / a[0]), [a[1] / a[0], a[2] / a[0]])
+    }
+}
+
+// Magnitude of the response of b(z) / a(z) at `hz`.
+fn magnitude(b: &[f64; 3], a: &[f64; 3], hz: f64, sample_rate: u32) -> f64 {
+    let w = 2.0 * PI * hz / sample_rate.max(1) as f64;
+    let (re, im) = divide(evaluate(b, w), evaluate(a, w));
+    (re * re + im * im).sqrt()
+}
+
+// The polynomial c0 + c1 z^-1 + c2 z^-2 at z = e^jw, as (real, imaginary).
+fn evaluate(c: &[f64; 3], w: f64) -> (f64, f64) {
+    let re = c[0] + c[1] * math::cos_f64(w) + c[2] * math::cos_f64(2.0 * w);
+    let im = -c[1] * math::sin_f64(w) - c[2] * math::sin_f64(2.0 * w);
+    (re, im)
+}
+
+// Complex division of (real, imaginary) pairs.
+fn divide((a, b): (f64, f64), (c, d): (f64, f64)) -> (f64, f64) {
+    let norm = c * c + d * d;
+    ((a * c + b * d) / norm, (b * c - a * d) / norm)
+}
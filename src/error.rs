@@ -12,6 +12,35 @@ pub enum OxidizerError {
     #[error("Invalid value: {0}")]
     InvalidValue(String),
 
+    /// A parameter is outside the range it can take.
+    #[error("Invalid {name}: {value} (expected {expected_range})")]
+    InvalidParameter {
+        name: &'static str,
+        value: f64,
+        expected_range: String,
+    },
+
+    /// A file or codec can't be read or written, with the formats that can.
+    #[error("Unsupported format: {found} (supported: {})", .supported.join(", "))]
+    UnsupportedFormat {
+        found: String,
+        supported: &'static [&'static str],
+    },
+
+    /// Audio has another number of channels than it should: a decoded packet differs
+    /// from its track (or from the packets before it), or planar input has more than
+    /// two channels or none.
+    #[error("Expected {expected} channel(s), found {found}")]
+    ChannelMismatch { expected: usize, found: usize },
+
+    /// Channels that have to be equally long aren't.
+    #[error("Expected {expected} samples, found {found}")]
+    LengthMismatch { expected: usize, found: usize },
+
+    /// A file decoded to no audio at all, e.g. when the time range starts past its end.
+    #[error("No audio in {}", .0.display())]
+    EmptyInput(std::path::PathBuf),
+
     /// Errors occurring during the decoding of raw audio packets.
     #[error("Decoding failed: {0}")]
     Decoding(String),
@@ -23,7 +52,45 @@ pub enum OxidizerError {
     /// Errors passed through from the Symphonia multimedia framework.
     #[error("Symphonia error: {0}")]
     Symphonia(String),
+
+    /// Errors reading, parsing or writing configuration files.
+    #[error("Config error: {0}")]
+    Config(String),
+
+    /// The output file exists and overwriting it wasn't allowed.
+    #[error("Output file already exists: {}", .0.display())]
+    OutputExists(std::path::PathBuf),
+
+    /// The operation was aborted through a cancellation token.
+    #[error("Processing was cancelled")]
+    Cancelled,
+
+    /// Errors raised while running a background processing job.
+    #[error("Job failed: {0}")]
+    Job(String),
+
+    /// Errors reported by the JACK server or client library.
+    #[cfg(feature = "jack")]
+    #[error("JACK error: {0}")]
+    Jack(String),
 }
 
 /// A specialized Result type for Oxidizer operations.
 pub type Result<T> = std::result::Result<T, OxidizerError>;
+
+// Fails with `InvalidParameter` unless `range` contains `value` (NaN never passes).
+pub(crate) fn check_range(
+    name: &'static str,
+    value: f64,
+    range: impl std::ops::RangeBounds<f64>,
+    expected_range: impl std::fmt::Display,
+) -> Result<()> {
+    match range.contains(&value) {
+        true => Ok(()),
+        false => Err(OxidizerError::InvalidParameter {
+            name,
+            value,
+            expected_range: expected_range.to_string(),
+        }),
+    }
+}
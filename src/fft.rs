@@ -0,0 +1,76 @@
+//! A small radix-2 fast Fourier transform for the spectral effects and measurements.
+//!
+//! Signals are split into real and imaginary parts, each a slice whose length is a power
+//! of two. The transforms work in place.
+
+use crate::math;
+use std::f64::consts::PI;
+
+/// Transforms `re` + j`im` from the time domain to the frequency domain.
+///
+/// # Panics
+/// If the two slices differ in length or the length isn't a power of two.
+pub fn forward(re: &mut [f32], im: &mut [f32]) {
+    transform(re, im, -1.0);
+}
+
+/// Transforms `re` + j`im` back from the frequency domain to the time domain, scaled so
+/// that `inverse` undoes [`forward`].
+///
+/// # Panics
+/// Like [`forward`].
+pub fn inverse(re: &mut [f32], im: &mut [f32]) {
+    transform(re, im, 1.0);
+    let scale = 1.0 / re.len().max(1) as f32;
+    re.iter_mut().chain(im.iter_mut()).for_each(|x| *x *= scale);
+}
+
+/// A periodic Hann window of `size` points. Frames overlapping by half a window sum to
+/// a constant under it.
+pub fn hann(size: usize) -> Vec<f32> {
+    (0..size)
+        .map(|i| (0.5 - 0.5 * math::cos_f64(2.0 * PI * i as f64 / size as f64)) as f32)
+        .collect()
+}
+
+// Iterative Cooley-Tukey with the twiddles turning in the direction of `sign`.
+fn transform(re: &mut [f32], im: &mut [f32], sign: f64) {
+    let n = re.len();
+    assert_eq!(n, im.len(), "real and imaginary parts differ in length");
+    assert!(n.is_power_of_two(), "FFT length {} isn't a power of two", n);
+
+    // Bit-reversed order, so the butterflies can combine neighbours
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i
+            .reverse_bits()
+            .checked_shr(usize::BITS - bits)
+            .unwrap_or(0);
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut size = 2;
+    while size <= n {
+        let half = size / 2;
+        let angle = sign * 2.0 * PI / size as f64;
+        for k in 0..half {
+            let (w_re, w_im) = (
+                math::cos_f64(angle * k as f64) as f32,
+                math::sin_f64(angle * k as f64) as f32,
+            );
+            for start in (0..n).step_by(size) {
+                let (a, b) = (start + k, start + k + half);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        size *= 2;
+    }
+}
@@ -0,0 +1,99 @@
+//! Mains hum and buzz picked up through a bad ground.
+//!
+//! A [`GroundLoop`] doesn't hum on its own: the hum rides on the program's envelope,
+//! the way a dirty amplifier buzzes harder the more current it draws.
+
+use crate::analysis::from_dbfs;
+use crate::math;
+use crate::modulation::EnvelopeFollower;
+
+// Highest harmonic of the mains frequency in the buzz.
+const HARMONICS: usize = 16;
+
+// Entries in the single-cycle wavetable of the buzz.
+const TABLE_SIZE: usize = 2048;
+
+// Attack and release of the envelope the hum follows, in milliseconds.
+const ATTACK_MS: f32 = 10.0;
+const RELEASE_MS: f32 = 200.0;
+
+/// Ground-loop hum: the mains fundamental and its harmonics, scaled by the level of the
+/// program and added to both channels.
+///
+/// The tilt shapes the tone of the buzz. Around 0 dB per octave it's a harsh rasp,
+/// strongly negative values leave a dull hum close to a pure tone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct GroundLoop {
+    /// Mains frequency, in Hz: 50 in most of the world, 60 in the Americas.
+    pub frequency_hz: f32,
+    /// Level change of the harmonics per octave above the fundamental, in dB.
+    pub tilt_db: f32,
+    /// Peak level of the hum relative to the program's envelope, in dB.
+    pub level_db: f32,
+}
+
+impl Default for GroundLoop {
+    fn default() -> Self {
+        Self {
+            frequency_hz: 50.0,
+            tilt_db: -6.0,
+            level_db: -30.0,
+        }
+    }
+}
+
+impl GroundLoop {
+    /// Adds the hum to interleaved stereo `samples` in place.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        let rate = sample_rate.max(1) as f64;
+        let table = self.wavetable(rate);
+        let gain = from_dbfs(self.level_db);
+        let step = self.frequency_hz.max(0.0) as f64 / rate * TABLE_SIZE as f64;
+        let mut envelope = EnvelopeFollower::new(ATTACK_MS, RELEASE_MS, sample_rate);
+        let mut phase = 0.0f64;
+
+        for frame in samples.chunks_exact_mut(2) {
+            let level = envelope.process(frame[0].abs().max(frame[1].abs()));
+
+            let index = phase as usize;
+            let fraction = (phase - index as f64) as f32;
+            let (a, b) = (table[index], table[(index + 1) % TABLE_SIZE]);
+            let hum = (a + (b - a) * fraction) * level * gain;
+            for sample in frame {
+                *sample += hum;
+            }
+
+            phase = (phase + step) % TABLE_SIZE as f64;
+        }
+    }
+
+    // One cycle of the buzz, normalized to a peak of 1.0. Harmonics at or above Nyquist are
+    // left out so the buzz doesn't alias.
+    fn wavetable(&self, rate: f64) -> Vec<f32> {
+        let amplitudes: Vec<f32> = (1..=HARMONICS)
+            .take_while(|&k| k as f64 * (self.frequency_hz as f64) < rate / 2.0)
+            .map(|k| from_dbfs(self.tilt_db * math::ln(k as f32) / std::f32::consts::LN_2))
+            .collect();
+
+        let mut table: Vec<f32> = (0..TABLE_SIZE)
+            .map(|i| {
+                let phase = std::f64::consts::TAU * i as f64 / TABLE_SIZE as f64;
+                amplitudes
+                    .iter()
+                    .enumerate()
+                    .map(|(k, amplitude)| amplitude * math::sin_f64(phase * (k + 1) as f64) as f32)
+                    .sum::<f32>()
+            })
+            .collect();
+        let peak = table.iter().fold(f32::EPSILON, |peak, s| peak.max(s.abs()));
+        for sample in &mut table {
+            *sample /= peak;
+        }
+        table
+    }
+}
@@ -1,48 +1,376 @@
 use crate::error::{OxidizerError, Result};
+use crate::math;
 use std::fs::File;
+use std::ops::Range;
+use std::path::Path;
+use std::time::Duration;
 use symphonia::core::{
-    audio::Signal,
+    audio::{AudioBuffer, AudioBufferRef, Signal},
     codecs::{CODEC_TYPE_NULL, DecoderOptions},
     errors::Error,
-    formats::FormatOptions,
+    formats::{FormatOptions, FormatReader, SeekMode, SeekTo, Track},
     io::MediaSourceStream,
-    meta::MetadataOptions,
+    meta::{MetadataOptions, MetadataRevision},
+    probe::ProbeResult,
+    units::Time,
 };
 
-/// Decodes an audio file from the given path into a flat vector of f32 samples.
-///
-/// This function supports any format recognized by Symphonia (MP3, WAV, FLAC, etc.).
-/// It automatically converts mono signals to stereo by duplicating the channel,
-/// resulting in an interleaved [L, R, L, R, ...] buffer.
-pub fn load_audio(path: &std::path::Path) -> Result<Vec<f32>> {
+mod flac;
+
+// Number of samples written between two encoder progress reports.
+const PROGRESS_INTERVAL: usize = 1 << 16;
+
+// How far before a range's start decoding resumes after a seek. Codecs with inter-frame
+// state (e.g. the MP3 bit reservoir) need a few packets to warm up before their output
+// matches a decode from the beginning of the file.
+const SEEK_PREROLL: Duration = Duration::from_millis(100);
+
+/// Input formats the decoder can read.
+pub const INPUT_FORMATS: &[&str] = &["WAV", "FLAC", "MP3", "Ogg Vorbis", "MKV/WebM", "MP4"];
+
+// Starting value of an FNV-1a hash.
+pub(crate) const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+
+// Largest positive value of a signed 24-bit sample.
+const I24_MAX: f32 = 8_388_607.0;
+
+/// Stream properties read from a file's headers, without decoding any audio.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioInfo {
+    /// Sample rate of the audio track, if declared.
+    pub sample_rate: Option<u32>,
+    /// Number of channels of the audio track, if declared.
+    pub channels: Option<usize>,
+    /// Number of frames (samples per channel) in the track, if declared.
+    pub frames: Option<u64>,
+}
+
+impl AudioInfo {
+    /// Returns the track length, if both its frame count and sample rate are known.
+    pub fn duration(&self) -> Option<Duration> {
+        match (self.frames, self.sample_rate) {
+            (Some(frames), Some(rate)) if rate > 0 => {
+                Some(Duration::from_secs_f64(frames as f64 / rate as f64))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// What a file is made of, read from its headers without decoding any audio.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileDetails {
+    pub stream: AudioInfo,
+    /// Container format (e.g. `WAV`, `MP4`), if recognized from the file's signature.
+    pub container: Option<&'static str>,
+    /// Name of the codec of the audio track, e.g. `MPEG Audio Layer 3`.
+    pub codec: String,
+    /// Bits per sample of the encoded audio, if declared. Lossy codecs have none.
+    pub bit_depth: Option<u32>,
+    /// Embedded tags (title, artist, ...) as `(name, value)` pairs, in file order.
+    pub tags: Vec<(String, String)>,
+}
+
+/// A window of an audio file, measured from the beginning of the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TimeRange {
+    /// Offset of the first decoded frame.
+    pub start: Duration,
+    /// Length of the window, or `None` to read until the end of the file.
+    pub duration: Option<Duration>,
+}
+
+impl TimeRange {
+    pub fn new(start: Duration, duration: Option<Duration>) -> Self {
+        Self { start, duration }
+    }
+
+    /// Returns the window as `(first_frame, end_frame)` at the given sample rate.
+    pub(crate) fn frames(&self, sample_rate: u32) -> (u64, Option<u64>) {
+        let to_frames = |d: Duration| (d.as_secs_f64() * sample_rate as f64).round() as u64;
+        let start = to_frames(self.start);
+        (start, self.duration.map(|d| start + to_frames(d)))
+    }
+}
+
+/// Reads the properties of the first audio track in a file.
+pub fn probe(path: &Path) -> Result<AudioInfo> {
+    let (_, track) = open_track(path)?;
+    let params = &track.codec_params;
+
+    Ok(AudioInfo {
+        sample_rate: params.sample_rate,
+        channels: params.channels.map(|c| c.count()),
+        frames: params.n_frames,
+    })
+}
+
+/// Reads the container, codec, stream properties and tags of a file. Only the headers
+/// are parsed, so this is quick even for long recordings.
+pub fn inspect(path: &Path) -> Result<FileDetails> {
+    let mut probed = probe_format(path)?;
+    let track = first_track(probed.format.as_ref(), path)?;
+    let params = &track.codec_params;
+
+    let codec = symphonia::default::get_codecs()
+        .get_codec(params.codec)
+        .map_or_else(
+            || format!("{}", params.codec),
+            |codec| codec.long_name.to_string(),
+        );
+
+    // Tags can sit in front of the container (e.g. ID3) or inside it
+    let mut tags = Vec::new();
+    let mut collect = |revision: Option<&MetadataRevision>| {
+        for tag in revision.map(MetadataRevision::tags).unwrap_or_default() {
+            let name = match tag.std_key {
+                Some(key) => format!("{:?}", key),
+                None => tag.key.clone(),
+            };
+            tags.push((name, tag.value.to_string()));
+        }
+    };
+    if let Some(mut metadata) = probed.metadata.get() {
+        collect(metadata.skip_to_latest());
+    }
+    collect(probed.format.metadata().skip_to_latest());
+
+    Ok(FileDetails {
+        stream: AudioInfo {
+            sample_rate: params.sample_rate,
+            channels: params.channels.map(|c| c.count()),
+            frames: params.n_frames,
+        },
+        container: container_of(path)?,
+        codec,
+        bit_depth: params.bits_per_sample,
+        tags,
+    })
+}
+
+/// Returns a fingerprint of the bytes of a file, as 16 hex digits (64-bit FNV-1a). Not
+/// cryptographic, but enough to tell whether an input has changed since a render.
+pub fn file_hash(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut buffer = vec![0u8; 1 << 16];
+    let mut hash = FNV_OFFSET;
+    loop {
+        let read = std::io::Read::read(&mut file, &mut buffer)?;
+        if read == 0 {
+            return Ok(format!("{:016x}", hash));
+        }
+        hash = fnv1a(hash, &buffer[..read]);
+    }
+}
+
+// Feeds `bytes` into an FNV-1a hash. Unlike the standard hasher, its output never
+// changes between Rust releases, so it can be stored.
+pub(crate) fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
+
+// Recognizes the container of a file by the signature at its start.
+fn container_of(path: &Path) -> Result<Option<&'static str>> {
+    let mut header = [0u8; 12];
+    let read = std::io::Read::read(&mut File::open(path)?, &mut header)?;
+    let header = &header[..read];
+
+    Ok(match header {
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'A',
+            b'V',
+            b'E',
+            ..,
+        ] => Some("WAV"),
+        [b'f', b'L', b'a', b'C', ..] => Some("FLAC"),
+        [b'O', b'g', b'g', b'S', ..] => Some("Ogg"),
+        [0x1A, 0x45, 0xDF, 0xA3, ..] => Some("MKV/WebM"),
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some("MP4"),
+        // An ID3 tag or an MPEG frame sync
+        [b'I', b'D', b'3', ..] => Some("MP3"),
+        [0xFF, second, ..] if second & 0xE0 == 0xE0 => Some("MP3"),
+        _ => None,
+    })
+}
+
+// Probes the file and returns its format reader along with the first decodable track.
+fn open_track(path: &Path) -> Result<(Box<dyn FormatReader>, Track)> {
+    let probed = probe_format(path)?;
+    let track = first_track(probed.format.as_ref(), path)?;
+    Ok((probed.format, track))
+}
+
+fn probe_format(path: &Path) -> Result<ProbeResult> {
     let src = File::open(path)?;
     let mss = MediaSourceStream::new(Box::new(src), Default::default());
 
     // Probe the input file for format and metadata
-    let mut probed = symphonia::default::get_probe()
+    symphonia::default::get_probe()
         .format(
             &Default::default(),
             mss,
             &FormatOptions::default(),
             &MetadataOptions::default(),
         )
-        .map_err(|e| OxidizerError::Symphonia(e.to_string()))?;
-
-    let format = &mut probed.format;
+        .map_err(|e| match e {
+            Error::Unsupported(_) => unsupported(file_name(path)),
+            Error::IoError(e) => OxidizerError::Io(e),
+            e => OxidizerError::Symphonia(e.to_string()),
+        })
+}
 
-    // Find the first track with a valid codec
-    let track = format
+// Finds the first track with a valid codec.
+fn first_track(format: &dyn FormatReader, path: &Path) -> Result<Track> {
+    format
         .tracks()
         .iter()
         .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
-        .ok_or_else(|| OxidizerError::Decoding("No supported audio track found".to_string()))?;
+        .cloned()
+        .ok_or_else(|| unsupported(format!("{} (no audio track)", file_name(path))))
+}
+
+fn unsupported(found: String) -> OxidizerError {
+    OxidizerError::UnsupportedFormat {
+        found,
+        supported: INPUT_FORMATS,
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Decodes an audio file from the given path into a flat vector of f32 samples.
+///
+/// This function supports any format recognized by Symphonia (MP3, WAV, FLAC, etc.).
+/// Integer sample formats are converted to f32. Mono signals are converted to stereo
+/// by duplicating the channel,
+/// resulting in an interleaved [L, R, L, R, ...] buffer.
+pub fn load_audio(path: &Path) -> Result<Vec<f32>> {
+    load_audio_with_progress(path, |_, _| Ok(()))
+}
+
+/// Appends interleaved stereo `next` to `program`, overlapping the last
+/// `crossfade_frames` of the program with the start of `next`.
+///
+/// The crossfade is equal-power, so material that isn't correlated across the join
+/// (like two different songs) keeps a steady level. It's shortened to fit if either
+/// side is shorter; 0 butts the two together.
+pub fn append_crossfaded(program: &mut Vec<f32>, next: &[f32], crossfade_frames: usize) {
+    let overlap = crossfade_frames.min(program.len() / 2).min(next.len() / 2);
+    let start = program.len() - overlap * 2;
+
+    for (i, (out, &incoming)) in program[start..].iter_mut().zip(next).enumerate() {
+        let position = ((i / 2) as f32 + 0.5) / overlap as f32;
+        let angle = position * std::f32::consts::FRAC_PI_2;
+        *out = *out * math::cos(angle) + incoming * math::sin(angle);
+    }
+    program.extend_from_slice(&next[overlap * 2..]);
+}
+
+/// Same as [`load_audio`], but reports `(decoded_frames, total_frames)` after every packet.
+///
+/// `total_frames` is 0 when the container doesn't declare its length.
+/// Decoding stops early with the callback's error if it returns one.
+pub fn load_audio_with_progress(
+    path: &Path,
+    progress: impl FnMut(u64, u64) -> Result<()>,
+) -> Result<Vec<f32>> {
+    load_audio_range_with_progress(path, TimeRange::default(), progress)
+}
+
+/// Decodes only the given window of an audio file.
+///
+/// The reader seeks to the start of the window when the format allows it, and decoding
+/// stops as soon as the end is reached, so auditioning a few seconds of a long recording
+/// doesn't require decoding all of it. A window reaching past the end of the file is cut short.
+pub fn load_audio_range(path: &Path, range: TimeRange) -> Result<Vec<f32>> {
+    load_audio_range_with_progress(path, range, |_, _| Ok(()))
+}
+
+/// Same as [`load_audio_range`], but reports `(decoded_frames, total_frames)` after
+/// every packet, counted within the window.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info", skip_all, fields(path = %path.display(), ?range)))]
+pub fn load_audio_range_with_progress(
+    path: &Path,
+    range: TimeRange,
+    mut progress: impl FnMut(u64, u64) -> Result<()>,
+) -> Result<Vec<f32>> {
+    let (mut format, track) = open_track(path)?;
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        codec = ?track.codec_params.codec,
+        sample_rate = ?track.codec_params.sample_rate,
+        frames = ?track.codec_params.n_frames,
+        "selected track"
+    );
+
+    let track_id = track.id;
+    let params = &track.codec_params;
+    let mut channels = params.channels.map(|c| c.count());
+    let (start_frame, end_frame) = match params.sample_rate {
+        Some(rate) => range.frames(rate),
+        None if range == TimeRange::default() => (0, None),
+        None => {
+            return Err(OxidizerError::Decoding(
+                "Can't decode a time range: unknown sample rate".to_string(),
+            ));
+        }
+    };
+
+    let total_frames = match (params.n_frames, end_frame) {
+        (Some(n), Some(end)) => end.min(n).saturating_sub(start_frame),
+        (Some(n), None) => n.saturating_sub(start_frame),
+        (None, Some(end)) => end - start_frame,
+        (None, None) => 0,
+    };
+
+    if start_frame > 0 {
+        let seek = SeekTo::Time {
+            time: Time::from(range.start.saturating_sub(SEEK_PREROLL).as_secs_f64()),
+            track_id: Some(track_id),
+        };
+        // Unseekable streams are decoded from the start; the window is still applied below
+        if let Err(_e) = format.seek(SeekMode::Accurate, seek) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(error = %_e, "seek failed, decoding from the start");
+        }
+    }
 
     // Initialize the decoder for the selected track
     let mut decoder = symphonia::default::get_codecs()
-        .make(&track.codec_params, &DecoderOptions::default())
-        .map_err(|e| OxidizerError::Decoding(format!("Couldn't create a decoder: {}", e)))?;
+        .make(params, &DecoderOptions::default())
+        .map_err(|e| match e {
+            Error::Unsupported(_) => unsupported(format!("the codec of {}", file_name(path))),
+            e => OxidizerError::Decoding(format!("Couldn't create a decoder: {}", e)),
+        })?;
+
+    // Converts a packet timestamp to a frame index
+    let time_base = params.time_base;
+    let sample_rate = params.sample_rate.unwrap_or(0) as f64;
+    let frame_of = |ts: u64| match time_base {
+        Some(tb) => {
+            let time = tb.calc_time(ts);
+            ((time.seconds as f64 + time.frac) * sample_rate).round() as u64
+        }
+        None => ts,
+    };
 
-    let track_id = track.id;
     let mut samples: Vec<f32> = Vec::new();
 
     // Decode packets loop
@@ -51,55 +379,277 @@ pub fn load_audio(path: &std::path::Path) -> Result<Vec<f32>> {
             continue;
         }
 
+        let packet_start = frame_of(packet.ts());
+        if end_frame.is_some_and(|end| packet_start >= end) {
+            break;
+        }
+
         match decoder.decode(&packet) {
-            Ok(symphonia::core::audio::AudioBufferRef::F32(buf)) => {
-                let chan_l = buf.chan(0);
-                // If the source is mono, use channel 0 for both L and R
-                let chan_r = if buf.spec().channels.count() > 1 {
-                    buf.chan(1)
-                } else {
-                    buf.chan(0)
-                };
-
-                // Interleave channels into the samples vector
-                for i in 0..buf.frames() {
-                    samples.push(chan_l[i]);
-                    samples.push(chan_r[i]);
-                }
+            Ok(AudioBufferRef::F32(buf)) => {
+                check_channels(&buf, &mut channels)?;
+                let window = window_in(&buf, packet_start, start_frame, end_frame);
+                interleave_stereo(&buf, window, &mut samples);
+                progress(samples.len() as u64 / 2, total_frames)?;
+            }
+            // Integer and f64 buffers (e.g., S16, S24, S32) are converted to f32 first
+            Ok(decoded) => {
+                let mut buf = decoded.make_equivalent::<f32>();
+                decoded.convert(&mut buf);
+                check_channels(&buf, &mut channels)?;
+                let window = window_in(&buf, packet_start, start_frame, end_frame);
+                interleave_stereo(&buf, window, &mut samples);
+                progress(samples.len() as u64 / 2, total_frames)?;
+            }
+            // A corrupt packet (or one missing its predecessor right after a seek) is skipped
+            Err(Error::DecodeError(_e)) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(error = _e, "skipping undecodable packet");
             }
-            // Currently ignores non-f32 buffers (e.g., S16, S24, S32)
-            Ok(_) => {}
             Err(Error::IoError(_)) => break,
             Err(e) => {
                 return Err(OxidizerError::Symphonia(e.to_string()));
             }
         }
     }
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        frames = samples.len() / 2,
+        peak = crate::analysis::peak(&samples),
+        "decoded"
+    );
     Ok(samples)
 }
 
+// Fails for a buffer without channels, which has nothing to interleave, and for one
+// whose channels differ from the track's, or from the first buffer's when the track
+// doesn't declare them.
+fn check_channels(buf: &AudioBuffer<f32>, expected: &mut Option<usize>) -> Result<()> {
+    let found = buf.spec().channels.count();
+    match *expected.get_or_insert(found) {
+        expected if found == 0 || found != expected => Err(OxidizerError::ChannelMismatch {
+            expected: expected.max(1),
+            found,
+        }),
+        _ => Ok(()),
+    }
+}
+
+// Returns the frames of a buffer starting at `packet_start` that fall inside the window.
+fn window_in(
+    buf: &AudioBuffer<f32>,
+    packet_start: u64,
+    start_frame: u64,
+    end_frame: Option<u64>,
+) -> Range<usize> {
+    let frames = buf.frames() as u64;
+    let first = start_frame.saturating_sub(packet_start).min(frames);
+    let last = end_frame.map_or(frames, |end| end.saturating_sub(packet_start).min(frames));
+    first as usize..last.max(first) as usize
+}
+
 /// Saves the provided f32 sample data into a 16-bit PCM WAV file.
 ///
 /// The data is expected to be interleaved stereo. Samples are scaled from the
 /// [-1.0, 1.0] range to the 16-bit integer range [-32768, 32767].
 pub fn save_audio(path: &String, data: Vec<f32>, sample_rate: u32) -> Result<()> {
-    // Define the WAV forma: Stereo, 16-bit PCM
-    let spec = hound::WavSpec {
-        channels: 2,
-        sample_rate,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
+    save_audio_with_progress(path, data, sample_rate, BitDepth::Int16, |_, _| Ok(()))
+}
+
+// Appends the given frames of the buffer as interleaved stereo.
+// Mono sources are duplicated into both channels.
+fn interleave_stereo(buf: &AudioBuffer<f32>, frames: Range<usize>, samples: &mut Vec<f32>) {
+    let chan_l = buf.chan(0);
+    // If the source is mono, use channel 0 for both L and R
+    let chan_r = if buf.spec().channels.count() > 1 {
+        buf.chan(1)
+    } else {
+        buf.chan(0)
     };
 
+    // Interleave channels into the samples vector
+    for i in frames {
+        samples.push(chan_l[i]);
+        samples.push(chan_r[i]);
+    }
+}
+
+/// Container and codec of written files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum AudioFormat {
+    #[default]
+    Wav, // Uncompressed RIFF/WAVE.
+    Flac, // Lossless compression, roughly half the size of WAV.
+}
+
+impl AudioFormat {
+    /// Every supported output format.
+    pub const ALL: [AudioFormat; 2] = [AudioFormat::Wav, AudioFormat::Flac];
+
+    /// Returns the name used in config files and on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Flac => "flac",
+        }
+    }
+
+    /// Returns a short description of the format.
+    pub fn description(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "Uncompressed WAV (16/24-bit PCM or 32-bit float)",
+            AudioFormat::Flac => "Lossless FLAC (16 or 24-bit)",
+        }
+    }
+
+    /// Returns the file extension, without the dot.
+    pub fn extension(&self) -> &'static str {
+        self.name()
+    }
+
+    /// Picks the format matching a file's extension, if it is one of ours.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension().eq_ignore_ascii_case(extension))
+    }
+
+    pub fn try_from_str(s: &str) -> std::result::Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown output format: {}. Use 'wav' or 'flac'", s))
+    }
+
+    /// Checks that the format can store samples of the given bit depth.
+    pub fn check(&self, bit_depth: BitDepth) -> Result<()> {
+        match (self, bit_depth) {
+            // FLAC only stores integer samples
+            (AudioFormat::Flac, BitDepth::Float32) => Err(OxidizerError::UnsupportedFormat {
+                found: "32-bit float FLAC".to_string(),
+                supported: &["16 or 24-bit FLAC", "16, 24 or 32-bit float WAV"],
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Sample format of written files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum BitDepth {
+    Int16,   // 16-bit PCM, CD quality.
+    Int24,   // 24-bit PCM, studio deliverables.
+    Float32, // 32-bit IEEE float, lossless headroom above 0 dBFS.
+}
+
+impl BitDepth {
+    /// Every supported sample format.
+    pub const ALL: [BitDepth; 3] = [BitDepth::Int16, BitDepth::Int24, BitDepth::Float32];
+
+    /// Returns the name used in config files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BitDepth::Int16 => "int16",
+            BitDepth::Int24 => "int24",
+            BitDepth::Float32 => "float32",
+        }
+    }
+
+    /// Returns a short description of the format.
+    pub fn description(&self) -> &'static str {
+        match self {
+            BitDepth::Int16 => "16-bit PCM, CD quality",
+            BitDepth::Int24 => "24-bit PCM, studio deliverables",
+            BitDepth::Float32 => "32-bit IEEE float, lossless headroom above 0 dBFS",
+        }
+    }
+
+    /// Parses a bit depth given as bits ("16", "24", "32f") or by name ("int16", ...).
+    pub fn try_from_str(s: &str) -> std::result::Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "16" | "int16" => Ok(BitDepth::Int16),
+            "24" | "int24" => Ok(BitDepth::Int24),
+            "32f" | "float32" => Ok(BitDepth::Float32),
+            "32" => Err("32-bit output is floating point only. Use '32f'".to_string()),
+            _ => Err(format!("Unknown bit depth: {}. Use 16, 24 or 32f", s)),
+        }
+    }
+
+    // Scales a sample from [-1.0, 1.0] to the integer range, clipping anything outside it.
+    fn to_int(self, sample: f32) -> i32 {
+        let max = match self {
+            BitDepth::Int16 => i16::MAX as f32,
+            _ => I24_MAX,
+        };
+        (sample.clamp(-1.0, 1.0) * max) as i32
+    }
+
+    fn bits(&self) -> u32 {
+        match self {
+            BitDepth::Int16 => 16,
+            BitDepth::Int24 => 24,
+            BitDepth::Float32 => 32,
+        }
+    }
+
+    fn spec(&self, sample_rate: u32) -> hound::WavSpec {
+        let sample_format = match self {
+            BitDepth::Float32 => hound::SampleFormat::Float,
+            _ => hound::SampleFormat::Int,
+        };
+
+        hound::WavSpec {
+            channels: 2,
+            sample_rate,
+            bits_per_sample: self.bits() as u16,
+            sample_format,
+        }
+    }
+}
+
+/// Same as [`save_audio`], but writes the given [`BitDepth`] and reports
+/// `(written_frames, total_frames)` periodically.
+///
+/// Integer formats are scaled from [-1.0, 1.0] to their full range and clipped outside
+/// it. Encoding stops early with the callback's error if it returns one. The partially
+/// written file is left on disk for the caller to deal with.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info", skip(data, progress), fields(path = %path, frames = data.len() / 2)))]
+pub fn save_audio_with_progress(
+    path: &String,
+    data: Vec<f32>,
+    sample_rate: u32,
+    bit_depth: BitDepth,
+    mut progress: impl FnMut(u64, u64) -> Result<()>,
+) -> Result<()> {
+    let mut writer = hound::WavWriter::create(path, bit_depth.spec(sample_rate))
+        .map_err(|e| OxidizerError::Encoding(e.to_string()))?;
+
     // Scale and write each sample
-    let mut writer =
-        hound::WavWriter::create(path, spec).map_err(|e| OxidizerError::Encoding(e.to_string()))?;
-    for sample in data {
-        let scaled_sample = (sample * i16::MAX as f32) as i16;
-        writer
-            .write_sample(scaled_sample)
-            .map_err(|e| OxidizerError::Decoding(e.to_string()))?;
+    let total_frames = data.len() as u64 / 2;
+    for (i, sample) in data.into_iter().enumerate() {
+        match bit_depth {
+            BitDepth::Int16 => writer.write_sample(bit_depth.to_int(sample) as i16),
+            BitDepth::Int24 => writer.write_sample(bit_depth.to_int(sample)),
+            BitDepth::Float32 => writer.write_sample(sample),
+        }
+        .map_err(|e| OxidizerError::Encoding(e.to_string()))?;
+
+        if i % PROGRESS_INTERVAL == 0 {
+            progress(i as u64 / 2, total_frames)?;
+        }
     }
+    progress(total_frames, total_frames)?;
 
     // Finalize the WAV file to update the header with correct data length
     writer
@@ -107,3 +657,43 @@ pub fn save_audio(path: &String, data: Vec<f32>, sample_rate: u32) -> Result<()>
         .map_err(|e| OxidizerError::Encoding(e.to_string()))?;
     Ok(())
 }
+
+/// Writes `data` (interleaved stereo) in the given format, reporting
+/// `(written_frames, total_frames)` like [`save_audio_with_progress`].
+///
+/// Fails before creating the file if the format can't store `bit_depth`.
+pub fn save_audio_as(
+    path: &Path,
+    data: Vec<f32>,
+    sample_rate: u32,
+    format: AudioFormat,
+    bit_depth: BitDepth,
+    mut progress: impl FnMut(u64, u64) -> Result<()>,
+) -> Result<()> {
+    format.check(bit_depth)?;
+
+    match format {
+        AudioFormat::Wav => save_audio_with_progress(
+            &path.to_string_lossy().into_owned(),
+            data,
+            sample_rate,
+            bit_depth,
+            progress,
+        ),
+        AudioFormat::Flac => {
+            let total_frames = data.len() as u64 / 2;
+            let samples: Vec<i32> = data.into_iter().map(|s| bit_depth.to_int(s)).collect();
+
+            let mut writer = std::io::BufWriter::new(File::create(path)?);
+            flac::write(
+                &mut writer,
+                &samples,
+                sample_rate,
+                bit_depth.bits(),
+                |done| progress(done, total_frames),
+            )?;
+            std::io::Write::flush(&mut writer)?;
+            Ok(())
+        }
+    }
+}
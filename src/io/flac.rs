@@ -0,0 +1,559 @@
+//! A small FLAC encoder for interleaved stereo integer samples.
+//!
+//! Every frame holds a fixed-size block. Each channel is coded with the best of FLAC's
+//! fixed polynomial predictors (orders 0 to 4) and a partitioned Rice code for the
+//! residual, and the stereo pair is stored as whichever of left/right, left/side,
+//! side/right or mid/side needs the fewest bits. That reaches most of the compression of
+//! the reference encoder's fast settings without LPC analysis.
+//!
+//! STREAMINFO is filled in after the frames, with the smallest and largest frame size
+//! and the MD5 signature of the samples, so `flac -t` can verify the file.
+
+use crate::error::Result;
+use std::io::{Seek, SeekFrom, Write};
+
+// Frames per FLAC frame; the reference encoder's default.
+const BLOCK_SIZE: usize = 4096;
+
+const MAX_FIXED_ORDER: usize = 4;
+const MAX_PARTITION_ORDER: u32 = 8;
+// Largest Rice parameter of the RICE2 coding method (31 is reserved as the escape code).
+const MAX_RICE_PARAMETER: u32 = 30;
+
+/// Writes a complete FLAC stream of interleaved stereo `samples`, each holding a signed
+/// value of `bits_per_sample` bits (16 or 24).
+///
+/// `progress` is called with the number of frames written after each block. The
+/// STREAMINFO block is filled in once the frames are written, so `writer` has to seek.
+pub(super) fn write<W: Write + Seek>(
+    writer: &mut W,
+    samples: &[i32],
+    sample_rate: u32,
+    bits_per_sample: u32,
+    mut progress: impl FnMut(u64) -> Result<()>,
+) -> Result<()> {
+    let frames = samples.len() / 2;
+    let start = writer.stream_position()?;
+    let mut info = StreamInfo {
+        sample_rate,
+        bits_per_sample,
+        total_frames: frames as u64,
+        min_frame_size: u32::MAX,
+        max_frame_size: 0,
+        md5: [0; 16],
+    };
+    writer.write_all(b"fLaC")?;
+    writer.write_all(&info.to_bytes())?;
+
+    let mut md5 = Md5::default();
+    let bytes_per_sample = (bits_per_sample / 8) as usize;
+    let mut left = Vec::with_capacity(BLOCK_SIZE);
+    let mut right = Vec::with_capacity(BLOCK_SIZE);
+    for (number, block) in samples.chunks(BLOCK_SIZE * 2).enumerate() {
+        left.clear();
+        right.clear();
+        for frame in block.chunks_exact(2) {
+            left.push(frame[0] as i64);
+            right.push(frame[1] as i64);
+        }
+        // The signature covers the samples as little-endian bytes, interleaved
+        for sample in block {
+            md5.update(&sample.to_le_bytes()[..bytes_per_sample]);
+        }
+
+        let frame = encode_frame(number as u64, &left, &right, bits_per_sample);
+        info.min_frame_size = info.min_frame_size.min(frame.len() as u32);
+        info.max_frame_size = info.max_frame_size.max(frame.len() as u32);
+        writer.write_all(&frame)?;
+        progress(((number + 1) * BLOCK_SIZE).min(frames) as u64)?;
+    }
+
+    info.md5 = md5.finish();
+    let end = writer.stream_position()?;
+    writer.seek(SeekFrom::Start(start + 4))?;
+    writer.write_all(&info.to_bytes())?;
+    writer.seek(SeekFrom::Start(end))?;
+    Ok(())
+}
+
+// What the mandatory STREAMINFO metadata block records about the stream.
+struct StreamInfo {
+    sample_rate: u32,
+    bits_per_sample: u32,
+    total_frames: u64,
+    // Sizes of the smallest and largest encoded frame, in bytes; 0 when unknown
+    min_frame_size: u32,
+    max_frame_size: u32,
+    md5: [u8; 16],
+}
+
+impl StreamInfo {
+    // The block, flagged as the last metadata block.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bits = BitWriter::default();
+        bits.write(1, 1); // Last metadata block
+        bits.write(0, 7); // STREAMINFO
+        bits.write(34, 24); // Block length
+        bits.write(BLOCK_SIZE as u64, 16); // Minimum block size
+        bits.write(BLOCK_SIZE as u64, 16); // Maximum block size
+        let known = |size: u32| if size == u32::MAX { 0 } else { size as u64 };
+        bits.write(known(self.min_frame_size), 24);
+        bits.write(known(self.max_frame_size), 24);
+        bits.write(self.sample_rate as u64, 20);
+        bits.write(1, 3); // Channels - 1
+        bits.write(self.bits_per_sample as u64 - 1, 5);
+        bits.write(self.total_frames, 36);
+        for byte in self.md5 {
+            bits.write(byte as u64, 8);
+        }
+        bits.into_bytes()
+    }
+}
+
+fn encode_frame(number: u64, left: &[i64], right: &[i64], bits_per_sample: u32) -> Vec<u8> {
+    let side: Vec<i64> = left.iter().zip(right).map(|(l, r)| l - r).collect();
+    let mid: Vec<i64> = left.iter().zip(right).map(|(l, r)| (l + r) >> 1).collect();
+
+    // The side channel needs one extra bit
+    let left_sub = Subframe::analyze(left, bits_per_sample);
+    let right_sub = Subframe::analyze(right, bits_per_sample);
+    let side_sub = Subframe::analyze(&side, bits_per_sample + 1);
+    let mid_sub = Subframe::analyze(&mid, bits_per_sample);
+
+    // Channel assignment codes from the FLAC format
+    let (assignment, first, second) = [
+        (0b0001, &left_sub, &right_sub),
+        (0b1000, &left_sub, &side_sub),
+        (0b1001, &side_sub, &right_sub),
+        (0b1010, &mid_sub, &side_sub),
+    ]
+    .into_iter()
+    .min_by_key(|(_, a, b)| a.bits + b.bits)
+    .expect("there are four candidates");
+
+    let mut bits = BitWriter::default();
+    write_frame_header(&mut bits, number, left.len(), assignment, bits_per_sample);
+    first.write(&mut bits);
+    second.write(&mut bits);
+    bits.align();
+
+    let crc = crc16(bits.bytes());
+    bits.write(crc as u64, 16);
+    bits.into_bytes()
+}
+
+fn write_frame_header(
+    bits: &mut BitWriter,
+    number: u64,
+    block_size: usize,
+    assignment: u64,
+    bits_per_sample: u32,
+) {
+    bits.write(0b11_1111_1111_1110, 14); // Sync code
+    bits.write(0, 1); // Reserved
+    bits.write(0, 1); // Fixed block size
+    bits.write(0b0111, 4); // Block size stored as a 16-bit value after the frame number
+    bits.write(0b0000, 4); // Sample rate from STREAMINFO
+    bits.write(assignment, 4);
+    bits.write(if bits_per_sample == 24 { 0b110 } else { 0b100 }, 3);
+    bits.write(0, 1); // Reserved
+    write_utf8_number(bits, number);
+    bits.write(block_size as u64 - 1, 16);
+
+    let crc = crc8(bits.bytes());
+    bits.write(crc as u64, 8);
+}
+
+// Frame numbers are coded like UTF-8 code points, extended up to 36 bits.
+fn write_utf8_number(bits: &mut BitWriter, number: u64) {
+    if number < 0x80 {
+        bits.write(number, 8);
+        return;
+    }
+
+    let significant_bits = 64 - number.leading_zeros();
+    // Each continuation byte carries 6 bits; the first byte carries 7 - bytes
+    let bytes = (2..=7)
+        .find(|&n| significant_bits <= 5 * n + 1)
+        .expect("frame numbers fit in 36 bits");
+    let continuation_bits = 6 * (bytes - 1);
+
+    let prefix = (0xFF00u64 >> bytes) & 0xFF;
+    bits.write(prefix | (number >> continuation_bits), 8);
+    for i in (0..bytes - 1).rev() {
+        bits.write(0x80 | ((number >> (6 * i)) & 0x3F), 8);
+    }
+}
+
+// A channel coded with its cheapest fixed predictor.
+struct Subframe {
+    order: usize,
+    warm_up: Vec<i64>,
+    residual: Vec<i64>,
+    partition_order: u32,
+    parameters: Vec<u32>,
+    bits_per_sample: u32,
+    // Size of the coded subframe, used to choose the stereo decorrelation
+    bits: u64,
+}
+
+impl Subframe {
+    fn analyze(samples: &[i64], bits_per_sample: u32) -> Self {
+        let order = best_fixed_order(samples);
+        let residual = fixed_residual(samples, order);
+        let (partition_order, parameters, residual_bits) = best_partitioning(&residual, order);
+
+        Self {
+            order,
+            warm_up: samples[..order].to_vec(),
+            residual,
+            partition_order,
+            parameters,
+            bits_per_sample,
+            bits: 8 + (order as u64 * bits_per_sample as u64) + residual_bits,
+        }
+    }
+
+    fn write(&self, bits: &mut BitWriter) {
+        bits.write(0, 1); // Zero padding
+        bits.write(0b001000 | self.order as u64, 6); // Fixed predictor of this order
+        bits.write(0, 1); // No wasted bits
+
+        for &sample in &self.warm_up {
+            bits.write_signed(sample, self.bits_per_sample);
+        }
+
+        bits.write(0b01, 2); // RICE2: 5-bit parameters
+        bits.write(self.partition_order as u64, 4);
+
+        let partition_len = (self.residual.len() + self.order) >> self.partition_order;
+        let mut start = 0;
+        for (i, &parameter) in self.parameters.iter().enumerate() {
+            // The first partition's warm-up samples aren't part of the residual
+            let len = if i == 0 {
+                partition_len - self.order
+            } else {
+                partition_len
+            };
+            bits.write(parameter as u64, 5);
+            for &value in &self.residual[start..start + len] {
+                bits.write_rice(zigzag(value), parameter);
+            }
+            start += len;
+        }
+    }
+}
+
+// Picks the fixed predictor order with the smallest sum of absolute residuals. The
+// warm-up must leave at least one sample to predict.
+fn best_fixed_order(samples: &[i64]) -> usize {
+    (0..=MAX_FIXED_ORDER.min(samples.len().saturating_sub(1)))
+        .min_by_key(|&order| {
+            fixed_residual(samples, order)
+                .iter()
+                .map(|r| r.unsigned_abs())
+                .sum::<u64>()
+        })
+        .unwrap_or(0)
+}
+
+// Residual of the fixed polynomial predictor of `order`, for the samples after the warm-up.
+fn fixed_residual(samples: &[i64], order: usize) -> Vec<i64> {
+    if samples.len() <= order {
+        return Vec::new();
+    }
+
+    (order..samples.len())
+        .map(|i| {
+            let s = |k: usize| samples[i - k];
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+// Chooses the partition order and per-partition Rice parameters that minimize the coded
+// size, returning them with that size in bits (including the 6-bit method header).
+fn best_partitioning(residual: &[i64], order: usize) -> (u32, Vec<u32>, u64) {
+    let block_size = residual.len() + order;
+    let mapped: Vec<u64> = residual.iter().map(|&r| zigzag(r)).collect();
+
+    (0..=MAX_PARTITION_ORDER)
+        // Partitions must evenly split the block and the first one must outlast the warm-up
+        .take_while(|&p| block_size.is_multiple_of(1 << p) && (block_size >> p) > order)
+        .map(|p| {
+            let partition_len = block_size >> p;
+            let mut start = 0;
+            let mut total = 6;
+            let parameters = (0..1usize << p)
+                .map(|i| {
+                    let len = if i == 0 {
+                        partition_len - order
+                    } else {
+                        partition_len
+                    };
+                    let (parameter, bits) = best_rice_parameter(&mapped[start..start + len]);
+                    start += len;
+                    total += 5 + bits;
+                    parameter
+                })
+                .collect();
+            (p, parameters, total)
+        })
+        .min_by_key(|(_, _, bits)| *bits)
+        .unwrap_or((0, vec![0], 6 + 5))
+}
+
+// Picks the Rice parameter for a partition of zigzag-mapped values, starting from an
+// estimate based on their mean and checking its neighbours exactly.
+fn best_rice_parameter(values: &[u64]) -> (u32, u64) {
+    if values.is_empty() {
+        return (0, 0);
+    }
+
+    let sum: u64 = values.iter().sum();
+    let mean = sum / values.len() as u64;
+    let estimate = (64 - mean.leading_zeros()).min(MAX_RICE_PARAMETER);
+
+    (estimate.saturating_sub(1)..=(estimate + 1).min(MAX_RICE_PARAMETER))
+        .map(|k| {
+            let bits = values.iter().map(|v| (v >> k) + 1 + k as u64).sum();
+            (k, bits)
+        })
+        .min_by_key(|(_, bits)| *bits)
+        .expect("the candidate range is never empty")
+}
+
+// Maps signed residuals to unsigned ones: 0, -1, 1, -2, 2, ... => 0, 1, 2, 3, 4, ...
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |crc, &byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |crc, &byte| {
+        (0..8).fold(crc ^ ((byte as u16) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+// Big-endian bit packer.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    accumulator: u64,
+    pending: u32,
+}
+
+impl BitWriter {
+    // Writes the low `bits` of `value`, up to 32 at a time. Fewer than 8 bits are ever
+    // pending, so the accumulator can't overflow.
+    fn write(&mut self, value: u64, bits: u32) {
+        if bits > 32 {
+            self.write(value >> 32, bits - 32);
+            self.write(value, 32);
+            return;
+        }
+        let mask = (1u64 << bits) - 1;
+        self.accumulator = (self.accumulator << bits) | (value & mask);
+        self.pending += bits;
+        while self.pending >= 8 {
+            self.pending -= 8;
+            self.bytes.push((self.accumulator >> self.pending) as u8);
+        }
+        self.accumulator &= (1 << self.pending) - 1;
+    }
+
+    fn write_signed(&mut self, value: i64, bits: u32) {
+        self.write(value as u64 & ((1 << bits) - 1), bits);
+    }
+
+    fn write_rice(&mut self, value: u64, parameter: u32) {
+        // Unary-coded quotient, a stop bit, then the low bits
+        let mut quotient = value >> parameter;
+        while quotient >= 32 {
+            self.write(0, 32);
+            quotient -= 32;
+        }
+        self.write(1, quotient as u32 + 1);
+        self.write(value, parameter);
+    }
+
+    fn align(&mut self) {
+        if self.pending > 0 {
+            self.write(0, 8 - self.pending);
+        }
+    }
+
+    // Complete bytes written so far.
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+// Initial state of the MD5 hash.
+const MD5_INIT: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+// The sines of 1 to 64, scaled to 32 bits.
+const MD5_SINES: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+// Per-step left rotations of the four MD5 rounds.
+const MD5_SHIFTS: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+
+// MD5 (RFC 1321), which FLAC uses to sign the decoded samples. Only for the signature;
+// it isn't fit for anything secure.
+struct Md5 {
+    state: [u32; 4],
+    block: [u8; 64],
+    filled: usize,
+    length: u64,
+}
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Self {
+            state: MD5_INIT,
+            block: [0; 64],
+            filled: 0,
+            length: 0,
+        }
+    }
+}
+
+impl Md5 {
+    fn update(&mut self, mut bytes: &[u8]) {
+        self.length += bytes.len() as u64;
+        while !bytes.is_empty() {
+            let take = (64 - self.filled).min(bytes.len());
+            self.block[self.filled..self.filled + take].copy_from_slice(&bytes[..take]);
+            self.filled += take;
+            bytes = &bytes[take..];
+            if self.filled == 64 {
+                self.compress();
+                self.filled = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> [u8; 16] {
+        let bits = self.length.wrapping_mul(8);
+        self.update(&[0x80]);
+        while self.filled != 56 {
+            self.update(&[0]);
+        }
+        self.update(&bits.to_le_bytes());
+
+        let mut digest = [0; 16];
+        for (chunk, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        digest
+    }
+
+    fn compress(&mut self) {
+        let words: Vec<u32> = self
+            .block
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let [mut a, mut b, mut c, mut d] = self.state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let sum = a
+                .wrapping_add(f)
+                .wrapping_add(MD5_SINES[i])
+                .wrapping_add(words[g]);
+            let rotated = sum.rotate_left(MD5_SHIFTS[i / 16 * 4 + i % 4]);
+            (a, b, c, d) = (d, b.wrapping_add(rotated), b, c);
+        }
+        for (state, value) in self.state.iter_mut().zip([a, b, c, d]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn md5_hex(bytes: &[u8]) -> String {
+        let mut md5 = Md5::default();
+        md5.update(bytes);
+        md5.finish().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_md5_matches_the_rfc_vectors() {
+        assert_eq!(md5_hex(b""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(md5_hex(b"abc"), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(
+            md5_hex(
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890"
+            ),
+            "57edf4a22be3c955ac49da2e2107b67a"
+        );
+    }
+
+    #[test]
+    fn test_stream_info_records_the_frames() {
+        let samples: Vec<i32> = (0..2 * (BLOCK_SIZE as i32 + 10))
+            .map(|i| i * 37 % 1000)
+            .collect();
+        let mut stream = std::io::Cursor::new(Vec::new());
+        write(&mut stream, &samples, 44_100, 16, |_| Ok(())).unwrap();
+        let stream = stream.into_inner();
+
+        let info = &stream[8..42];
+        let size = |at: usize| u32::from_be_bytes([0, info[at], info[at + 1], info[at + 2]]);
+        let (min, max) = (size(4), size(7));
+        // Two frames: a full block and a short one
+        assert_eq!((min + max) as usize, stream.len() - 42);
+        assert!(min < max);
+
+        let mut md5 = Md5::default();
+        for sample in &samples {
+            md5.update(&sample.to_le_bytes()[..2]);
+        }
+        assert_eq!(info[18..34], md5.finish());
+    }
+}
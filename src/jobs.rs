@@ -0,0 +1,222 @@
+//! Background processing jobs.
+//!
+//! [`spawn`] runs a complete decode → oxidize → encode pipeline on its own thread and
+//! returns a [`JobHandle`]. The handle can be polled for progress, blocked on with
+//! [`JobHandle::wait`], or `.await`ed from any async runtime (tokio, async-std, ...),
+//! which makes it easy to embed in a web service that accepts uploads.
+//!
+//! ```no_run
+//! use oxidizer::OxidizerConfig;
+//! use oxidizer::jobs::{self, JobConfig};
+//!
+//! let handle = jobs::spawn(JobConfig {
+//!     input: "upload.mp3".into(),
+//!     output: "oxidized.wav".into(),
+//!     config: OxidizerConfig::default(),
+//! });
+//!
+//! println!("{:.0}% done", handle.progress() * 100.0);
+//! let output = handle.wait()?;
+//! # Ok::<(), oxidizer::OxidizerError>(())
+//! ```
+
+use crate::config::OxidizerConfig;
+use crate::error::{OxidizerError, Result};
+use crate::pipeline::{CancellationToken, Pipeline, Progress, Stage};
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::thread::JoinHandle;
+
+/// Describes a single file to oxidize.
+#[derive(Debug, Clone)]
+pub struct JobConfig {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub config: OxidizerConfig,
+}
+
+/// The pipeline stage a job is currently in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Queued,
+    Decoding,
+    Processing,
+    Encoding,
+    Finished,
+    Failed,
+}
+
+impl JobStatus {
+    // Portion of the overall progress covered by each stage, as (start, end).
+    fn span(&self) -> (f32, f32) {
+        match self {
+            JobStatus::Queued => (0.0, 0.0),
+            JobStatus::Decoding => (0.0, 0.2),
+            JobStatus::Processing => (0.2, 0.9),
+            JobStatus::Encoding => (0.9, 1.0),
+            JobStatus::Finished | JobStatus::Failed => (1.0, 1.0),
+        }
+    }
+}
+
+impl From<Stage> for JobStatus {
+    fn from(stage: Stage) -> Self {
+        match stage {
+            Stage::Decoding => JobStatus::Decoding,
+            Stage::Processing => JobStatus::Processing,
+            Stage::Encoding => JobStatus::Encoding,
+        }
+    }
+}
+
+// State shared between the worker thread and the handle.
+struct JobState {
+    status: JobStatus,
+    progress: Option<Progress>,
+    result: Option<Result<PathBuf>>,
+    waker: Option<Waker>,
+}
+
+#[derive(Clone)]
+struct Shared(Arc<Mutex<JobState>>);
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, JobState> {
+        // A poisoned lock only means the worker panicked mid-update; the state is still readable
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn set_progress(&self, progress: Progress) {
+        let mut state = self.lock();
+        state.status = progress.stage.into();
+        state.progress = Some(progress);
+    }
+
+    fn finish(&self, result: Result<PathBuf>) {
+        let mut state = self.lock();
+        state.status = if result.is_ok() {
+            JobStatus::Finished
+        } else {
+            JobStatus::Failed
+        };
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Handle to a running job.
+///
+/// Resolves to the output path once the file has been written. Awaiting the handle
+/// and calling [`wait`](Self::wait) are interchangeable; the result can be taken once.
+pub struct JobHandle {
+    shared: Shared,
+    cancellation: CancellationToken,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl JobHandle {
+    /// Returns the stage the job is currently in.
+    pub fn status(&self) -> JobStatus {
+        self.shared.lock().status
+    }
+
+    /// Returns an estimate of the job's overall completion in the range [0.0, 1.0].
+    pub fn progress(&self) -> f32 {
+        let state = self.shared.lock();
+        let (start, end) = state.status.span();
+        let fraction = state.progress.and_then(|p| p.fraction()).unwrap_or(0.0);
+        start + (end - start) * fraction
+    }
+
+    /// Returns the most recent frame-level progress report of the current stage.
+    pub fn stage_progress(&self) -> Option<Progress> {
+        self.shared.lock().progress
+    }
+
+    /// Returns `true` once the job has either finished or failed.
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status(), JobStatus::Finished | JobStatus::Failed)
+    }
+
+    /// Asks the job to stop. It resolves to [`OxidizerError::Cancelled`] shortly after,
+    /// with any partially written output removed.
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+
+    /// Blocks the current thread until the job completes.
+    pub fn wait(mut self) -> Result<PathBuf> {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        self.take_result()
+    }
+
+    fn take_result(&self) -> Result<PathBuf> {
+        self.shared.lock().result.take().unwrap_or_else(|| {
+            Err(OxidizerError::Job(
+                "Job result was already taken".to_string(),
+            ))
+        })
+    }
+}
+
+impl Future for JobHandle {
+    type Output = Result<PathBuf>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.lock();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Starts processing a file on a background thread.
+pub fn spawn(job: JobConfig) -> JobHandle {
+    let shared = Shared(Arc::new(Mutex::new(JobState {
+        status: JobStatus::Queued,
+        progress: None,
+        result: None,
+        waker: None,
+    })));
+
+    let cancellation = CancellationToken::new();
+
+    let worker = shared.clone();
+    let token = cancellation.clone();
+    let thread = std::thread::spawn(move || {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            render(&job, &worker, token)
+        }))
+        .unwrap_or_else(|_| Err(OxidizerError::Job("Processing thread panicked".to_string())));
+        worker.finish(result);
+    });
+
+    JobHandle {
+        shared,
+        cancellation,
+        thread: Some(thread),
+    }
+}
+
+// Runs the full pipeline for a single job, forwarding progress to the handle.
+fn render(job: &JobConfig, shared: &Shared, token: CancellationToken) -> Result<PathBuf> {
+    let mut pipeline = Pipeline::new(job.config.clone());
+    let progress = shared.clone();
+    pipeline
+        .set_progress_callback(move |p| progress.set_progress(p))
+        .set_cancellation_token(token);
+    pipeline.run(&job.input, &job.output)?;
+
+    Ok(job.output.clone())
+}
@@ -8,9 +8,12 @@
 //!
 //! 1. **Consume**: Load raw PCM samples into the engine (zero-copy).
 //! 2. **Process**: Apply low-pass filtration based on [`OxidationLevel`].
-//! 3. **Texture**: Overlay generated noise and apply `tanh` saturation.
+//! 3. **Texture**: Overlay generated noise and apply `tanh` saturation, optionally
+//!    layering recorded ambience on top ([`overlay`]).
 //! 4. **Normalize**: Ensure the output stays within safe digital bounds (-0.5 dBFS).
-//! 5. **Collect**: Extract the processed buffer for playback or storage.
+//! 5. **Collect**: Extract the processed buffer for playback or storage. An optional
+//!    output guard ([`Oxidizer::set_output_guard`]) soft-limits it on the way out, in
+//!    case stages ran after normalization.
 //!
 //! ## Quick Start
 //!
@@ -28,12 +31,97 @@
 //!     .collect_samples();
 //! ```
 //!
+//! ## Rendering without allocations
+//!
+//! [`render_into`] runs the core chain (filter, noise, saturation, mix and peak
+//! normalization) from an input slice into an output slice, in a preallocated
+//! [`Scratch`](processor::Scratch). Once the scratch is large enough it never touches
+//! the allocator, so profiles show the engine alone. [`render_planar`] does the same in
+//! place on planar channels, the way plugin hosts hand out audio;
+//! [`Pipeline::process_planar`] runs the full chain on them. To drive the engine stage
+//! by stage on memory you don't own (a host's block, a memory-mapped file),
+//! [`Oxidizer::process_slice`] offers the same fluent API on a borrowed slice.
+//!
+//! ```rust
+//! use oxidizer::OxidizerConfig;
+//! use oxidizer::processor::Scratch;
+//!
+//! let input = vec![0.0; 88200];
+//! let mut output = vec![0.0; input.len()];
+//! let mut scratch = Scratch::new(input.len() / 2);
+//! oxidizer::render_into(&input, &mut output, &mut scratch, &OxidizerConfig::default())?;
+//! # Ok::<(), oxidizer::OxidizerError>(())
+//! ```
+//!
+//! ## Comparing with the original
+//!
+//! Filtering makes a track quieter and saturation makes it denser, and the louder of two
+//! versions tends to sound better. [`Pipeline::process_matched`] returns the input and
+//! its render at the same integrated loudness ([`MatchedPair`]), and
+//! [`Pipeline::run_matched`] writes them as a pair of files, for blind A/B listening.
+//! [`analysis::spectral_distance`] puts a number on the difference, e.g. to catch a
+//! preset drifting between versions.
+//!
+//! ## Deterministic rendering
+//!
+//! With the `deterministic` feature, all math on the render path goes through a
+//! portable software implementation instead of the platform's, and unseeded renders
+//! use seed 0. The same input, settings and seed then give bit-identical output on
+//! every OS and architecture, which is what golden-file tests and render farms need.
+//!
+//! ## Feature Flags
+//!
+//! - `cli` (default): the command-line tool. Also implements `clap::ValueEnum` for
+//!   [`OxidationLevel`], for frontends building their own CLI.
+//! - `jack`: real-time JACK client mode (`jack_client` module).
+//! - `tracing`: spans and events for decoding, every processing stage and encoding,
+//!   carrying buffer sizes and peak levels. Enable span close events in your
+//!   subscriber (e.g. `FmtSpan::CLOSE`) to get per-stage timings.
+//! - `serde`: `Serialize`/`Deserialize` for [`OxidizerConfig`], [`OxidationLevel`]
+//!   and [`NoiseConfig`], for persisting and transmitting settings, and `.oxproj`
+//!   project files describing a whole session (`project` module).
+//! - `testing`: known signals (sines, sweeps, impulses, pink bursts) and golden-output
+//!   comparisons for regression tests of the DSP (`testing` module).
+//!
 
+pub mod analysis;
+pub mod artifacts;
+pub mod automation;
+pub mod batch;
+pub mod codec;
+pub mod config;
+pub mod dynamics;
+pub mod eq;
 pub mod error;
+pub mod fft;
+pub mod hum;
 pub mod io;
+#[cfg(feature = "jack")]
+pub mod jack_client;
+pub mod jobs;
+mod math;
+pub mod modulation;
+pub mod overlay;
+pub mod pipeline;
+pub mod presets;
 pub mod processor;
+#[cfg(feature = "serde")]
+pub mod project;
+pub mod resample;
+pub mod speed;
+pub mod stereo;
+#[cfg(feature = "testing")]
+pub mod testing;
 
+pub use config::{
+    Branch, BranchInput, ChannelConfig, NoiseConfig, NoiseRouting, Normalization, OxidizerConfig,
+    Segment,
+};
 pub use error::{OxidizerError, Result};
+pub use pipeline::{
+    CancellationToken, MatchedPair, Pipeline, Progress, RunReport, Stage, StageLevels, render_into,
+    render_planar, run,
+};
 pub use processor::{OxidationLevel, Oxidizer};
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
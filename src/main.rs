@@ -1,79 +1,86 @@
-use clap::Parser;
-use oxidizer::OxidationLevel;
-use oxidizer::OxidizerError;
+mod commands;
+
+use clap::{Parser, Subcommand};
+use commands::analyze::AnalyzeArgs;
+use commands::info::InfoArgs;
+use commands::list::ListTopic;
+use commands::log::Verbosity;
+use commands::noise::NoiseArgs;
+use commands::oxidize::OxidizeArgs;
+use commands::presets::PresetsCommand;
+use commands::preview::PreviewArgs;
+use commands::project::ProjectCommand;
 use oxidizer::error::Result;
-use oxidizer::io;
-use oxidizer::processor::Oxidizer;
-use oxidizer::processor::noise;
-use oxidizer::processor::noise::NoiseGenerator;
-use std::f32;
 
 /// Command-line arguments for the Oxidizer application.
 #[derive(Parser, Debug)]
 #[command(
     author,
     version,
-    about = "An audio transformer that makes everything sound like a Brownian noise"
+    about = "An audio transformer that makes everything sound like a Brownian noise",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
-struct Args {
-    /// Path to the input file (e.g., music.mp3). Supports multiple formats via Symphonia.
-    #[arg(short, long)]
-    input: String,
-
-    /// Path where the processed .wav file will be saved.    #[arg(short, long, default_value = "output.wav")]
-    output: String,
-
-    /// The level of oxidation. Options: 'muffled', 'deep', 'clear'.
-    #[arg(short, long, default_value = "deep")]
-    level: String,
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    /// The characteristic of the background hiss. 'brown' (bass-heavy) or 'white' (full-spectrum).
-    #[arg(short, long, default_value = "brown")]
-    noise: String,
+    // Running without a subcommand behaves like `oxidize`, for backwards compatibility
+    #[command(flatten)]
+    oxidize: OxidizeArgs,
 
-    /// Scale of the noise and saturation effect. Typically 0.0 (subtle) to 1.0 (crushed).
-    #[arg(short = 't', long, default_value_t = 0.05)]
-    intensity: f32,
-
-    /// Sample rate for the output WAV file. Should match the input for pitch consistency. Lower rates may result in a slowed down audio (pitch-shift).
-    #[arg(short = 's', long, default_value_t = 44100)]
-    sample_rate: u32,
-
-    /// Number of filter iterations.
-    /// Each pass doubles the filter slope (e.g., from 6dB/oct to 12dB/oct).
-    #[arg(short, long, default_value_t = 1)]
-    passes: u32,
+    #[command(flatten)]
+    verbosity: Verbosity,
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
-
-    let input_path = std::path::Path::new(&args.input);
-    let input_samples: Vec<f32> = io::load_audio(input_path)?;
-
-    // Dispatch processing based on selected noise generator
-    let output_samples = match args.noise.as_str() {
-        "white" => run_process(input_samples, noise::WhiteNoise::default(), &args)?,
-        _ => run_process(input_samples, noise::BrownianNoise::default(), &args)?,
-    };
-
-    io::save_audio(&args.output, output_samples, args.sample_rate)?;
-
-    Ok(())
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Process an audio file (the default when no subcommand is given).
+    Oxidize(OxidizeArgs),
+    /// Print the length and levels of an audio file.
+    Analyze(AnalyzeArgs),
+    /// Print the container, codec, length and tags of an audio file without decoding it.
+    Info(InfoArgs),
+    /// Render a short excerpt to audition settings quickly.
+    Preview(PreviewArgs),
+    /// Render pure generator noise to a file, e.g. as source material.
+    Noise(NoiseArgs),
+    /// List the available noise generators, levels, presets or file formats.
+    List {
+        /// What to list. Lists everything when omitted.
+        #[arg(value_enum)]
+        topic: Option<ListTopic>,
+    },
+    /// Tweak settings interactively while auditioning a looping excerpt, then export.
+    #[cfg(feature = "tui")]
+    Tui(commands::tui::TuiArgs),
+    /// Browse the built-in presets.
+    Presets {
+        #[command(subcommand)]
+        command: PresetsCommand,
+    },
+    /// Work with project files, which describe a whole session.
+    Project {
+        #[command(subcommand)]
+        command: ProjectCommand,
+    },
 }
 
-// Orchestrates the oxidation pipeline using a generic noise generator.
-fn run_process<N: NoiseGenerator>(samples: Vec<f32>, noise: N, args: &Args) -> Result<Vec<f32>> {
-    let mut oxidizer = Oxidizer::new(noise);
-    let level = OxidationLevel::try_from_str(&args.level).map_err(OxidizerError::InvalidValue)?;
-
-    // Ownership-based pipeline (zero-copy)
-    let processed = oxidizer
-        .consume(samples)
-        .process_multiple(level, args.passes)
-        .apply_noise_texture(args.intensity)
-        .normalize()
-        .collect_samples();
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    cli.verbosity.init();
 
-    Ok(processed)
+    match &cli.command {
+        None => commands::oxidize::run(&cli.oxidize),
+        Some(Command::Oxidize(args)) => commands::oxidize::run(args),
+        Some(Command::Analyze(args)) => commands::analyze::run(args),
+        Some(Command::Info(args)) => commands::info::run(args),
+        Some(Command::Preview(args)) => commands::preview::run(args),
+        Some(Command::Noise(args)) => commands::noise::run(args),
+        Some(Command::List { topic }) => commands::list::run(*topic),
+        Some(Command::Presets { command }) => commands::presets::run(command),
+        Some(Command::Project { command }) => commands::project::run(command),
+        #[cfg(feature = "tui")]
+        Some(Command::Tui(args)) => commands::tui::run(args),
+    }
 }
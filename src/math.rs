@@ -0,0 +1,80 @@
+//! Transcendental functions used on the rendering path.
+//!
+//! The standard library hands these to the platform's math library, whose results can
+//! differ in the last bit between operating systems and architectures. With the
+//! `deterministic` feature they come from `libm`, a pure Rust implementation that
+//! returns the same bits everywhere.
+
+#[cfg(not(feature = "deterministic"))]
+mod imp {
+    pub fn tanh(x: f32) -> f32 {
+        x.tanh()
+    }
+
+    pub fn sin(x: f32) -> f32 {
+        x.sin()
+    }
+
+    pub fn cos(x: f32) -> f32 {
+        x.cos()
+    }
+
+    pub fn exp(x: f32) -> f32 {
+        x.exp()
+    }
+
+    pub fn exp2(x: f32) -> f32 {
+        x.exp2()
+    }
+
+    pub fn ln(x: f32) -> f32 {
+        x.ln()
+    }
+
+    pub fn log10(x: f32) -> f32 {
+        x.log10()
+    }
+
+    pub fn powf(x: f32, y: f32) -> f32 {
+        x.powf(y)
+    }
+
+    pub fn sin_f64(x: f64) -> f64 {
+        x.sin()
+    }
+
+    pub fn cos_f64(x: f64) -> f64 {
+        x.cos()
+    }
+
+    pub fn exp_f64(x: f64) -> f64 {
+        x.exp()
+    }
+
+    pub fn tan_f64(x: f64) -> f64 {
+        x.tan()
+    }
+
+    pub fn acos_f64(x: f64) -> f64 {
+        x.acos()
+    }
+
+    pub fn log10_f64(x: f64) -> f64 {
+        x.log10()
+    }
+
+    pub fn powf_f64(x: f64, y: f64) -> f64 {
+        x.powf(y)
+    }
+}
+
+#[cfg(feature = "deterministic")]
+mod imp {
+    pub use libm::{
+        acos as acos_f64, cos as cos_f64, cosf as cos, exp as exp_f64, exp2f as exp2, expf as exp,
+        log10 as log10_f64, log10f as log10, logf as ln, pow as powf_f64, powf, sin as sin_f64,
+        sinf as sin, tan as tan_f64, tanhf as tanh,
+    };
+}
+
+pub(crate) use imp::*;
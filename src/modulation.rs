@@ -0,0 +1,312 @@
+//! Periodic, random and signal-dependent modulation sources.
+//!
+//! Modulators are evaluated once per automation control block, like
+//! [`Automation`](crate::automation::Automation) curves, and offset a parameter around
+//! its base value.
+
+use crate::analysis;
+use crate::automation::AutomationTarget;
+use crate::math;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Waveform of an [`Lfo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum LfoShape {
+    #[default]
+    Sine, // Smooth, regular breathing.
+    Triangle,   // Constant-speed sweeps up and down.
+    RandomWalk, // Aimless wandering, like a failing power supply.
+}
+
+impl LfoShape {
+    /// Every LFO shape.
+    pub const ALL: [LfoShape; 3] = [LfoShape::Sine, LfoShape::Triangle, LfoShape::RandomWalk];
+
+    /// Returns the name used on the command line and in config files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LfoShape::Sine => "sine",
+            LfoShape::Triangle => "triangle",
+            LfoShape::RandomWalk => "random-walk",
+        }
+    }
+
+    pub fn try_from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|shape| shape.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown LFO shape: {} (use sine, triangle or random-walk)",
+                    s
+                )
+            })
+    }
+}
+
+/// A low-frequency oscillator that sweeps the filter cutoff up and down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lfo {
+    pub shape: LfoShape,
+    /// Cycles per second. For the random walk, how fast it wanders.
+    pub rate_hz: f32,
+    /// Largest excursion of the cutoff from its base value, in octaves.
+    pub depth: f32,
+}
+
+impl Default for Lfo {
+    fn default() -> Self {
+        Self {
+            shape: LfoShape::Sine,
+            rate_hz: 0.5,
+            depth: 1.0,
+        }
+    }
+}
+
+impl Lfo {
+    /// Starts an oscillator at phase zero. `seed` drives the random walk.
+    pub fn start(&self, seed: u64) -> LfoOscillator {
+        LfoOscillator {
+            lfo: *self,
+            phase: 0.0,
+            walk: 0.0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Moves `cutoff_hz` by `value * depth` octaves, for an oscillator output `value`.
+    pub fn modulate(&self, cutoff_hz: f32, value: f32) -> f32 {
+        cutoff_hz * math::exp2(self.depth * value)
+    }
+}
+
+/// The running state of an [`Lfo`].
+#[derive(Debug, Clone)]
+pub struct LfoOscillator {
+    lfo: Lfo,
+    // Position within the current cycle, from 0.0 to 1.0
+    phase: f32,
+    walk: f32,
+    rng: StdRng,
+}
+
+impl LfoOscillator {
+    /// Returns the current output, from -1.0 to 1.0, and moves `seconds` ahead.
+    pub fn advance(&mut self, seconds: f32) -> f32 {
+        let value = match self.lfo.shape {
+            LfoShape::Sine => math::sin(self.phase * std::f32::consts::TAU),
+            LfoShape::Triangle => 1.0 - 4.0 * (self.phase - 0.5).abs(),
+            LfoShape::RandomWalk => self.walk,
+        };
+
+        let cycles = self.lfo.rate_hz.max(0.0) * seconds;
+        self.phase = (self.phase + cycles).fract();
+        if self.lfo.shape == LfoShape::RandomWalk {
+            // Uniform steps scaled so the walk spreads about as far per cycle as a sweep
+            let step = self.rng.random_range(-1.0f32..1.0) * (3.0 * cycles).sqrt();
+            self.walk = reflect(self.walk + step);
+        }
+
+        value
+    }
+}
+
+// Folds a value back into [-1.0, 1.0], as if bouncing off the bounds.
+fn reflect(value: f32) -> f32 {
+    let folded = (value + 1.0).rem_euclid(4.0);
+    if folded > 2.0 {
+        3.0 - folded
+    } else {
+        folded - 1.0
+    }
+}
+
+// Largest factor an envelope response scales its parameter by, either way.
+const MAX_RESPONSE_GAIN: f32 = 16.0;
+
+// Level envelopes are measured down to, in dBFS.
+const ENVELOPE_FLOOR_DB: f32 = -120.0;
+
+/// Tracks the level of a signal with separate attack and release times.
+#[derive(Debug, Clone)]
+pub struct EnvelopeFollower {
+    attack: f32,
+    release: f32,
+    level: f32,
+}
+
+impl EnvelopeFollower {
+    pub fn new(attack_ms: f32, release_ms: f32, sample_rate: u32) -> Self {
+        Self {
+            attack: smoothing(attack_ms, sample_rate),
+            release: smoothing(release_ms, sample_rate),
+            level: 0.0,
+        }
+    }
+
+    /// Feeds one sample and returns the updated envelope (linear amplitude).
+    pub fn process(&mut self, sample: f32) -> f32 {
+        let input = sample.abs();
+        let coefficient = if input > self.level {
+            self.attack
+        } else {
+            self.release
+        };
+        self.level = input + coefficient * (self.level - input);
+        self.level
+    }
+
+    /// Feeds interleaved stereo frames, following the louder channel, and returns the
+    /// envelope after the last one.
+    pub fn process_frames(&mut self, samples: &[f32]) -> f32 {
+        for frame in samples.chunks(2) {
+            let peak = frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+            self.process(peak);
+        }
+        self.level
+    }
+
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Forgets the level followed so far, as if the signal had been silent.
+    pub fn reset(&mut self) {
+        self.level = 0.0;
+    }
+}
+
+// One-pole smoothing coefficient reaching ~63% of a step after `ms` milliseconds.
+pub(crate) fn smoothing(ms: f32, sample_rate: u32) -> f32 {
+    if ms <= 0.0 {
+        return 0.0;
+    }
+    math::exp(-1000.0 / (ms * sample_rate.max(1) as f32))
+}
+
+/// The signal an [`EnvelopeResponse`] listens to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum EnvelopeSource {
+    #[default]
+    Input, // The unprocessed input.
+    Sidechain, // A second file, e.g. a voiceover ducking the crackle under the music.
+}
+
+/// Makes a parameter react to the level of the input (or of a sidechain), as followed
+/// by an [`EnvelopeFollower`] on the unprocessed signal.
+///
+/// While the envelope is `threshold_db` the parameter is unchanged. For every dB the
+/// envelope lies below the threshold, the parameter is scaled up by `amount` dB (and
+/// down for every dB above it). A positive amount on the intensity brings the hiss and
+/// grit up in quiet passages, like tape hiss surfacing between songs; a negative one
+/// makes loud parts dirtier.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct EnvelopeResponse {
+    pub target: AutomationTarget,
+    pub amount: f32,
+    pub source: EnvelopeSource,
+    pub threshold_db: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+}
+
+impl EnvelopeResponse {
+    /// A response of the given target and amount to the input, with a threshold of
+    /// -30 dBFS, 10 ms attack and 300 ms release.
+    pub fn new(target: AutomationTarget, amount: f32) -> Self {
+        Self {
+            target,
+            amount,
+            source: EnvelopeSource::Input,
+            threshold_db: -30.0,
+            attack_ms: 10.0,
+            release_ms: 300.0,
+        }
+    }
+
+    /// The factor the parameter is scaled by for an envelope of `level` (linear).
+    pub fn gain(&self, level: f32) -> f32 {
+        if self.amount == 0.0 {
+            return 1.0;
+        }
+        // Silence would otherwise be infinitely far below the threshold
+        let below = self.threshold_db - analysis::to_dbfs(level).max(ENVELOPE_FLOOR_DB);
+        analysis::from_dbfs(self.amount * below).clamp(1.0 / MAX_RESPONSE_GAIN, MAX_RESPONSE_GAIN)
+    }
+}
+
+impl Default for EnvelopeResponse {
+    fn default() -> Self {
+        Self::new(AutomationTarget::Intensity, 0.0)
+    }
+}
+
+/// Slowly wanders a parameter between two bounds over the course of a render, so long
+/// files don't sound statically processed.
+///
+/// The value glides (with cosine easing) to a new random point between `min` and `max`
+/// every `period_secs`. It depends only on the time and a seed, so a seeded render
+/// drifts the same way every time. A drifting parameter ignores its fixed value and
+/// automation curve; the LFO and envelope responses still apply on top.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Drift {
+    pub target: AutomationTarget,
+    pub min: f32,
+    pub max: f32,
+    /// Seconds between two random points. [default: 8]
+    #[cfg_attr(feature = "serde", serde(default = "Drift::default_period"))]
+    pub period_secs: f32,
+}
+
+impl Drift {
+    pub fn new(target: AutomationTarget, min: f32, max: f32) -> Self {
+        Self {
+            target,
+            min,
+            max,
+            period_secs: Self::default_period(),
+        }
+    }
+
+    fn default_period() -> f32 {
+        8.0
+    }
+
+    /// Returns the value at `seconds` into the render for the given seed.
+    pub fn value_at(&self, seconds: f32, seed: u64) -> f32 {
+        let position = (seconds / self.period_secs.max(f32::EPSILON)).max(0.0);
+        let segment = position.floor();
+        let from = self.point(segment as u64, seed);
+        let to = self.point(segment as u64 + 1, seed);
+
+        let weight = (1.0 - math::cos((position - segment) * std::f32::consts::PI)) / 2.0;
+        from + (to - from) * weight
+    }
+
+    // The random point at the start of `segment`.
+    fn point(&self, segment: u64, seed: u64) -> f32 {
+        // Golden-ratio increments spread neighbouring segments across the seed space
+        let mut rng = StdRng::seed_from_u64(seed ^ segment.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let (low, high) = (self.min.min(self.max), self.min.max(self.max));
+        low + (high - low) * rng.random::<f32>()
+    }
+}
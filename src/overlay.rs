@@ -0,0 +1,125 @@
+//! Recorded ambience layered over the output.
+//!
+//! An [`Overlay`] mixes another recording (rain, room tone, the run-out groove of a real
+//! record) into the render. The bed is resampled to the rate of the render and either
+//! looped or cut to fit its length.
+
+use crate::analysis;
+use crate::error::Result;
+use crate::io;
+use crate::resample::{Linear, Resampler};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Where the audio of an [`Overlay`] comes from.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(untagged)
+)]
+pub enum OverlaySource {
+    /// An audio file, decoded when the render starts.
+    File(PathBuf),
+    /// Already decoded interleaved stereo samples at `sample_rate`.
+    Buffer { samples: Vec<f32>, sample_rate: u32 },
+}
+
+// Buffers are summarized, so configs stay readable in logs
+impl fmt::Debug for OverlaySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverlaySource::File(path) => f.debug_tuple("File").field(path).finish(),
+            OverlaySource::Buffer {
+                samples,
+                sample_rate,
+            } => f
+                .debug_struct("Buffer")
+                .field("samples", &samples.len())
+                .field("sample_rate", sample_rate)
+                .finish(),
+        }
+    }
+}
+
+impl From<PathBuf> for OverlaySource {
+    fn from(path: PathBuf) -> Self {
+        OverlaySource::File(path)
+    }
+}
+
+impl From<&Path> for OverlaySource {
+    fn from(path: &Path) -> Self {
+        OverlaySource::File(path.to_path_buf())
+    }
+}
+
+impl From<(Vec<f32>, u32)> for OverlaySource {
+    fn from((samples, sample_rate): (Vec<f32>, u32)) -> Self {
+        OverlaySource::Buffer {
+            samples,
+            sample_rate,
+        }
+    }
+}
+
+/// A recording mixed into the output, after the noise stage and before normalization.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Overlay {
+    pub source: OverlaySource,
+    /// Level of the bed relative to its original level, in dB.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub gain_db: f32,
+    /// Repeats a bed shorter than the render; otherwise it simply stops.
+    #[cfg_attr(feature = "serde", serde(default = "default_loop"))]
+    pub loop_if_shorter: bool,
+}
+
+#[cfg(feature = "serde")]
+fn default_loop() -> bool {
+    true
+}
+
+impl Overlay {
+    pub fn new(source: impl Into<OverlaySource>, gain_db: f32, loop_if_shorter: bool) -> Self {
+        Self {
+            source: source.into(),
+            gain_db,
+            loop_if_shorter,
+        }
+    }
+
+    /// Linear gain the bed is mixed in at.
+    pub fn gain(&self) -> f32 {
+        analysis::from_dbfs(self.gain_db)
+    }
+
+    /// Returns the bed as interleaved stereo at `sample_rate`, decoding it if needed.
+    pub fn load(&self, sample_rate: u32) -> Result<Vec<f32>> {
+        self.load_with(sample_rate, &Linear)
+    }
+
+    /// Like [`Overlay::load`], converting the rate with `resampler`.
+    pub fn load_with(&self, sample_rate: u32, resampler: &dyn Resampler) -> Result<Vec<f32>> {
+        match &self.source {
+            OverlaySource::File(path) => {
+                let rate = io::probe(path)?.sample_rate.unwrap_or(sample_rate);
+                Ok(resampler.resample(&io::load_audio(path)?, rate, sample_rate))
+            }
+            OverlaySource::Buffer {
+                samples,
+                sample_rate: rate,
+            } => Ok(resampler.resample(samples, *rate, sample_rate)),
+        }
+    }
+}
+
+/// Converts interleaved stereo samples from one rate to another by linear
+/// interpolation (see [`Linear`]).
+///
+/// Good enough for ambience beds, which have little content near the top of the band;
+/// it doesn't filter out aliasing.
+pub fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    Linear.resample(samples, from, to)
+}
@@ -0,0 +1,1685 @@
+//! The complete file pipeline: decode → oxidize → normalize → encode.
+//!
+//! For one-off conversions [`run`] is all you need. [`Pipeline`] additionally
+//! accepts a progress callback and a cancellation token.
+
+use crate::analysis::{self, Levels};
+use crate::artifacts::Artifacts;
+use crate::automation::{AutomationTarget, CONTROL_BLOCK_FRAMES};
+use crate::config::{
+    BranchInput, NoiseConfig, NoiseRouting, Normalization, OxidizerConfig, Segment,
+};
+use crate::dynamics::GateMode;
+use crate::eq::Riaa;
+use crate::error::{OxidizerError, Result};
+use crate::io::{self, TimeRange};
+use crate::math;
+use crate::modulation::{EnvelopeFollower, EnvelopeResponse, EnvelopeSource, LfoOscillator};
+use crate::overlay::{Overlay, OverlaySource};
+use crate::processor::levels::{alpha_for_cutoff, cutoff_for_alpha};
+use crate::processor::noise::{BrownianNoise, CrackleNoise, NoiseGenerator, WhiteNoise};
+use crate::processor::{Oxidizer, Scratch};
+use std::borrow::Cow;
+use std::ops::{Bound, Range, RangeBounds};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Number of frames processed between two progress reports.
+const BLOCK_FRAMES: usize = 1 << 16;
+
+// Length of the crossfades into and out of a segment, in seconds.
+const SEGMENT_FADE_SECS: f64 = 0.05;
+
+/// The pipeline stage a progress report refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Decoding,
+    Processing,
+    Encoding,
+}
+
+/// A progress report emitted while a file is being processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub stage: Stage,
+    /// Frames completed so far in the current stage.
+    /// While processing, this counts frames across all filter passes and the noise stage.
+    pub processed_frames: u64,
+    /// Total frames the stage will handle, or 0 if it's unknown (e.g. an unsized stream).
+    pub total_frames: u64,
+}
+
+impl Progress {
+    /// Returns the completed fraction of the current stage, or `None` if the total is unknown.
+    pub fn fraction(&self) -> Option<f32> {
+        (self.total_frames > 0)
+            .then(|| (self.processed_frames as f32 / self.total_frames as f32).min(1.0))
+    }
+}
+
+/// Levels of the main chain's buffer right after one of its stages, recorded with
+/// [`Pipeline::set_metering`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StageLevels {
+    /// The stage the buffer just left, e.g. `"filter"` or `"compressor"`.
+    pub stage: String,
+    /// Above 0 dBFS, the stage pushed the signal past full scale; only the saturation
+    /// and the limiter bring it back.
+    pub peak_dbfs: f32,
+    pub rms_dbfs: f32,
+}
+
+impl StageLevels {
+    fn measure(stage: &str, samples: &[f32]) -> Self {
+        Self {
+            stage: stage.to_string(),
+            peak_dbfs: analysis::to_dbfs(analysis::peak(samples)),
+            rms_dbfs: analysis::to_dbfs(analysis::rms(samples)),
+        }
+    }
+}
+
+/// Summary of a finished run, returned by [`Pipeline::run_with_report`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunReport {
+    pub input: PathBuf,
+    /// The inputs appended after `input` in a joined run.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub joined: Vec<PathBuf>,
+    pub output: PathBuf,
+    pub input_sample_rate: u32,
+    pub output_sample_rate: u32,
+    /// Length of the processed audio at the input's sample rate.
+    pub input_duration_secs: f64,
+    /// Length of the written file, which differs when the output sample rate does.
+    pub output_duration_secs: f64,
+    pub config: OxidizerConfig,
+    /// Levels of the decoded input (or the selected range of it).
+    pub before: Levels,
+    /// Levels of the processed audio, as written.
+    pub after: Levels,
+    /// Wall-clock time the whole run took.
+    pub elapsed_secs: f64,
+    /// Levels between the stages of the main chain, if metering was on.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub stages: Vec<StageLevels>,
+}
+
+/// A record of how an output file was made, written next to it so the render can be
+/// audited and reproduced months later.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Sidecar {
+    /// Version of oxidizer that rendered the file.
+    pub version: String,
+    pub output: PathBuf,
+    /// The input, followed by the inputs joined after it.
+    pub inputs: Vec<PathBuf>,
+    /// [`io::file_hash`] of each input, in the same order.
+    pub input_hashes: Vec<String>,
+    /// Seed the noise and random modulation were drawn with, if the render had one.
+    /// Without it the render can't be repeated exactly.
+    pub seed: Option<u64>,
+    /// The fully resolved settings, including those taken from a preset or config file.
+    pub config: OxidizerConfig,
+}
+
+#[cfg(feature = "serde")]
+impl Sidecar {
+    /// Builds the record of a finished run, hashing its inputs.
+    pub fn new(report: &RunReport) -> Result<Self> {
+        let inputs: Vec<PathBuf> = std::iter::once(&report.input)
+            .chain(&report.joined)
+            .cloned()
+            .collect();
+        Ok(Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            output: report.output.clone(),
+            input_hashes: inputs
+                .iter()
+                .map(|input| io::file_hash(input))
+                .collect::<Result<_>>()?,
+            inputs,
+            seed: report.config.seed,
+            config: report.config.clone(),
+        })
+    }
+
+    /// Where the sidecar of `output` goes: next to it, with `extension` (`json` or
+    /// `toml`) appended to its name, e.g. `song.wav.json`.
+    pub fn path_for(output: &Path, extension: &str) -> PathBuf {
+        let mut name = output.as_os_str().to_owned();
+        name.push(".");
+        name.push(extension);
+        PathBuf::from(name)
+    }
+
+    /// Writes the record to a `.json` or `.toml` file, chosen by extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        crate::config::write_file(path.as_ref(), self)
+    }
+
+    /// Reads a record written by [`save`](Self::save).
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        crate::config::read_file(path.as_ref())
+    }
+}
+
+type ProgressCallback = Box<dyn Fn(Progress) + Send + Sync>;
+
+/// A dry signal and its render at the same loudness, returned by
+/// [`Pipeline::process_matched`], so an A/B comparison judges the sound of the chain
+/// rather than its level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedPair {
+    pub dry: Vec<f32>,
+    pub wet: Vec<f32>,
+    /// Gain applied to the dry signal, in dB: below zero only when the matched pair
+    /// would otherwise clip.
+    pub dry_gain_db: f32,
+    /// Gain applied to the render, in dB.
+    pub wet_gain_db: f32,
+}
+
+impl MatchedPair {
+    /// Brings `wet` to the integrated loudness of `dry` (or to its RMS level, when
+    /// either is too short to measure), then lowers both by the same amount if that
+    /// would push a peak above full scale.
+    pub fn new(mut dry: Vec<f32>, mut wet: Vec<f32>, sample_rate: u32) -> Self {
+        let difference = match (
+            analysis::loudness(&dry, sample_rate),
+            analysis::loudness(&wet, sample_rate),
+        ) {
+            (dry, wet) if dry.is_finite() && wet.is_finite() => dry - wet,
+            _ => analysis::to_dbfs(analysis::rms(&dry)) - analysis::to_dbfs(analysis::rms(&wet)),
+        };
+        // Silence on either side leaves nothing to match
+        let difference = if difference.is_finite() {
+            difference
+        } else {
+            0.0
+        };
+
+        let peak = analysis::peak(&dry).max(analysis::peak(&wet) * analysis::from_dbfs(difference));
+        let trim = analysis::to_dbfs(peak).max(0.0);
+        let (dry_gain_db, wet_gain_db) = (-trim, difference - trim);
+
+        for (samples, gain_db) in [(&mut dry, dry_gain_db), (&mut wet, wet_gain_db)] {
+            let gain = analysis::from_dbfs(gain_db);
+            samples.iter_mut().for_each(|s| *s *= gain);
+        }
+        Self {
+            dry,
+            wet,
+            dry_gain_db,
+            wet_gain_db,
+        }
+    }
+}
+
+/// A cheaply cloneable flag used to abort a running pipeline from another thread.
+///
+/// The pipeline checks the token between packets while decoding, between blocks while
+/// processing, and periodically while encoding.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation. Every clone of the token observes the request.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Runs the oxidation pipeline on files, using the settings from an [`OxidizerConfig`].
+pub struct Pipeline {
+    config: OxidizerConfig,
+    progress: Option<ProgressCallback>,
+    cancellation: CancellationToken,
+    max_frames: Option<usize>,
+    range: TimeRange,
+    crossfade: Duration,
+    // Decoded sidechain and its sample rate, overriding the file of the config
+    sidechain: Option<(Vec<f32>, u32)>,
+    noise_only: bool,
+    stage_dir: Option<PathBuf>,
+    metering: bool,
+    // Levels recorded by the last render, when metering
+    meters: Mutex<Vec<StageLevels>>,
+}
+
+impl Pipeline {
+    pub fn new(config: OxidizerConfig) -> Self {
+        Self {
+            config,
+            progress: None,
+            cancellation: CancellationToken::new(),
+            max_frames: None,
+            range: TimeRange::default(),
+            crossfade: Duration::ZERO,
+            sidechain: None,
+            noise_only: false,
+            stage_dir: None,
+            metering: false,
+            meters: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The settings this pipeline renders with.
+    pub fn config(&self) -> &OxidizerConfig {
+        &self.config
+    }
+
+    /// Registers a callback invoked periodically while decoding, processing and encoding.
+    pub fn set_progress_callback(
+        &mut self,
+        callback: impl Fn(Progress) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Makes the pipeline abort with [`OxidizerError::Cancelled`] once `token` is cancelled.
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) -> &mut Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Only processes the first `frames` frames of the input, e.g. to render a quick preview.
+    pub fn set_max_frames(&mut self, frames: usize) -> &mut Self {
+        self.max_frames = Some(frames);
+        self
+    }
+
+    /// Only decodes and processes the given window of the input.
+    pub fn set_range(&mut self, range: TimeRange) -> &mut Self {
+        self.range = range;
+        self
+    }
+
+    /// Overlaps consecutive inputs of a joined run by this long. [default: none]
+    pub fn set_crossfade(&mut self, crossfade: Duration) -> &mut Self {
+        self.crossfade = crossfade;
+        self
+    }
+
+    /// Uses an already decoded interleaved stereo buffer as the sidechain, instead of
+    /// the file named in the config.
+    pub fn set_sidechain(&mut self, samples: Vec<f32>, sample_rate: u32) -> &mut Self {
+        self.sidechain = Some((samples, sample_rate));
+        self
+    }
+
+    /// Renders only what the chain adds to the program (the noise, the artifacts, the
+    /// overlays and their interplay with the saturation) at the level it has in the
+    /// full render, e.g. to import it into a DAW as a separate stem. The full render
+    /// minus this layer is the processed program without them.
+    pub fn set_noise_only(&mut self, noise_only: bool) -> &mut Self {
+        self.noise_only = noise_only;
+        self
+    }
+
+    /// Writes the main chain's buffer after the filter, after the noise is added and
+    /// after the saturation to `filtered.wav`, `noise.wav` and `saturated.wav` in `dir`
+    /// (created if needed), as 32-bit float so nothing clips. The output file stays the
+    /// final result. Branches and segments aren't captured.
+    pub fn set_stage_export(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.stage_dir = Some(dir.into());
+        self
+    }
+
+    /// Measures the peak and RMS level of the main chain's buffer after every stage that
+    /// runs (the input, the filter, the noise, the saturation, each effect and the
+    /// normalization), to find the stage that clips or squashes the signal. Read them
+    /// with [`stage_levels`](Self::stage_levels) once processing is done; runs with a
+    /// report carry them too. Branches and segments aren't metered.
+    pub fn set_metering(&mut self, metering: bool) -> &mut Self {
+        self.metering = metering;
+        self
+    }
+
+    /// The levels between the stages of the last render, in chain order. Empty unless
+    /// metering is on.
+    pub fn stage_levels(&self) -> Vec<StageLevels> {
+        self.lock_meters().clone()
+    }
+
+    /// Layers a recording (a file path or a `(samples, sample_rate)` buffer) over the
+    /// output at `gain_db`, looping it if it's shorter than the render and
+    /// `loop_if_shorter` is set. Can be called repeatedly to stack several beds.
+    pub fn overlay(
+        &mut self,
+        source: impl Into<OverlaySource>,
+        gain_db: f32,
+        loop_if_shorter: bool,
+    ) -> &mut Self {
+        self.config
+            .overlay
+            .push(Overlay::new(source, gain_db, loop_if_shorter));
+        self
+    }
+
+    /// Processes a section of the input with other settings, e.g. a heavily muffled
+    /// intro before the rest opens up. Times are positions in the input. Can be called
+    /// repeatedly; later segments win where they overlap. See [`Segment`].
+    pub fn apply_in_range(
+        &mut self,
+        range: impl RangeBounds<Duration>,
+        config: OxidizerConfig,
+    ) -> &mut Self {
+        let start_secs = match range.start_bound() {
+            Bound::Included(start) | Bound::Excluded(start) => start.as_secs_f64(),
+            Bound::Unbounded => 0.0,
+        };
+        let end_secs = match range.end_bound() {
+            Bound::Included(end) | Bound::Excluded(end) => Some(end.as_secs_f64()),
+            Bound::Unbounded => None,
+        };
+        self.config.segments.push(Segment {
+            start_secs,
+            end_secs,
+            config: Box::new(config),
+        });
+        self
+    }
+
+    /// Decodes `input`, oxidizes it, and writes the result to `output` as a WAV file.
+    ///
+    /// If the run is cancelled or fails while encoding, the partially written output
+    /// file is removed.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "info", skip(self), fields(config = ?self.config)))]
+    pub fn run(&self, input: &Path, output: &Path) -> Result<()> {
+        let sample_rate = self.input_sample_rate(input)?;
+        let samples = self.decode(input)?;
+        let processed = self.process(samples, sample_rate)?;
+        self.encode(processed, output)
+    }
+
+    /// Same as [`run`](Self::run), but also measures the audio before and after
+    /// processing and returns a summary of the run.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "info", skip(self), fields(config = ?self.config)))]
+    pub fn run_with_report(&self, input: &Path, output: &Path) -> Result<RunReport> {
+        self.run_joined_with_report(&[input], output)
+    }
+
+    /// Decodes `inputs` one after another, crossfaded as set with
+    /// [`set_crossfade`](Self::set_crossfade), and oxidizes them as one continuous
+    /// program, so the noise floor and the filter run on across the joins.
+    ///
+    /// Inputs at a different sample rate than the first are resampled to it. The time
+    /// range applies to the joined program.
+    pub fn run_joined_with_report(
+        &self,
+        inputs: &[impl AsRef<Path>],
+        output: &Path,
+    ) -> Result<RunReport> {
+        let Some((input, joined)) = inputs.split_first() else {
+            return Err(OxidizerError::InvalidValue(
+                "Nothing to join: no inputs given".to_string(),
+            ));
+        };
+        let input = input.as_ref();
+
+        let started = Instant::now();
+        // Catch unwritable settings before spending time on decoding
+        self.config.format.check(self.config.bit_depth)?;
+        self.config.validate()?;
+        let input_sample_rate = self.input_sample_rate(input)?;
+
+        let samples = match joined {
+            [] => self.decode(input)?,
+            _ => self.decode_joined(inputs, input_sample_rate)?,
+        };
+        let frames = (samples.len() / 2) as f64;
+        let before = Levels::measure(&samples, input_sample_rate);
+
+        let processed = self.process(samples, input_sample_rate)?;
+        let after = Levels::measure(&processed, input_sample_rate);
+        self.encode(processed, output)?;
+
+        Ok(RunReport {
+            input: input.to_path_buf(),
+            joined: joined
+                .iter()
+                .map(|path| path.as_ref().to_path_buf())
+                .collect(),
+            output: output.to_path_buf(),
+            input_sample_rate,
+            output_sample_rate: self.config.sample_rate,
+            input_duration_secs: frames / input_sample_rate.max(1) as f64,
+            output_duration_secs: frames / self.config.sample_rate.max(1) as f64,
+            config: self.config.clone(),
+            before,
+            after,
+            elapsed_secs: started.elapsed().as_secs_f64(),
+            stages: self.stage_levels(),
+        })
+    }
+
+    /// Processes `inputs` back to back as one gapless album and writes each to the
+    /// output at the same position in `outputs`.
+    ///
+    /// The filter and noise run on across the track boundaries, and normalization
+    /// applies one gain to the whole album instead of levelling every track on its own,
+    /// so the tracks keep their relative loudness and play back without a seam. Inputs
+    /// are resampled to the rate of the first one. The time range and frame limit
+    /// don't apply.
+    pub fn run_album(
+        &self,
+        inputs: &[impl AsRef<Path>],
+        outputs: &[impl AsRef<Path>],
+    ) -> Result<Vec<RunReport>> {
+        if inputs.len() != outputs.len() {
+            return Err(OxidizerError::InvalidValue(format!(
+                "An album needs one output per input, got {} inputs and {} outputs",
+                inputs.len(),
+                outputs.len()
+            )));
+        }
+        let Some(first) = inputs.first() else {
+            return Ok(Vec::new());
+        };
+
+        let started = Instant::now();
+        self.config.format.check(self.config.bit_depth)?;
+        self.config.validate()?;
+        let sample_rate = self.input_sample_rate(first.as_ref())?;
+
+        let mut album = Vec::new();
+        let mut tracks = Vec::new();
+        self.decode_each(inputs, sample_rate, |samples| {
+            tracks.push(album.len()..album.len() + samples.len());
+            album.extend(samples);
+        })?;
+
+        let before: Vec<Levels> = tracks
+            .iter()
+            .map(|track| Levels::measure(&album[track.clone()], sample_rate))
+            .collect();
+        let processed = self.process(album, sample_rate)?;
+
+        let mut reports = Vec::with_capacity(tracks.len());
+        for (i, track) in tracks.into_iter().enumerate() {
+            let (input, output) = (inputs[i].as_ref(), outputs[i].as_ref());
+            let samples = processed[track].to_vec();
+            let after = Levels::measure(&samples, sample_rate);
+            let frames = (samples.len() / 2) as f64;
+            self.encode(samples, output)?;
+
+            reports.push(RunReport {
+                input: input.to_path_buf(),
+                joined: Vec::new(),
+                output: output.to_path_buf(),
+                input_sample_rate: sample_rate,
+                output_sample_rate: self.config.sample_rate,
+                input_duration_secs: frames / sample_rate.max(1) as f64,
+                output_duration_secs: frames / self.config.sample_rate.max(1) as f64,
+                config: self.config.clone(),
+                before: before[i],
+                after,
+                elapsed_secs: started.elapsed().as_secs_f64(),
+                // The meters span the whole album, see `stage_levels`
+                stages: Vec::new(),
+            });
+        }
+        Ok(reports)
+    }
+
+    // The rate the audio is processed at. Falls back to the output rate if the input
+    // doesn't declare one.
+    fn input_sample_rate(&self, input: &Path) -> Result<u32> {
+        Ok(io::probe(input)?
+            .sample_rate
+            .unwrap_or(self.config.sample_rate))
+    }
+
+    fn decode(&self, input: &Path) -> Result<Vec<f32>> {
+        let mut samples = io::load_audio_range_with_progress(input, self.range, |done, total| {
+            self.report(Stage::Decoding, done, total)
+        })?;
+        // Most likely a range starting past the end; there'd be nothing to write
+        if samples.is_empty() {
+            return Err(OxidizerError::EmptyInput(input.to_path_buf()));
+        }
+
+        if let Some(frames) = self.max_frames {
+            samples.truncate(frames * 2);
+        }
+        Ok(samples)
+    }
+
+    // Decodes every input whole, resampled to `sample_rate`, and joins them before
+    // applying the range.
+    fn decode_joined(&self, inputs: &[impl AsRef<Path>], sample_rate: u32) -> Result<Vec<f32>> {
+        let crossfade = (self.crossfade.as_secs_f64() * sample_rate as f64).round() as usize;
+        let mut program = Vec::new();
+        self.decode_each(inputs, sample_rate, |samples| {
+            io::append_crossfaded(&mut program, &samples, crossfade)
+        })?;
+
+        let (start, end) = self.range.frames(sample_rate);
+        let start = (start as usize * 2).min(program.len());
+        let mut end = end.map_or(program.len(), |end| (end as usize * 2).min(program.len()));
+        if let Some(frames) = self.max_frames {
+            end = end.min(start + frames * 2);
+        }
+        program.truncate(end);
+        program.drain(..start);
+        match (program.is_empty(), inputs.first()) {
+            (true, Some(first)) => Err(OxidizerError::EmptyInput(first.as_ref().to_path_buf())),
+            _ => Ok(program),
+        }
+    }
+
+    // Decodes the inputs one by one, resampled to `sample_rate`, and hands each to `each`.
+    fn decode_each(
+        &self,
+        inputs: &[impl AsRef<Path>],
+        sample_rate: u32,
+        mut each: impl FnMut(Vec<f32>),
+    ) -> Result<()> {
+        // Progress spans all inputs; unknown if any of them doesn't declare its length
+        let lengths = inputs
+            .iter()
+            .map(|path| io::probe(path.as_ref()).map(|info| info.frames))
+            .collect::<Result<Vec<_>>>()?;
+        let total = lengths.into_iter().sum::<Option<u64>>().unwrap_or(0);
+
+        let resampler = self.config.resample_quality.resampler();
+        let mut decoded = 0;
+        for path in inputs {
+            let path = path.as_ref();
+            let samples = io::load_audio_with_progress(path, |done, _| {
+                self.report(Stage::Decoding, decoded + done, total)
+            })?;
+            decoded += (samples.len() / 2) as u64;
+
+            let rate = self.input_sample_rate(path)?;
+            each(resampler.resample(&samples, rate, sample_rate));
+        }
+        Ok(())
+    }
+
+    fn encode(&self, samples: Vec<f32>, output: &Path) -> Result<()> {
+        io::save_audio_as(
+            output,
+            samples,
+            self.config.sample_rate,
+            self.config.format,
+            self.config.bit_depth,
+            |done, total| self.report(Stage::Encoding, done, total),
+        )
+        .inspect_err(|_| {
+            // Best effort: don't leave a truncated file behind
+            let _ = std::fs::remove_file(output);
+        })
+    }
+
+    // The sidechain the envelope responses listen to, if any of them does. Decodes the
+    // file of the config unless a buffer was set.
+    fn sidechain(&self, config: &OxidizerConfig) -> Result<Option<(Cow<'_, [f32]>, u32)>> {
+        let listens = config
+            .envelope
+            .iter()
+            .any(|response| response.source == EnvelopeSource::Sidechain);
+        if !listens {
+            return Ok(None);
+        }
+
+        if let Some((samples, sample_rate)) = &self.sidechain {
+            return Ok(Some((Cow::Borrowed(samples), *sample_rate)));
+        }
+        let Some(path) = &config.sidechain else {
+            return Err(OxidizerError::InvalidValue(
+                "An envelope response follows the sidechain, but no sidechain file is set"
+                    .to_string(),
+            ));
+        };
+        let sample_rate = self.input_sample_rate(path)?;
+        let samples = io::load_audio_range(path, self.range)?;
+        Ok(Some((Cow::Owned(samples), sample_rate)))
+    }
+
+    /// Oxidizes an already decoded interleaved stereo buffer, without touching any file.
+    ///
+    /// `sample_rate` is the rate of `samples`, needed for loudness normalization.
+    /// Progress is reported for the processing stage only. A sidechain file named in
+    /// the config is decoded here when an envelope response listens to it.
+    ///
+    /// Settings out of range (see [`OxidizerConfig::validate`]) are rejected up front.
+    pub fn process(&self, mut samples: Vec<f32>, sample_rate: u32) -> Result<Vec<f32>> {
+        self.config.validate()?;
+        self.lock_meters().clear();
+        let mut config = Cow::Borrowed(&self.config);
+        if let Normalization::Smart { ceiling } = config.normalization {
+            // The estimate has to hear the same noise as the render
+            let seeded = seeded(&config, config.seed.unwrap_or_else(fallback_seed));
+            let gain = self.smart_gain(&seeded, &samples, sample_rate, ceiling)?;
+            #[cfg(feature = "tracing")]
+            tracing::debug!(gain_db = analysis::to_dbfs(gain), "staged the input level");
+            samples.iter_mut().for_each(|s| *s *= gain);
+            config = Cow::Owned(seeded);
+        }
+
+        if self.noise_only {
+            return self.process_noise_only(&config, samples, sample_rate);
+        }
+        let looped = config.loop_crossfade_ms.is_some();
+        if config.segments.is_empty() && config.branches.is_empty() && !looped {
+            return self.render(&config, samples, sample_rate, true);
+        }
+
+        // Branches are summed and segments spliced into the main render, and the result
+        // normalized as a whole
+        let output = self.render_program(&config, samples, sample_rate, true)?;
+        let mut oxidizer = Oxidizer::new(WhiteNoise::default());
+        oxidizer.consume(output);
+        normalize(&mut oxidizer, config.normalization, sample_rate);
+        Ok(oxidizer.collect_samples())
+    }
+
+    // The input gain at which the render of `config` should peak at `ceiling`, estimated
+    // from a render at unity gain. Everything is taken to meet at the `tanh()`: the noise
+    // fills up to its perceived intensity of the drive it can take, and the program
+    // scales into the rest.
+    fn smart_gain(
+        &self,
+        config: &OxidizerConfig,
+        samples: &[f32],
+        sample_rate: u32,
+        ceiling: f32,
+    ) -> Result<f32> {
+        let probe = OxidizerConfig {
+            normalization: Normalization::None,
+            ..config.clone()
+        };
+        let rendered = self.render_program(&probe, samples.to_vec(), sample_rate, false)?;
+
+        // The drive that saturates to `level`
+        let drive = |level: f32| {
+            let level = level.clamp(0.0, 0.999);
+            0.5 * math::ln((1.0 + level) / (1.0 - level))
+        };
+        let noise = config
+            .intensity_curve
+            .apply(config.intensity.clamp(0.0, 1.0));
+        let program = drive(analysis::peak(&rendered)) - noise;
+        let room = drive(ceiling) - noise;
+        // With nothing to scale, or the noise alone over the ceiling, the output trim does it all
+        if program <= f32::EPSILON || room <= 0.0 {
+            return Ok(1.0);
+        }
+        Ok(room / program)
+    }
+
+    /// Same as [`process`](Self::process) for planar audio, processed in place: one or
+    /// two channels of the same length (e.g. `&mut [Vec<f32>]` or `&mut [&mut [f32]]`).
+    /// A single channel runs through both sides and gets their average back.
+    ///
+    /// # Errors
+    /// The errors of [`process`](Self::process), and [`OxidizerError::InvalidValue`] for
+    /// any other number of channels or channels of different lengths.
+    pub fn process_planar<C: AsRef<[f32]> + AsMut<[f32]>>(
+        &self,
+        channels: &mut [C],
+        sample_rate: u32,
+    ) -> Result<()> {
+        let mut oxidizer = Oxidizer::new(WhiteNoise::default());
+        let samples = oxidizer.load_planar(channels)?.collect_samples();
+        let processed = self.process(samples, sample_rate)?;
+        oxidizer.consume(processed).collect_planar(channels);
+        Ok(())
+    }
+
+    /// Renders `samples` like [`process`](Self::process) and returns the render with
+    /// the input, gain-matched for a loudness-fair comparison (see [`MatchedPair`]).
+    pub fn process_matched(&self, samples: Vec<f32>, sample_rate: u32) -> Result<MatchedPair> {
+        let wet = self.process(samples.clone(), sample_rate)?;
+        Ok(MatchedPair::new(samples, wet, sample_rate))
+    }
+
+    /// Same as [`run`](Self::run), but also writes the input to `dry_output`, both
+    /// gain-matched as by [`process_matched`](Self::process_matched).
+    pub fn run_matched(&self, input: &Path, dry_output: &Path, wet_output: &Path) -> Result<()> {
+        let sample_rate = self.input_sample_rate(input)?;
+        let samples = self.decode(input)?;
+        let pair = self.process_matched(samples, sample_rate)?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            dry_gain_db = pair.dry_gain_db,
+            wet_gain_db = pair.wet_gain_db,
+            "matched the loudness of the render"
+        );
+        self.encode(pair.dry, dry_output)?;
+        self.encode(pair.wet, wet_output)
+    }
+
+    // Renders the program once as configured and once without anything added to it, and
+    // returns the difference at the gain the full render gets from normalization.
+    fn process_noise_only(
+        &self,
+        config: &OxidizerConfig,
+        samples: Vec<f32>,
+        sample_rate: u32,
+    ) -> Result<Vec<f32>> {
+        // Both renders have to draw the same random modulation
+        let full = seeded(config, config.seed.unwrap_or_else(fallback_seed));
+        let clean = without_noise(&full);
+
+        let full = self.render_program(&full, samples.clone(), sample_rate, true)?;
+        let clean = self.render_program(&clean, samples, sample_rate, false)?;
+        let gain = normalization_gain(&full, config.normalization, sample_rate);
+        Ok(full
+            .iter()
+            .zip(&clean)
+            .map(|(full, clean)| (full - clean) * gain)
+            .collect())
+    }
+
+    // Renders `config` with its branches, segments and loop, without normalizing.
+    fn render_program(
+        &self,
+        config: &OxidizerConfig,
+        mut samples: Vec<f32>,
+        sample_rate: u32,
+        progress: bool,
+    ) -> Result<Vec<f32>> {
+        // A loop renders on past the end into the start of the input again
+        let wrap = config.loop_crossfade_ms.map_or(0, |ms| {
+            let frames = (ms as f64 / 1000.0 * sample_rate as f64).round() as usize;
+            frames.min(samples.len() / 2)
+        });
+        samples.extend_from_within(..wrap * 2);
+
+        let mut output = self.render_graph(config, &samples, sample_rate, progress)?;
+        for segment in &config.segments {
+            self.render_segment(segment, &samples, &mut output, sample_rate)?;
+        }
+        if config.loop_crossfade_ms.is_some() {
+            close_loop(&mut output, wrap);
+        }
+        Ok(output)
+    }
+
+    fn render(
+        &self,
+        config: &OxidizerConfig,
+        samples: Vec<f32>,
+        sample_rate: u32,
+        progress: bool,
+    ) -> Result<Vec<f32>> {
+        match config.noise {
+            NoiseConfig::White => self.oxidize(
+                config,
+                samples,
+                sample_rate,
+                WhiteNoise::default(),
+                progress,
+            ),
+            NoiseConfig::Brown { damping, step } => self.oxidize(
+                config,
+                samples,
+                sample_rate,
+                BrownianNoise::new(damping, step)?,
+                progress,
+            ),
+            NoiseConfig::Crackle { density } => self.oxidize(
+                config,
+                samples,
+                sample_rate,
+                CrackleNoise::new(density)?,
+                progress,
+            ),
+        }
+    }
+
+    // Renders `config` and adds its branches (and theirs) on top, without normalizing.
+    fn render_graph(
+        &self,
+        config: &OxidizerConfig,
+        samples: &[f32],
+        sample_rate: u32,
+        progress: bool,
+    ) -> Result<Vec<f32>> {
+        let main = OxidizerConfig {
+            normalization: Normalization::None,
+            ..config.clone()
+        };
+        let mut output = self.render(&main, samples.to_vec(), sample_rate, progress)?;
+
+        for (i, branch) in config.branches.iter().enumerate() {
+            // Sharing the parent's seed would make the two noise textures add up coherently
+            let seed = config.seed.map(|seed| seed.wrapping_add(i as u64 + 1));
+            let branch_config = OxidizerConfig {
+                segments: Vec::new(),
+                seed: branch.config.seed.or(seed),
+                ..(*branch.config).clone()
+            };
+            let input = match branch.input {
+                BranchInput::Input => samples.to_vec(),
+                BranchInput::Silence => vec![0.0; samples.len()],
+            };
+            let rendered = self.render_graph(&branch_config, &input, sample_rate, false)?;
+
+            for (out, sample) in output.iter_mut().zip(rendered) {
+                *out += sample * branch.gain();
+            }
+        }
+        Ok(output)
+    }
+
+    // Renders the section of `input` covered by `segment` with its settings and splices
+    // it into `output`, fading over at both ends.
+    fn render_segment(
+        &self,
+        segment: &Segment,
+        input: &[f32],
+        output: &mut [f32],
+        sample_rate: u32,
+    ) -> Result<()> {
+        let frames = input.len() / 2;
+        // Segment times are positions in the input, like the pipeline's range
+        let to_frame = |secs: f64| {
+            let secs = secs - self.range.start.as_secs_f64();
+            ((secs.max(0.0) * sample_rate as f64).round() as usize).min(frames)
+        };
+        let start = to_frame(segment.start_secs);
+        let end = segment.end_secs.map_or(frames, to_frame);
+        if start >= end {
+            return Ok(());
+        }
+
+        let fade = (SEGMENT_FADE_SECS * sample_rate as f64) as usize;
+        // Start early so the filter has settled by the time the segment fades in
+        let from = start.saturating_sub(fade);
+        let to = (end + fade).min(frames);
+        let config = OxidizerConfig {
+            segments: Vec::new(),
+            seed: segment.config.seed.or(self.config.seed),
+            ..(*segment.config).clone()
+        };
+        let rendered = self.render_graph(&config, &input[from * 2..to * 2], sample_rate, false)?;
+
+        for frame in from..to {
+            // Ramps up over the frames before the start and down over those after the end
+            let weight = if frame < start {
+                (frame - from) as f32 / fade as f32
+            } else if frame >= end {
+                1.0 - (frame - end) as f32 / fade as f32
+            } else {
+                1.0
+            };
+            for channel in 0..2 {
+                let i = frame * 2 + channel;
+                let part = rendered[i - from * 2];
+                output[i] += (part - output[i]) * weight;
+            }
+        }
+        Ok(())
+    }
+
+    // Processes the buffer block by block so progress can be reported along the way.
+    // All passes run in one traversal of each block, exactly like `process_multiple`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "info", skip_all, fields(frames = samples.len() / 2)))]
+    fn oxidize<N: NoiseGenerator>(
+        &self,
+        config: &OxidizerConfig,
+        mut samples: Vec<f32>,
+        sample_rate: u32,
+        mut noise: N,
+        progress: bool,
+    ) -> Result<Vec<f32>> {
+        // Seeds the noise and the random modulators, so every pass (and a seeded rerun)
+        // matches
+        let seed = config.seed.unwrap_or_else(fallback_seed);
+        noise.reseed(seed);
+        // Like the stages, only the main chain is metered
+        let metering = self.metering && progress;
+        let meter = |stage: &str, samples: &[f32]| {
+            if metering {
+                self.lock_meters()
+                    .push(StageLevels::measure(stage, samples));
+            }
+        };
+        meter("input", &samples);
+        // The spread, wavering, chorused input is what the rest of the chain (and the dry mix) hears
+        if let Some(haas) = &config.haas {
+            haas.process(&mut samples, sample_rate);
+            meter("haas", &samples);
+        }
+        if let Some(flutter) = &config.flutter {
+            flutter.process(&mut samples, sample_rate, seed);
+            meter("flutter", &samples);
+        }
+        if let Some(wobble) = &config.wobble {
+            wobble.process(&mut samples, sample_rate);
+            meter("wobble", &samples);
+        }
+        if let Some(chorus) = &config.chorus {
+            chorus.process(&mut samples, sample_rate);
+            meter("chorus", &samples);
+        }
+        let sidechain = self.sidechain(config)?;
+        let controls = Controls::new(config, &samples, sample_rate, seed)
+            .with_sidechain(sidechain.as_ref().map(|(s, rate)| (&s[..], *rate)));
+        // Only keep a copy of the input when some of it is mixed back in, or it keys the gate
+        let gate = config.gate.as_ref();
+        let keyed = gate.is_some_and(|gate| gate.mode == GateMode::Output);
+        let dry = (controls.mixes() || keyed).then(|| samples.clone());
+
+        let mut oxidizer = Oxidizer::new(noise);
+        oxidizer
+            .set_intensity_curve(config.intensity_curve.clone())
+            // Automated values ramp from one control block to the next
+            .set_smoothing(CONTROL_BLOCK_FRAMES)
+            .set_noise_ducking(config.noise_ducking, sample_rate)
+            .set_channels(config.channels, sample_rate)
+            .consume(samples);
+
+        let frames = oxidizer.frames();
+        let total = frames as u64 * (config.passes as u64 + 1);
+        // Segment renders only check for cancellation
+        let report = |done| match progress {
+            true => self.report(Stage::Processing, done, total),
+            false => self.check_cancelled(),
+        };
+        let mut done = 0;
+        // Only the main chain's stages are written
+        let stages = self.stage_dir.as_deref().filter(|_| progress);
+        let add_noise = |oxidizer: &mut Oxidizer<N>, done: &mut u64| -> Result<()> {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("noise", intensity = config.intensity).entered();
+            if config.riaa {
+                oxidizer.riaa(Riaa::PreEmphasis, sample_rate);
+            }
+            for start in (0..frames).step_by(BLOCK_FRAMES) {
+                let end = (start + BLOCK_FRAMES).min(frames);
+                for (block, time) in control_blocks(start..end, sample_rate) {
+                    let intensity = controls.intensity(&block, time);
+                    match stages.is_some() || metering {
+                        // The sum has to be captured before it's saturated
+                        true => oxidizer.add_noise_frames(intensity, block),
+                        false => oxidizer.apply_noise_texture_frames(intensity, block),
+                    };
+                }
+                *done += (end - start) as u64;
+                report(*done)?;
+            }
+            if stages.is_some() || metering {
+                let mut noise = oxidizer.samples().to_vec();
+                if config.riaa {
+                    Riaa::DeEmphasis.process(&mut noise, sample_rate);
+                }
+                meter("noise", &noise);
+                if let Some(dir) = stages {
+                    write_stage(dir, "noise", noise, sample_rate)?;
+                }
+                oxidizer.saturate_frames(0..frames);
+            }
+            if config.riaa {
+                oxidizer.riaa(Riaa::DeEmphasis, sample_rate);
+            }
+            meter("saturation", oxidizer.samples());
+            if let Some(dir) = stages {
+                write_stage(dir, "saturated", oxidizer.samples().to_vec(), sample_rate)?;
+            }
+            Ok(())
+        };
+
+        let pre_filter = config.noise_routing == NoiseRouting::PreFilter;
+        let program_gate = gate.filter(|gate| gate.mode == GateMode::Program);
+        if pre_filter {
+            // The program gate can only leave the noise alone before it's mixed in
+            if let Some(gate) = program_gate {
+                oxidizer.gate(gate, None, sample_rate);
+                meter("gate", oxidizer.samples());
+            }
+            add_noise(&mut oxidizer, &mut done)?;
+        }
+
+        // Every pass sees the same sweep
+        let mut lfo = config.lfo.map(|lfo| lfo.start(seed));
+        let passes = config.passes;
+        #[cfg(feature = "tracing")]
+        let filter_span = tracing::debug_span!("filter", passes, level = ?config.level).entered();
+        for start in (0..frames).step_by(BLOCK_FRAMES) {
+            let end = (start + BLOCK_FRAMES).min(frames);
+            if controls.varies_cutoff() {
+                for (block, time) in control_blocks(start..end, sample_rate) {
+                    let hz = controls.cutoff(&block, time, lfo.as_mut());
+                    let alpha = alpha_for_cutoff(hz, sample_rate);
+                    oxidizer.filter_frames_cascaded(alpha, passes, block);
+                }
+            } else {
+                oxidizer.filter_frames_cascaded(config.level.alpha(), passes, start..end);
+            }
+            done += (end - start) as u64 * passes as u64;
+            report(done)?;
+        }
+
+        #[cfg(feature = "tracing")]
+        drop(filter_span);
+        meter("filter", oxidizer.samples());
+        if let Some(dir) = stages {
+            write_stage(dir, "filtered", oxidizer.samples().to_vec(), sample_rate)?;
+        }
+
+        if let Some(shaper) = &config.transients {
+            oxidizer.shape_transients(shaper, sample_rate);
+            meter("transients", oxidizer.samples());
+        }
+        if !pre_filter {
+            if let Some(gate) = program_gate {
+                oxidizer.gate(gate, None, sample_rate);
+                meter("gate", oxidizer.samples());
+            }
+            add_noise(&mut oxidizer, &mut done)?;
+        }
+        if !config.artifacts.is_empty() {
+            let duration = frames as f64 / sample_rate.max(1) as f64;
+            let events = config.artifacts.schedule(duration, seed);
+            oxidizer.add_artifacts(&events, sample_rate);
+            meter("artifacts", oxidizer.samples());
+        }
+        for band in &config.eq {
+            oxidizer.equalize(band, sample_rate);
+        }
+        if !config.eq.is_empty() {
+            meter("eq", oxidizer.samples());
+        }
+        if let Some(line) = &config.telephone {
+            oxidizer.telephone(line, sample_rate);
+            meter("telephone", oxidizer.samples());
+        }
+        if let Some(codec) = &config.lossy {
+            oxidizer.lossy_codec(codec, sample_rate);
+            meter("lossy codec", oxidizer.samples());
+        }
+        if let Some(dry) = dry.as_ref().filter(|_| controls.mixes()) {
+            for (block, time) in control_blocks(0..frames, sample_rate) {
+                oxidizer.mix_frames(dry, controls.mix(&block, time), block);
+            }
+            meter("mix", oxidizer.samples());
+        }
+
+        if let Some(gate) = gate.filter(|_| keyed) {
+            oxidizer.gate(gate, dry.as_deref(), sample_rate);
+            meter("gate", oxidizer.samples());
+        }
+        if let Some(ground_loop) = &config.ground_loop {
+            oxidizer.ground_loop(ground_loop, sample_rate);
+            meter("ground loop", oxidizer.samples());
+        }
+        if let Some(expander) = &config.expander {
+            oxidizer.expand(expander, sample_rate);
+            meter("expander", oxidizer.samples());
+        }
+        if let Some(compressor) = &config.compressor {
+            oxidizer.compress(compressor, sample_rate);
+            meter("compressor", oxidizer.samples());
+        }
+        if let Some(limiter) = &config.limiter {
+            oxidizer.limit(limiter, sample_rate);
+            meter("limiter", oxidizer.samples());
+        }
+
+        let resampler = config.resample_quality.resampler();
+        for overlay in &config.overlay {
+            oxidizer.overlay(
+                &overlay.load_with(sample_rate, resampler.as_ref())?,
+                overlay.gain(),
+                overlay.loop_if_shorter,
+            );
+        }
+        if !config.overlay.is_empty() {
+            meter("overlays", oxidizer.samples());
+        }
+        if let Some(crossfeed) = &config.crossfeed {
+            oxidizer.crossfeed(crossfeed, sample_rate);
+            meter("crossfeed", oxidizer.samples());
+        }
+
+        normalize(&mut oxidizer, config.normalization, sample_rate);
+        meter("normalization", oxidizer.samples());
+        Ok(oxidizer.collect_samples())
+    }
+
+    // A poisoned lock only means a render panicked halfway; its levels are still readable
+    fn lock_meters(&self) -> std::sync::MutexGuard<'_, Vec<StageLevels>> {
+        self.meters.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        match self.cancellation.is_cancelled() {
+            true => Err(OxidizerError::Cancelled),
+            false => Ok(()),
+        }
+    }
+
+    // Forwards progress to the callback and bails out if cancellation was requested.
+    fn report(&self, stage: Stage, processed_frames: u64, total_frames: u64) -> Result<()> {
+        if self.cancellation.is_cancelled() {
+            #[cfg(feature = "tracing")]
+            tracing::info!(?stage, processed_frames, "cancelled");
+            return Err(OxidizerError::Cancelled);
+        }
+
+        if let Some(callback) = &self.progress {
+            callback(Progress {
+                stage,
+                processed_frames,
+                total_frames,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Seed of renders without one. Deterministic builds use a fixed seed rather than a
+/// random one.
+pub fn fallback_seed() -> u64 {
+    match cfg!(feature = "deterministic") {
+        true => 0,
+        false => rand::random(),
+    }
+}
+
+// `config` with `seed` wherever it would otherwise pick one, including its segments.
+fn seeded(config: &OxidizerConfig, seed: u64) -> OxidizerConfig {
+    let seed = config.seed.unwrap_or(seed);
+    let mut config = OxidizerConfig {
+        seed: Some(seed),
+        ..config.clone()
+    };
+    // Segments fall back to the main seed, branches derive theirs from their parent's
+    for segment in &mut config.segments {
+        segment.config.seed = segment.config.seed.or(Some(seed));
+    }
+    config
+}
+
+// `config` without anything the chain adds to the program: the noise, the artifacts and
+// the overlays, in branches and segments as well.
+fn without_noise(config: &OxidizerConfig) -> OxidizerConfig {
+    let mut clean = OxidizerConfig {
+        intensity: 0.0,
+        artifacts: Artifacts::default(),
+        overlay: Vec::new(),
+        ground_loop: None,
+        ..config.clone()
+    };
+    clean.automation.intensity = None;
+    clean
+        .drift
+        .retain(|drift| drift.target != AutomationTarget::Intensity);
+    for branch in &mut clean.branches {
+        *branch.config = without_noise(&branch.config);
+    }
+    for segment in &mut clean.segments {
+        *segment.config = without_noise(&segment.config);
+    }
+    clean
+}
+
+// The gain `normalization` applies to `samples`.
+fn normalization_gain(samples: &[f32], normalization: Normalization, sample_rate: u32) -> f32 {
+    let loudest = (0..samples.len()).max_by(|&a, &b| samples[a].abs().total_cmp(&samples[b].abs()));
+    let Some(loudest) = loudest.filter(|&i| samples[i] != 0.0) else {
+        return 1.0;
+    };
+
+    let mut oxidizer = Oxidizer::new(WhiteNoise::default());
+    oxidizer.consume(samples.to_vec());
+    normalize(&mut oxidizer, normalization, sample_rate);
+    oxidizer.collect_samples()[loudest] / samples[loudest]
+}
+
+// Writes one stage of the chain to `<dir>/<name>.wav` as 32-bit float.
+fn write_stage(dir: &Path, name: &str, samples: Vec<f32>, sample_rate: u32) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    io::save_audio_as(
+        &dir.join(name).with_extension("wav"),
+        samples,
+        sample_rate,
+        io::AudioFormat::Wav,
+        io::BitDepth::Float32,
+        |_, _| Ok(()),
+    )
+}
+
+// Fades the start of a render in over the `wrap` frames rendered past its end, and cuts
+// those off. They carry on from the filter, dynamics and noise state at the end, so the
+// end of the result runs straight into its start.
+fn close_loop(output: &mut Vec<f32>, wrap: usize) {
+    let end = output.len().saturating_sub(wrap * 2);
+    let (looped, past_end) = output.split_at_mut(end);
+    for (frame, (start, past)) in looped
+        .chunks_exact_mut(2)
+        .zip(past_end.chunks_exact(2))
+        .enumerate()
+    {
+        let weight = frame as f32 / wrap as f32;
+        for (sample, past) in start.iter_mut().zip(past) {
+            *sample = past + (*sample - past) * weight;
+        }
+    }
+    output.truncate(end);
+}
+
+fn normalize<N: NoiseGenerator>(
+    oxidizer: &mut Oxidizer<N>,
+    normalization: Normalization,
+    sample_rate: u32,
+) {
+    match normalization {
+        Normalization::None => {}
+        // The smart normalization staged the input already, and only trims what's left
+        Normalization::Peak { ceiling } | Normalization::Smart { ceiling } => {
+            oxidizer.normalize_to(ceiling);
+        }
+        Normalization::Loudness {
+            target_lufs,
+            true_peak,
+        } => {
+            oxidizer.normalize_loudness(sample_rate, target_lufs, true_peak);
+        }
+    }
+}
+
+// Evaluates the parameters of a render for each control block, combining the fixed
+// settings with automation curves, drift, the LFO and envelope responses.
+struct Controls<'a> {
+    config: &'a OxidizerConfig,
+    sample_rate: u32,
+    seed: u64,
+    // Render length in seconds
+    length: f32,
+    base_cutoff: f32,
+    // Input level at the end of every control block, when an envelope response needs it
+    envelope: Vec<f32>,
+    // Same for the sidechain, with its sample rate
+    sidechain: Option<(Vec<f32>, u32)>,
+}
+
+impl<'a> Controls<'a> {
+    fn new(config: &'a OxidizerConfig, samples: &[f32], sample_rate: u32, seed: u64) -> Self {
+        Self {
+            config,
+            sample_rate,
+            seed,
+            length: (samples.len() / 2) as f32 / sample_rate.max(1) as f32,
+            base_cutoff: cutoff_for_alpha(config.level.alpha(), sample_rate),
+            envelope: follow(samples, sample_rate, &config.envelope),
+            sidechain: None,
+        }
+    }
+
+    fn with_sidechain(mut self, sidechain: Option<(&[f32], u32)>) -> Self {
+        self.sidechain =
+            sidechain.map(|(samples, rate)| (follow(samples, rate, &self.config.envelope), rate));
+        self
+    }
+
+    // Returns `true` if the cutoff needs to be evaluated per control block.
+    fn varies_cutoff(&self) -> bool {
+        self.config.automation.cutoff.is_some()
+            || self.config.lfo.is_some()
+            || self.responds(AutomationTarget::Cutoff)
+            || self.drifts(AutomationTarget::Cutoff)
+    }
+
+    // Returns `true` if any of the dry signal is mixed back in.
+    fn mixes(&self) -> bool {
+        self.config.mix < 1.0
+            || self.config.automation.mix.is_some()
+            || self.responds(AutomationTarget::Mix)
+            || self.drifts(AutomationTarget::Mix)
+    }
+
+    fn responds(&self, target: AutomationTarget) -> bool {
+        self.config.envelope.iter().any(|r| r.target == target)
+    }
+
+    fn drifts(&self, target: AutomationTarget) -> bool {
+        self.config.drift.iter().any(|d| d.target == target)
+    }
+
+    fn cutoff(&self, block: &Range<usize>, time: f32, lfo: Option<&mut LfoOscillator>) -> f32 {
+        let mut hz = self.value(AutomationTarget::Cutoff, self.base_cutoff, block, time);
+        if let (Some(config), Some(oscillator)) = (&self.config.lfo, lfo) {
+            let seconds = block.len() as f32 / self.sample_rate.max(1) as f32;
+            hz = config.modulate(hz, oscillator.advance(seconds));
+        }
+        hz
+    }
+
+    // Envelope responses can push the intensity past its range; that's not worth a warning
+    fn intensity(&self, block: &Range<usize>, time: f32) -> f32 {
+        let intensity = self.value(
+            AutomationTarget::Intensity,
+            self.config.intensity,
+            block,
+            time,
+        );
+        intensity.clamp(0.0, 1.0)
+    }
+
+    fn mix(&self, block: &Range<usize>, time: f32) -> f32 {
+        self.value(AutomationTarget::Mix, self.config.mix, block, time)
+    }
+
+    // The drifting, automated or fixed value of `target`, scaled by the envelope responses.
+    fn value(&self, target: AutomationTarget, fixed: f32, block: &Range<usize>, time: f32) -> f32 {
+        let drift = self
+            .config
+            .drift
+            .iter()
+            .enumerate()
+            .find(|(_, d)| d.target == target);
+        let value = match drift {
+            // Each drift wanders its own way
+            Some((i, drift)) => drift.value_at(time, self.seed.wrapping_add(i as u64 + 1)),
+            None => self
+                .config
+                .automation
+                .get(target)
+                .map_or(fixed, |curve| curve.value_at(time, self.length)),
+        };
+
+        let responses = self.config.envelope.len();
+        self.config
+            .envelope
+            .iter()
+            .enumerate()
+            .filter(|(_, response)| response.target == target)
+            .fold(value, |value, (i, response)| {
+                let level = match (response.source, &self.sidechain) {
+                    (EnvelopeSource::Sidechain, Some((levels, rate))) => {
+                        // The sidechain may run at another rate, so line it up by time.
+                        // Once it ends, it's silent.
+                        let index = (time * *rate as f32) as usize / CONTROL_BLOCK_FRAMES;
+                        levels.get(index * responses + i).copied().unwrap_or(0.0)
+                    }
+                    _ => self.envelope[block.start / CONTROL_BLOCK_FRAMES * responses + i],
+                };
+                value * response.gain(level)
+            })
+    }
+}
+
+// Runs an envelope follower per response over the input and records each one's level at
+// the end of every control block, interleaved by response.
+fn follow(samples: &[f32], sample_rate: u32, responses: &[EnvelopeResponse]) -> Vec<f32> {
+    let mut followers: Vec<EnvelopeFollower> = responses
+        .iter()
+        .map(|r| EnvelopeFollower::new(r.attack_ms, r.release_ms, sample_rate))
+        .collect();
+
+    samples
+        .chunks(CONTROL_BLOCK_FRAMES * 2)
+        .flat_map(|block| {
+            followers
+                .iter_mut()
+                .map(|follower| follower.process_frames(block))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+// Splits `frames` into control blocks, each paired with the time in seconds at its start.
+fn control_blocks(
+    frames: Range<usize>,
+    sample_rate: u32,
+) -> impl Iterator<Item = (Range<usize>, f32)> {
+    let end = frames.end;
+    frames.step_by(CONTROL_BLOCK_FRAMES).map(move |start| {
+        let time = start as f32 / sample_rate.max(1) as f32;
+        (start..(start + CONTROL_BLOCK_FRAMES).min(end), time)
+    })
+}
+
+/// Runs the whole pipeline on `input` and writes the oxidized result to `output`.
+///
+/// ```no_run
+/// use oxidizer::{OxidationLevel, OxidizerConfig};
+///
+/// let config = OxidizerConfig {
+///     level: OxidationLevel::Muffled,
+///     passes: 3,
+///     ..Default::default()
+/// };
+/// oxidizer::run("music.mp3", "oxidized.wav", &config)?;
+/// # Ok::<(), oxidizer::OxidizerError>(())
+/// ```
+pub fn run(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    config: &OxidizerConfig,
+) -> Result<()> {
+    Pipeline::new(config.clone()).run(input.as_ref(), output.as_ref())
+}
+
+/// Renders `input` into `output` without allocating: the engine works in `scratch`,
+/// and nothing is decoded, encoded or copied along the way. Meant for profiling and for
+/// offline renderers that can't afford allocator noise.
+///
+/// Both buffers are interleaved stereo at `config.sample_rate` and must have the same
+/// length. Give the scratch room for the longest input up front with
+/// [`Scratch::new`]; a smaller one grows on the first call and is reused after that.
+/// The output matches [`Pipeline::process`] with the same settings and seed.
+///
+/// # Errors
+/// Returns [`OxidizerError::InvalidValue`] for buffers of different lengths and for
+/// settings that need memory of their own (automation and modulation, dynamics,
+/// stereo effects, artifacts, overlays, branches, segments, loops and loudness
+/// normalization), and the errors of [`OxidizerConfig::validate`].
+pub fn render_into(
+    input: &[f32],
+    output: &mut [f32],
+    scratch: &mut Scratch,
+    config: &OxidizerConfig,
+) -> Result<()> {
+    if input.len() != output.len() {
+        return Err(OxidizerError::InvalidValue(format!(
+            "render_into needs an output as long as the input ({} samples), got {}",
+            input.len(),
+            output.len()
+        )));
+    }
+    render_buffers(Interleaved { input, output }, scratch, config)
+}
+
+/// Same as [`render_into`] for planar audio, processed in place: one or two channels
+/// of the same length (e.g. `&mut [Vec<f32>]` or `&mut [&mut [f32]]`), as plugin hosts
+/// hand them out. A single channel runs through both sides of the engine and gets their
+/// average back.
+///
+/// # Errors
+/// The errors of [`render_into`], [`OxidizerError::ChannelMismatch`] for any other
+/// number of channels and [`OxidizerError::LengthMismatch`] for channels of different
+/// lengths.
+pub fn render_planar<C: AsRef<[f32]> + AsMut<[f32]>>(
+    channels: &mut [C],
+    scratch: &mut Scratch,
+    config: &OxidizerConfig,
+) -> Result<()> {
+    render_buffers(Planar(channels), scratch, config)
+}
+
+// Where a render without allocations reads its input and writes its result.
+trait Buffers {
+    fn load<N: NoiseGenerator>(&self, oxidizer: &mut Oxidizer<N>) -> Result<()>;
+    fn mix<N: NoiseGenerator>(&self, oxidizer: &mut Oxidizer<N>, mix: f32);
+    fn collect<N: NoiseGenerator>(&mut self, oxidizer: &mut Oxidizer<N>);
+}
+
+struct Interleaved<'a> {
+    input: &'a [f32],
+    output: &'a mut [f32],
+}
+
+impl Buffers for Interleaved<'_> {
+    fn load<N: NoiseGenerator>(&self, oxidizer: &mut Oxidizer<N>) -> Result<()> {
+        oxidizer.load(self.input);
+        Ok(())
+    }
+
+    fn mix<N: NoiseGenerator>(&self, oxidizer: &mut Oxidizer<N>, mix: f32) {
+        oxidizer.mix_with(self.input, mix);
+    }
+
+    fn collect<N: NoiseGenerator>(&mut self, oxidizer: &mut Oxidizer<N>) {
+        oxidizer.collect_into(self.output);
+    }
+}
+
+// Planar channels, holding the dry input until the result is collected into them.
+struct Planar<'a, C>(&'a mut [C]);
+
+impl<C: AsRef<[f32]> + AsMut<[f32]>> Buffers for Planar<'_, C> {
+    fn load<N: NoiseGenerator>(&self, oxidizer: &mut Oxidizer<N>) -> Result<()> {
+        oxidizer.load_planar(self.0)?;
+        Ok(())
+    }
+
+    fn mix<N: NoiseGenerator>(&self, oxidizer: &mut Oxidizer<N>, mix: f32) {
+        oxidizer.mix_planar(self.0, mix);
+    }
+
+    fn collect<N: NoiseGenerator>(&mut self, oxidizer: &mut Oxidizer<N>) {
+        oxidizer.collect_planar(self.0);
+    }
+}
+
+fn render_buffers(
+    buffers: impl Buffers,
+    scratch: &mut Scratch,
+    config: &OxidizerConfig,
+) -> Result<()> {
+    config.validate()?;
+    if let Some(setting) = allocating_setting(config) {
+        return Err(OxidizerError::InvalidValue(format!(
+            "Can't apply {} without allocating; use Pipeline::process",
+            setting
+        )));
+    }
+
+    match config.noise {
+        NoiseConfig::White => render_with(buffers, scratch, config, WhiteNoise::default()),
+        NoiseConfig::Brown { damping, step } => {
+            render_with(buffers, scratch, config, BrownianNoise::new(damping, step)?)
+        }
+        NoiseConfig::Crackle { density } => {
+            render_with(buffers, scratch, config, CrackleNoise::new(density)?)
+        }
+    }
+}
+
+// The same chain as `Pipeline::oxidize` for the settings `render_into` accepts.
+fn render_with<N: NoiseGenerator>(
+    mut buffers: impl Buffers,
+    scratch: &mut Scratch,
+    config: &OxidizerConfig,
+    mut noise: N,
+) -> Result<()> {
+    let sample_rate = config.sample_rate;
+    noise.reseed(config.seed.unwrap_or_else(fallback_seed));
+    let mut oxidizer = Oxidizer::with_scratch(noise, std::mem::take(scratch));
+    oxidizer
+        .set_intensity_curve(config.intensity_curve.clone())
+        // Ramps like `Pipeline::oxidize`, so the output is the same
+        .set_smoothing(CONTROL_BLOCK_FRAMES)
+        .set_noise_ducking(config.noise_ducking, sample_rate)
+        .set_channels(config.channels, sample_rate);
+    if let Err(e) = buffers.load(&mut oxidizer) {
+        *scratch = oxidizer.into_scratch();
+        return Err(e);
+    }
+
+    let frames = oxidizer.frames();
+    let add_noise = |oxidizer: &mut Oxidizer<N>| {
+        if config.riaa {
+            oxidizer.riaa(Riaa::PreEmphasis, sample_rate);
+        }
+        oxidizer.apply_noise_texture_frames(config.intensity, 0..frames);
+        if config.riaa {
+            oxidizer.riaa(Riaa::DeEmphasis, sample_rate);
+        }
+    };
+
+    let pre_filter = config.noise_routing == NoiseRouting::PreFilter;
+    if pre_filter {
+        add_noise(&mut oxidizer);
+    }
+    oxidizer.filter_frames_cascaded(config.level.alpha(), config.passes, 0..frames);
+    if !pre_filter {
+        add_noise(&mut oxidizer);
+    }
+    if config.mix < 1.0 {
+        buffers.mix(&mut oxidizer, config.mix);
+    }
+    normalize(&mut oxidizer, config.normalization, sample_rate);
+
+    buffers.collect(&mut oxidizer);
+    *scratch = oxidizer.into_scratch();
+    Ok(())
+}
+
+// The first setting of `config` whose stage needs to allocate, if any.
+fn allocating_setting(config: &OxidizerConfig) -> Option<&'static str> {
+    let settings = [
+        (!config.automation.is_empty(), "automation"),
+        (config.lfo.is_some(), "an LFO"),
+        (!config.envelope.is_empty(), "envelope responses"),
+        (config.sidechain.is_some(), "a sidechain"),
+        (!config.drift.is_empty(), "drift"),
+        (config.haas.is_some(), "the Haas spreader"),
+        (config.flutter.is_some(), "flutter"),
+        (config.wobble.is_some(), "wobble"),
+        (config.chorus.is_some(), "chorus"),
+        (config.transients.is_some(), "the transient shaper"),
+        (config.gate.is_some(), "a gate"),
+        (config.ground_loop.is_some(), "a ground loop"),
+        (config.expander.is_some(), "the expander"),
+        (config.compressor.is_some(), "the compressor"),
+        (config.limiter.is_some(), "the limiter"),
+        (!config.artifacts.is_empty(), "artifacts"),
+        (!config.eq.is_empty(), "EQ bands"),
+        (config.telephone.is_some(), "a telephone line"),
+        (config.lossy.is_some(), "the lossy codec"),
+        (!config.overlay.is_empty(), "overlays"),
+        (config.crossfeed.is_some(), "crossfeed"),
+        (!config.branches.is_empty(), "branches"),
+        (!config.segments.is_empty(), "segments"),
+        (config.loop_crossfade_ms.is_some(), "a loop"),
+        (
+            matches!(config.normalization, Normalization::Loudness { .. }),
+            "loudness normalization",
+        ),
+        (
+            matches!(config.normalization, Normalization::Smart { .. }),
+            "smart normalization",
+        ),
+    ];
+    settings
+        .into_iter()
+        .find_map(|(used, setting)| used.then_some(setting))
+}
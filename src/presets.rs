@@ -0,0 +1,225 @@
+//! Built-in named presets and user preset files.
+//!
+//! Each preset is a complete [`OxidizerConfig`] tuned to evoke a particular medium,
+//! so users can ask for a sound instead of dialing in coefficients. With the `serde`
+//! feature, presets can also be saved to and loaded from `.toml`/`.json` files to be
+//! shared with collaborators.
+//!
+//! ```
+//! use oxidizer::presets;
+//!
+//! let vinyl = presets::find("vinyl").unwrap();
+//! assert_eq!(vinyl.name, "vinyl");
+//! ```
+
+use crate::codec::TelephoneLine;
+use crate::config::{NoiseConfig, OxidizerConfig};
+use crate::eq::{BandKind, EqBand};
+#[cfg(feature = "serde")]
+use crate::error::Result;
+use crate::modulation::Lfo;
+use crate::processor::OxidationLevel;
+use crate::speed::{Chorus, Flutter};
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+/// A named, documented processing configuration.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Preset {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub description: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub config: OxidizerConfig,
+}
+
+#[cfg(feature = "serde")]
+impl Preset {
+    /// Loads a preset from a `.toml` or `.json` file.
+    ///
+    /// A preset without a name is named after its file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut preset: Preset = crate::config::read_file(path)?;
+
+        if preset.name.is_empty() {
+            preset.name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+        }
+        Ok(preset)
+    }
+
+    /// Writes the preset to a `.toml` or `.json` file, chosen by extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        crate::config::write_file(path.as_ref(), self)
+    }
+}
+
+// Entry in the registry of built-in presets.
+struct Builtin {
+    name: &'static str,
+    description: &'static str,
+    config: fn() -> OxidizerConfig,
+}
+
+const BUILTIN: &[Builtin] = &[
+    Builtin {
+        name: "vinyl",
+        description: "Warm record playback with a soft surface rumble",
+        config: vinyl,
+    },
+    Builtin {
+        name: "gramophone",
+        description: "Honky 78 rpm shellac through a horn, thick with crackle",
+        config: gramophone,
+    },
+    Builtin {
+        name: "cassette",
+        description: "Dulled highs and a steady bed of tape hiss",
+        config: cassette,
+    },
+    Builtin {
+        name: "am-radio",
+        description: "Narrow, dark broadcast with audible static",
+        config: am_radio,
+    },
+    Builtin {
+        name: "telephone",
+        description: "Thin, heavily filtered line with crackly static",
+        config: telephone,
+    },
+    Builtin {
+        name: "underwater",
+        description: "A dull, swaying roar, as if heard through water or a wall",
+        config: underwater,
+    },
+];
+
+/// Returns every built-in preset, in a stable order.
+pub fn builtin() -> Vec<Preset> {
+    BUILTIN.iter().map(Builtin::make).collect()
+}
+
+/// Returns the names of all built-in presets.
+pub fn names() -> impl Iterator<Item = &'static str> {
+    BUILTIN.iter().map(|preset| preset.name)
+}
+
+/// Looks up a built-in preset by name (case-insensitive).
+pub fn find(name: &str) -> Option<Preset> {
+    BUILTIN
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name))
+        .map(Builtin::make)
+}
+
+impl Builtin {
+    fn make(&self) -> Preset {
+        Preset {
+            name: self.name.to_string(),
+            description: self.description.to_string(),
+            config: (self.config)(),
+        }
+    }
+}
+
+fn vinyl() -> OxidizerConfig {
+    OxidizerConfig {
+        level: OxidationLevel::Clear,
+        passes: 1,
+        noise: NoiseConfig::Brown {
+            damping: 0.995,
+            step: 0.05,
+        },
+        // The playback curve brings the rumble up a lot
+        intensity: 0.015,
+        riaa: true,
+        ..Default::default()
+    }
+}
+
+fn gramophone() -> OxidizerConfig {
+    OxidizerConfig {
+        level: OxidationLevel::Clear,
+        passes: 1,
+        noise: NoiseConfig::Crackle { density: 0.002 },
+        intensity: 0.35,
+        flutter: Some(Flutter {
+            rate_hz: 4.0,
+            depth_percent: 0.2,
+            ..Default::default()
+        }),
+        eq: vec![
+            // A steep band-pass: nothing below the horn's cutoff or above the shellac's
+            EqBand::pass(BandKind::HighPass, 200.0),
+            EqBand::pass(BandKind::HighPass, 200.0),
+            EqBand::pass(BandKind::LowPass, 4000.0),
+            EqBand::pass(BandKind::LowPass, 4000.0),
+            // Resonances of the horn
+            EqBand::peak(650.0, 5.0, 3.0),
+            EqBand::peak(1250.0, 6.0, 4.0),
+            EqBand::peak(2600.0, 4.0, 5.0),
+        ],
+        ..Default::default()
+    }
+}
+
+fn cassette() -> OxidizerConfig {
+    OxidizerConfig {
+        level: OxidationLevel::Clear,
+        passes: 2,
+        noise: NoiseConfig::White,
+        intensity: 0.04,
+        ..Default::default()
+    }
+}
+
+fn am_radio() -> OxidizerConfig {
+    OxidizerConfig {
+        level: OxidationLevel::Deep,
+        passes: 2,
+        noise: NoiseConfig::White,
+        intensity: 0.15,
+        ..Default::default()
+    }
+}
+
+fn telephone() -> OxidizerConfig {
+    OxidizerConfig {
+        // The line keeps only the voice band, so the filter can stay open
+        level: OxidationLevel::Clear,
+        passes: 1,
+        noise: NoiseConfig::White,
+        intensity: 0.1,
+        telephone: Some(TelephoneLine::default()),
+        ..Default::default()
+    }
+}
+
+fn underwater() -> OxidizerConfig {
+    OxidizerConfig {
+        level: OxidationLevel::Muffled,
+        passes: 3,
+        noise: NoiseConfig::Brown {
+            damping: 0.99,
+            step: 0.1,
+        },
+        intensity: 0.1,
+        // The muffling ebbs and flows like a slow swell
+        lfo: Some(Lfo {
+            rate_hz: 0.15,
+            depth: 0.75,
+            ..Default::default()
+        }),
+        chorus: Some(Chorus {
+            rate_hz: 0.4,
+            mix: 0.25,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,95 @@
+use crate::math;
+use std::fmt;
+use std::sync::Arc;
+
+/// Maps an intensity setting to the amount of noise actually added to the signal.
+///
+/// The default logarithmic curve spends most of the range on subtle textures, the way
+/// ears perceive it. Frontends whose knobs should behave differently can pick another
+/// base, a straight line or their own mapping.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename_all = "lowercase")
+)]
+pub enum IntensityCurve {
+    /// The intensity is used as is.
+    Linear,
+    /// `(base^x - 1) / (base - 1)`: 0.0 and 1.0 map to themselves, values in between
+    /// are pulled down more the larger the base. Bases of 1.0 and below act as linear.
+    Log { base: f32 },
+    /// Any other mapping. Can't be stored in config files.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Arc<dyn Fn(f32) -> f32 + Send + Sync>),
+}
+
+impl Default for IntensityCurve {
+    fn default() -> Self {
+        IntensityCurve::Log { base: 10.0 }
+    }
+}
+
+impl IntensityCurve {
+    /// A curve running `intensity` through `map`.
+    pub fn custom(map: impl Fn(f32) -> f32 + Send + Sync + 'static) -> Self {
+        IntensityCurve::Custom(Arc::new(map))
+    }
+
+    /// Returns the noise amount for an intensity setting.
+    pub fn apply(&self, intensity: f32) -> f32 {
+        match self {
+            IntensityCurve::Linear => intensity,
+            IntensityCurve::Log { base } if *base > 1.0 => {
+                (math::powf(*base, intensity) - 1.0) / (base - 1.0)
+            }
+            IntensityCurve::Log { .. } => intensity,
+            IntensityCurve::Custom(map) => map(intensity),
+        }
+    }
+
+    /// Attempts to parse `linear`, `log` or `log:BASE`.
+    ///
+    /// # Errors
+    /// Returns an error string for any other input, or a base that isn't a number.
+    pub fn try_from_str(s: &str) -> Result<Self, String> {
+        let (name, base) = match s.split_once(':') {
+            Some((name, base)) => (name, Some(base)),
+            None => (s, None),
+        };
+        match (name.to_lowercase().as_str(), base) {
+            ("linear", None) => Ok(IntensityCurve::Linear),
+            ("log", None) => Ok(IntensityCurve::default()),
+            ("log", Some(base)) => match base.trim().parse::<f32>() {
+                Ok(base) if base.is_finite() && base > 1.0 => Ok(IntensityCurve::Log { base }),
+                _ => Err(format!("Invalid curve base: {} (must be above 1)", base)),
+            },
+            _ => Err(format!(
+                "Unknown intensity curve: {} (use linear, log or log:BASE)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Debug for IntensityCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntensityCurve::Linear => f.write_str("Linear"),
+            IntensityCurve::Log { base } => f.debug_struct("Log").field("base", base).finish(),
+            IntensityCurve::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+// Custom curves are only equal to clones of themselves.
+impl PartialEq for IntensityCurve {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (IntensityCurve::Linear, IntensityCurve::Linear) => true,
+            (IntensityCurve::Log { base: a }, IntensityCurve::Log { base: b }) => a == b,
+            (IntensityCurve::Custom(a), IntensityCurve::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
@@ -1,31 +1,172 @@
-#[derive(Debug, Clone, Copy)]
+use crate::error::OxidizerError;
+use crate::math;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 /// Represents the intensity of the "oxidation" (low-pass) filter effect.
 pub enum OxidationLevel {
     Clear,   // Warm and clean.
+    Dusty,   // A gentle roll-off, like a well-kept old record.
+    Worn,    // Noticeably dulled, the highs are getting tired.
     Deep,    // Deep and mellow tone. Significantly reduces high frequencies.
+    Buried,  // Dark, as if heard through a wall.
     Muffled, // Extreme Low Pass ("it's all about that bass, no treble"). Very dark and bass-heavy.
+    /// Any point on the scale running through the named levels, from 0.0 (clear) to 1.0
+    /// (muffled). See [`OxidationLevel::amount`].
+    Amount(f32),
 }
 impl OxidationLevel {
+    /// Every named level, from the lightest to the heaviest filtering.
+    pub const ALL: [OxidationLevel; 6] = [
+        OxidationLevel::Clear,
+        OxidationLevel::Dusty,
+        OxidationLevel::Worn,
+        OxidationLevel::Deep,
+        OxidationLevel::Buried,
+        OxidationLevel::Muffled,
+    ];
+
+    /// A level anywhere between the named ones: 0.0 is clear, 1.0 muffled, and the named
+    /// levels sit evenly spaced in between (deep at 0.6). The filter coefficient is
+    /// interpolated geometrically between neighbours, so equal steps sound about equally
+    /// large. Amounts outside 0.0 to 1.0 are clamped.
+    pub fn amount(amount: f32) -> Self {
+        OxidationLevel::Amount(amount.clamp(0.0, 1.0))
+    }
+
+    /// Returns the name used on the command line and in config files, or "amount" for a
+    /// level on the continuous scale.
+    pub fn name(&self) -> &'static str {
+        match self {
+            OxidationLevel::Clear => "clear",
+            OxidationLevel::Dusty => "dusty",
+            OxidationLevel::Worn => "worn",
+            OxidationLevel::Deep => "deep",
+            OxidationLevel::Buried => "buried",
+            OxidationLevel::Muffled => "muffled",
+            OxidationLevel::Amount(_) => "amount",
+        }
+    }
+
+    /// Returns a short description of how the level sounds.
+    pub fn description(&self) -> &'static str {
+        match self {
+            OxidationLevel::Clear => "Warm and clean",
+            OxidationLevel::Dusty => "Gentle roll-off, like a well-kept old record",
+            OxidationLevel::Worn => "Noticeably dulled, tired highs",
+            OxidationLevel::Deep => "Deep and mellow tone, significantly reduced highs",
+            OxidationLevel::Buried => "Dark, as if heard through a wall",
+            OxidationLevel::Muffled => "Extreme low pass, very dark and bass-heavy",
+            OxidationLevel::Amount(_) => "A point between the named levels",
+        }
+    }
+
     /// Returns the filter coefficient (alpha) for the One-Pole Low Pass algorithm.
     /// Lower values result in a lower cutoff frequency.
     pub fn alpha(&self) -> f32 {
         match self {
             OxidationLevel::Clear => 0.1,
+            OxidationLevel::Dusty => 0.06,
+            OxidationLevel::Worn => 0.035,
             OxidationLevel::Deep => 0.02,
+            OxidationLevel::Buried => 0.01,
             OxidationLevel::Muffled => 0.005,
+            OxidationLevel::Amount(amount) => {
+                let steps = (Self::ALL.len() - 1) as f32;
+                let position = amount.clamp(0.0, 1.0) * steps;
+                let below = (position.floor() as usize).min(Self::ALL.len() - 2);
+                let (from, to) = (Self::ALL[below].alpha(), Self::ALL[below + 1].alpha());
+                from * math::powf(to / from, position - below as f32)
+            }
         }
     }
 
-    /// Attempts to parse a string into an `OxidationLevel`.
+    /// Returns the -3 dB cutoff frequency, in Hz, of `passes` filter passes at `sample_rate`.
     ///
-    /// # Errors
-    /// Returns an error string if the input does not match any known level.
-    pub fn try_from_str(s: &str) -> Result<Self, String> {
-        match s.to_lowercase().as_str() {
-            "clear" => Ok(OxidationLevel::Clear),
-            "muffled" => Ok(OxidationLevel::Muffled),
-            "deep" => Ok(OxidationLevel::Deep),
-            _ => Err(format!("Unknown oxidation level: {}", s)),
+    /// Each pass is a one-pole low pass, so stacking passes pulls the cutoff down as well
+    /// as steepening the slope. Zero passes leave the whole band up to Nyquist untouched.
+    pub fn cutoff_hz(&self, sample_rate: u32, passes: u32) -> f32 {
+        let nyquist = sample_rate as f32 / 2.0;
+        if passes == 0 {
+            return nyquist;
+        }
+
+        // Solve |H(w)|^(2 * passes) = 1/2 for H(z) = a / (1 - (1 - a)z^-1)
+        let a = self.alpha() as f64;
+        let b = 1.0 - a;
+        let target = a * a * math::powf_f64(2.0, 1.0 / passes as f64);
+        let cos_w = (1.0 + b * b - target) / (2.0 * b);
+        if cos_w <= -1.0 {
+            return nyquist;
         }
+
+        (math::acos_f64(cos_w.min(1.0)) / std::f64::consts::TAU * sample_rate as f64) as f32
     }
 }
+
+/// Parses a level by [`name`](OxidationLevel::name), ignoring case, or an amount from
+/// 0.0 to 1.0.
+impl FromStr for OxidationLevel {
+    type Err = OxidizerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(level) = Self::ALL
+            .into_iter()
+            .find(|level| level.name().eq_ignore_ascii_case(s))
+        {
+            return Ok(level);
+        }
+        match s.trim().parse::<f32>() {
+            Ok(amount) if (0.0..=1.0).contains(&amount) => Ok(OxidationLevel::Amount(amount)),
+            _ => Err(OxidizerError::InvalidValue(format!(
+                "Unknown oxidation level: {} (use clear, dusty, worn, deep, buried, muffled \
+                 or an amount from 0.0 to 1.0)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Writes the [`name`](OxidationLevel::name), or the amount, so levels round-trip
+/// through [`FromStr`].
+impl fmt::Display for OxidationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OxidationLevel::Amount(amount) => write!(f, "{}", amount),
+            level => f.write_str(level.name()),
+        }
+    }
+}
+
+/// Lists every level with its description, for `--help` and shell completions.
+#[cfg(feature = "cli")]
+impl clap::ValueEnum for OxidationLevel {
+    fn value_variants<'a>() -> &'a [Self] {
+        &Self::ALL
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        Some(clap::builder::PossibleValue::new(self.name()).help(self.description()))
+    }
+}
+
+/// Returns the one-pole low-pass coefficient whose cutoff lies at `cutoff_hz`.
+///
+/// The cutoff is kept between 1 Hz and Nyquist.
+pub fn alpha_for_cutoff(cutoff_hz: f32, sample_rate: u32) -> f32 {
+    let nyquist = sample_rate as f32 / 2.0;
+    let cutoff = cutoff_hz.clamp(1.0, nyquist.max(1.0));
+    1.0 - math::exp(-std::f32::consts::TAU * cutoff / sample_rate.max(1) as f32)
+}
+
+/// Inverse of [`alpha_for_cutoff`]: the cutoff of a one-pole low pass with coefficient
+/// `alpha`.
+pub fn cutoff_for_alpha(alpha: f32, sample_rate: u32) -> f32 {
+    -math::ln(1.0 - alpha.clamp(0.0, 0.999_999)) * sample_rate as f32 / std::f32::consts::TAU
+}
@@ -1,19 +1,105 @@
+pub mod intensity;
 pub mod levels;
 pub mod noise;
+pub mod slice;
+pub mod smoothing;
 
+pub use intensity::IntensityCurve;
 pub use levels::OxidationLevel;
+pub use slice::SliceProcessor;
+pub use smoothing::Smoothed;
 
+use crate::artifacts::ArtifactEvent;
+use crate::codec::{LossyCodec, TelephoneLine};
+use crate::config::ChannelConfig;
+use crate::dynamics::{Compressor, Expander, Gate, Limiter, NoiseDucking, TransientShaper};
+use crate::eq::{EqBand, Riaa};
+use crate::error::{OxidizerError, Result};
+use crate::hum::GroundLoop;
+use crate::io::{self, TimeRange};
+use crate::math;
+use crate::modulation::EnvelopeFollower;
 use crate::processor::noise::NoiseGenerator;
+use crate::stereo::{Crossfeed, Haas};
+use std::ops::Range;
+use std::path::Path;
+
+/// Most filter passes a render can ask for. Beyond a few dozen, the signal is long gone.
+pub const MAX_PASSES: u32 = 64;
 
 /// The main engine responsible for "oxidizing" (low-pass filtering)
 /// and applying noise textures to audio buffers.
+///
+/// An engine is `Send` and `Sync` whenever its generator is, as the built-in ones are,
+/// so it can be moved to a worker thread; see [`clone_settings`](Self::clone_settings).
 pub struct Oxidizer<N: NoiseGenerator> {
     noise_generator: N,
+    intensity_curve: IntensityCurve,
     last_l: f32,
     last_r: f32,
+    // Left and right state of every stage of the multi-pass cascade
+    stages: Vec<[f32; 2]>,
+    output_guard: Option<f32>,
+    // Frames a change of the filter coefficient, intensity or mix is spread over
+    smoothing_frames: usize,
+    // Filter coefficient and noise intensity of the left and the right channel
+    alpha: [Smoothed; 2],
+    intensity: [Smoothed; 2],
+    mix: Smoothed,
+    // Per-channel adjustments, with the sample rate their cutoff shifts are worked out at
+    channels: Option<([ChannelConfig; 2], u32)>,
+    // Ducking of the noise and the envelope of the program it follows
+    ducking: Option<(NoiseDucking, EnvelopeFollower)>,
+    // Peak the buffer was last normalized to, to notice when later stages undo it
+    normalized_to: Option<f32>,
     buffer: Vec<f32>,
 }
 
+/// Working memory an [`Oxidizer`] can borrow instead of allocating its own, for renders
+/// that must not touch the allocator (see [`render_into`](crate::pipeline::render_into)).
+#[derive(Debug, Default)]
+pub struct Scratch {
+    buffer: Vec<f32>,
+    stages: Vec<[f32; 2]>,
+}
+
+impl Scratch {
+    /// Room for renders of up to `frames` stereo frames, with any number of passes.
+    pub fn new(frames: usize) -> Self {
+        Self {
+            buffer: Vec::with_capacity(frames * 2),
+            stages: Vec::with_capacity(MAX_PASSES as usize),
+        }
+    }
+
+    /// The number of stereo frames that fit without growing.
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity() / 2
+    }
+}
+
+impl<N: NoiseGenerator + Clone> Oxidizer<N> {
+    /// A fresh engine with the same generator and settings (intensity curve, output
+    /// guard, smoothing, ducking, channel adjustments) but an empty buffer and a settled
+    /// filter, e.g. for one worker per thread.
+    ///
+    /// The clone continues the generator's sequence from where it is now, so it renders
+    /// the same noise as the original; reseed it for a different texture.
+    pub fn clone_settings(&self) -> Self {
+        Self {
+            intensity_curve: self.intensity_curve.clone(),
+            output_guard: self.output_guard,
+            smoothing_frames: self.smoothing_frames,
+            channels: self.channels,
+            ducking: self.ducking.clone().map(|(ducking, mut follower)| {
+                follower.reset();
+                (ducking, follower)
+            }),
+            ..Self::new(self.noise_generator.clone())
+        }
+    }
+}
+
 impl<N: NoiseGenerator + Default> Default for Oxidizer<N> {
     fn default() -> Self {
         Self::new(N::default())
@@ -25,14 +111,108 @@ impl<N: NoiseGenerator> Oxidizer<N> {
     pub fn new(noise_generator: N) -> Self {
         Self {
             noise_generator,
+            intensity_curve: IntensityCurve::default(),
             last_l: 0.0,
             last_r: 0.0,
+            stages: Vec::new(),
+            output_guard: None,
+            smoothing_frames: 0,
+            alpha: [Smoothed::default(); 2],
+            intensity: [Smoothed::default(); 2],
+            mix: Smoothed::default(),
+            channels: None,
+            ducking: None,
+            normalized_to: None,
             buffer: Vec::new(),
         }
     }
 
+    /// Creates an engine working in the memory of `scratch`, which
+    /// [`into_scratch`](Self::into_scratch) hands back for the next render. Nothing of a
+    /// previous render carries over.
+    pub fn with_scratch(noise_generator: N, scratch: Scratch) -> Self {
+        let Scratch {
+            mut buffer,
+            mut stages,
+        } = scratch;
+        buffer.clear();
+        stages.clear();
+        Self {
+            buffer,
+            stages,
+            ..Self::new(noise_generator)
+        }
+    }
+
+    /// Gives up the engine's memory, to be reused with [`with_scratch`](Self::with_scratch).
+    pub fn into_scratch(self) -> Scratch {
+        Scratch {
+            buffer: self.buffer,
+            stages: self.stages,
+        }
+    }
+
+    /// Replaces the noise generator. The filter state and the buffer are kept, so a
+    /// stream carries on without a click. To switch between generator types, build the
+    /// engine on a `Box<dyn NoiseGenerator>` (see [`NoiseConfig::generator`](crate::NoiseConfig::generator)).
+    pub fn set_noise_generator(&mut self, noise_generator: N) -> &mut Self {
+        self.noise_generator = noise_generator;
+        self
+    }
+
+    /// Sets how the intensity of the noise texture maps to the amount of noise added.
+    pub fn set_intensity_curve(&mut self, curve: IntensityCurve) -> &mut Self {
+        self.intensity_curve = curve;
+        self
+    }
+
+    /// Spreads every change of the filter cutoff, noise intensity and mix over `frames`
+    /// stereo frames, ramping linearly from the old value, so automation and live
+    /// tweaks don't click. `0`, the default, applies new values at once.
+    ///
+    /// Ramps carry on across calls and new buffers, like the filter state. The first
+    /// value of each parameter is applied at once.
+    pub fn set_smoothing(&mut self, frames: usize) -> &mut Self {
+        self.smoothing_frames = frames;
+        self
+    }
+
+    /// Makes the noise texture duck under the program it's added to (see
+    /// [`NoiseDucking`]). `None`, the default, adds it at a steady level.
+    ///
+    /// The envelope carries on across calls and new buffers, like the filter state.
+    pub fn set_noise_ducking(
+        &mut self,
+        ducking: Option<NoiseDucking>,
+        sample_rate: u32,
+    ) -> &mut Self {
+        self.ducking = ducking.map(|ducking| (ducking, ducking.follower(sample_rate)));
+        self
+    }
+
+    /// Lets the left and the right channel depart from the filter cutoff and noise
+    /// intensity the stages are given (see [`ChannelConfig`]). `None`, the default,
+    /// treats both alike.
+    pub fn set_channels(
+        &mut self,
+        channels: Option<[ChannelConfig; 2]>,
+        sample_rate: u32,
+    ) -> &mut Self {
+        self.channels = channels.map(|channels| (channels, sample_rate));
+        self
+    }
+
+    /// Makes [`collect_samples`](Self::collect_samples) soft-limit the buffer to
+    /// `ceiling` (linear amplitude) whenever its peak lies above it, whatever order the
+    /// stages were called in. `None` turns the guard off, which is the default.
+    pub fn set_output_guard(&mut self, ceiling: Option<f32>) -> &mut Self {
+        self.output_guard = ceiling;
+        self
+    }
+
     /// Takes ownership of the input sample vector.
     /// This is a zero-copy operation that reuses the allocated memory of the input vector.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(samples = samples.len())))]
     pub fn consume(&mut self, mut samples: Vec<f32>) -> &mut Self {
         // Sanitize samples
         samples.iter_mut().for_each(|s| {
@@ -42,78 +222,469 @@ impl<N: NoiseGenerator> Oxidizer<N> {
         });
 
         self.buffer = samples;
+        self.normalized_to = None;
         self
     }
 
+    /// Copies `samples` into the buffer, replacing its contents, and sanitizes them like
+    /// [`consume`](Self::consume). The buffer's memory is reused, so this only allocates
+    /// when it's too small.
+    pub fn load(&mut self, samples: &[f32]) -> &mut Self {
+        self.buffer.clear();
+        self.buffer
+            .extend(samples.iter().map(|&s| if s.is_finite() { s } else { 0.0 }));
+        self.normalized_to = None;
+        self
+    }
+
+    /// Same as [`load`](Self::load) for planar audio: one slice per channel, as most
+    /// plugin hosts hand it out. A single channel feeds both sides of the stereo engine.
+    ///
+    /// # Errors
+    /// Returns [`OxidizerError::ChannelMismatch`] unless there are one or two channels,
+    /// and [`OxidizerError::LengthMismatch`] for two of different lengths.
+    pub fn load_planar<C: AsRef<[f32]>>(&mut self, channels: &[C]) -> Result<&mut Self> {
+        let frames = check_planar(channels)?;
+        self.buffer.clear();
+        self.buffer
+            .extend((0..frames * 2).map(|i| planar_sample(channels, i)));
+        self.normalized_to = None;
+        Ok(self)
+    }
+
+    /// Runs the stages on `samples` (interleaved stereo) where they are, instead of on
+    /// the engine's buffer, e.g. on a block a host handed out, a memory-mapped file or
+    /// an arena, without copying it into a `Vec` for [`consume`](Self::consume):
+    ///
+    /// ```rust
+    /// use oxidizer::{Oxidizer, OxidationLevel};
+    /// use oxidizer::processor::noise::BrownianNoise;
+    ///
+    /// let mut block = vec![0.0; 1024];
+    /// let mut ox = Oxidizer::new(BrownianNoise::default());
+    /// ox.process_slice(&mut block)
+    ///     .process(OxidationLevel::Deep)
+    ///     .apply_noise_texture(0.05)
+    ///     .normalize();
+    /// ```
+    ///
+    /// The samples are sanitized like in `consume`. The engine's own buffer is left
+    /// alone, and its filter and noise state carry on into the next slice or buffer.
+    pub fn process_slice<'a>(&'a mut self, samples: &'a mut [f32]) -> SliceProcessor<'a, N> {
+        samples.iter_mut().for_each(|s| {
+            if !s.is_finite() {
+                *s = 0.0;
+            }
+        });
+        self.normalized_to = None;
+        SliceProcessor {
+            engine: self,
+            samples,
+        }
+    }
+
+    // Runs a stage on the engine's own buffer, through the same code as borrowed slices.
+    fn on_buffer(&mut self, stage: impl FnOnce(&mut SliceProcessor<'_, N>)) -> &mut Self {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        stage(&mut SliceProcessor {
+            engine: self,
+            samples: &mut buffer,
+        });
+        self.buffer = buffer;
+        self
+    }
+
+    /// Decodes only the given window of an audio file and takes it as the buffer.
+    ///
+    /// See [`io::load_audio_range`] for how the window is located.
+    pub fn consume_range(&mut self, path: &Path, range: TimeRange) -> Result<&mut Self> {
+        let samples = io::load_audio_range(path, range)?;
+        Ok(self.consume(samples))
+    }
+
     /// Processes the audio buffer using a One-Pole Low Pass Filter.
     /// The `alpha` value from the `OxidationLevel` determines the filter's cutoff frequency.
     pub fn process(&mut self, level: OxidationLevel) -> &mut Self {
-        let alpha = level.alpha();
+        self.on_buffer(|slice| {
+            slice.process(level);
+        })
+    }
+
+    /// Runs a single filter pass over a range of stereo frames.
+    ///
+    /// Filter state carries over between calls, so processing consecutive ranges
+    /// is equivalent to processing the whole buffer at once.
+    pub fn process_frames(&mut self, level: OxidationLevel, frames: Range<usize>) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.process_frames(level, frames);
+        })
+    }
+
+    /// Same as [`process_frames`](Self::process_frames) with an explicit filter
+    /// coefficient, e.g. one derived from an automated cutoff with [`levels::alpha_for_cutoff`].
+    pub fn filter_frames(&mut self, alpha: f32, frames: Range<usize>) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.filter_frames(alpha, frames);
+        })
+    }
 
-        for i in (0..self.buffer.len()).step_by(2) {
-            self.last_l = self.last_l + alpha * (self.buffer[i] - self.last_l);
-            self.buffer[i] = self.last_l;
+    /// Runs `passes` cascaded filter passes over a range of stereo frames in a single
+    /// traversal, each sample going through every stage before the next one is read.
+    ///
+    /// Every stage keeps its own state, carried over between calls like in
+    /// [`filter_frames`](Self::filter_frames). At most [`MAX_PASSES`] stages are run.
+    pub fn filter_frames_cascaded(
+        &mut self,
+        alpha: f32,
+        passes: u32,
+        frames: Range<usize>,
+    ) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.filter_frames_cascaded(alpha, passes, frames);
+        })
+    }
 
-            self.last_r = self.last_r + alpha * (self.buffer[i + 1] - self.last_r);
-            self.buffer[i + 1] = self.last_r;
+    // Heads the coefficient of each channel for `alpha`, shifted by the channel adjustments.
+    fn set_alpha(&mut self, alpha: f32) {
+        let alphas = match self.channels {
+            Some((channels, sample_rate)) => channels.map(|channel| {
+                let cutoff = levels::cutoff_for_alpha(alpha, sample_rate);
+                levels::alpha_for_cutoff(cutoff * math::exp2(channel.cutoff_octaves), sample_rate)
+            }),
+            None => [alpha; 2],
+        };
+        for (smoothed, alpha) in self.alpha.iter_mut().zip(alphas) {
+            smoothed.set_target(alpha, self.smoothing_frames);
         }
+    }
 
-        self
+    /// The buffer in its current state, e.g. to inspect an intermediate stage.
+    pub fn samples(&self) -> &[f32] {
+        &self.buffer
+    }
+
+    /// Returns the number of stereo frames currently held by the engine.
+    pub fn frames(&self) -> usize {
+        self.buffer.len() / 2
     }
 
     /// Normalizes the audio buffer so the highest peak reaches 0.95 (approx. -0.5 dBFS).
     /// This prevents digital clipping after noise and filter processing.
     pub fn normalize(&mut self) -> &mut Self {
-        let max_peak = self.buffer.iter().map(|s| s.abs()).fold(0.0, f32::max);
+        self.on_buffer(|slice| {
+            slice.normalize();
+        })
+    }
 
-        if max_peak > 0.0 {
-            let scale_factor = 0.95 / max_peak;
-            for sample in &mut self.buffer {
-                *sample *= scale_factor;
-            }
-        }
+    /// Normalizes the audio buffer so the highest peak reaches `target` (linear amplitude).
+    pub fn normalize_to(&mut self, target: f32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.normalize_to(target);
+        })
+    }
 
-        self
+    /// Scales the buffer to an integrated loudness of `target_lufs` (see
+    /// [`analysis::loudness`]), reduced as needed to keep the true peak at or below
+    /// `true_peak_db` dBTP. Silent buffers are left untouched.
+    pub fn normalize_loudness(
+        &mut self,
+        sample_rate: u32,
+        target_lufs: f32,
+        true_peak_db: f32,
+    ) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.normalize_loudness(sample_rate, target_lufs, true_peak_db);
+        })
     }
 
-    // Extracts the processes samples from the engine, leaving the internal buffer empty.
+    /// Extracts the processed samples from the engine, leaving the internal buffer empty.
+    ///
+    /// With an output guard set (see [`set_output_guard`](Self::set_output_guard)), the
+    /// samples are soft-limited first. When tracing is enabled, a warning tells if stages
+    /// run after [`normalize_to`](Self::normalize_to) pushed the peak back above its target.
     pub fn collect_samples(&mut self) -> Vec<f32> {
+        self.guard_output();
         std::mem::take(&mut self.buffer)
     }
 
-    /// Applies a noise texture to the audio signal. Intensity is mapped logarithmically.
+    /// Same as [`collect_samples`](Self::collect_samples), but copies the samples into
+    /// `output` and keeps the buffer's memory for the next [`load`](Self::load). Samples
+    /// that don't fit are dropped; the rest of a longer `output` is left untouched.
+    pub fn collect_into(&mut self, output: &mut [f32]) {
+        self.guard_output();
+        let len = self.buffer.len().min(output.len());
+        output[..len].copy_from_slice(&self.buffer[..len]);
+        self.buffer.clear();
+    }
+
+    /// Same as [`collect_into`](Self::collect_into) for planar audio. A single channel
+    /// gets the average of both sides.
+    pub fn collect_planar<C: AsMut<[f32]>>(&mut self, channels: &mut [C]) {
+        self.guard_output();
+        match channels {
+            [mono] => {
+                for (out, frame) in mono.as_mut().iter_mut().zip(self.buffer.chunks_exact(2)) {
+                    *out = (frame[0] + frame[1]) / 2.0;
+                }
+            }
+            [left, right] => {
+                let sides = left.as_mut().iter_mut().zip(right.as_mut());
+                for ((left, right), frame) in sides.zip(self.buffer.chunks_exact(2)) {
+                    (*left, *right) = (frame[0], frame[1]);
+                }
+            }
+            _ => {}
+        }
+        self.buffer.clear();
+    }
+
+    // Applies the output guard and checks the normalization on the way out.
+    fn guard_output(&mut self) {
+        self.on_buffer(|slice| slice.guard_output());
+    }
+
+    /// Applies a noise texture to the audio signal. Intensity is mapped through the
+    /// [`IntensityCurve`], logarithmic by default.
+    ///
+    /// Intensities outside 0.0 to 1.0 are clamped into it (with a warning when tracing
+    /// is enabled).
     /// The result is processed though a `tanh()` function for soft-clipping and saturation.
     pub fn apply_noise_texture(&mut self, intensity: f32) -> &mut Self {
-        let perceived_intensity = (10.0f32.powf(intensity) - 1.0) / 9.0;
+        self.on_buffer(|slice| {
+            slice.apply_noise_texture(intensity);
+        })
+    }
 
-        for i in (0..self.buffer.len()).step_by(2) {
-            let noise_l = self.noise_generator.next_sample();
-            let noise_r = self.noise_generator.next_sample();
+    /// Applies the noise texture to a range of stereo frames only.
+    pub fn apply_noise_texture_frames(
+        &mut self,
+        intensity: f32,
+        frames: Range<usize>,
+    ) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.apply_noise_texture_frames(intensity, frames);
+        })
+    }
 
-            self.buffer[i] = (self.buffer[i] + noise_l * perceived_intensity).tanh();
-            if i + 1 < self.buffer.len() {
-                self.buffer[i + 1] = (self.buffer[i + 1] + noise_r * perceived_intensity).tanh();
-            }
-        }
+    /// Adds the noise of the texture to a range of stereo frames, without the `tanh()`
+    /// saturation. Followed by [`saturate_frames`](Self::saturate_frames), it's the same
+    /// as [`apply_noise_texture_frames`](Self::apply_noise_texture_frames).
+    pub fn add_noise_frames(&mut self, intensity: f32, frames: Range<usize>) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.add_noise_frames(intensity, frames);
+        })
+    }
 
-        self
+    /// Soft-clips a range of stereo frames with `tanh()`.
+    pub fn saturate_frames(&mut self, frames: Range<usize>) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.saturate_frames(frames);
+        })
+    }
+
+    /// Blends the processed buffer with the original signal: `0.0` restores `dry`
+    /// entirely, `1.0` keeps only the processed signal.
+    ///
+    /// `dry` must be the unprocessed input; any part of the buffer past its end is left
+    /// untouched.
+    pub fn mix_with(&mut self, dry: &[f32], mix: f32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.mix_with(dry, mix);
+        })
+    }
+
+    /// Applies [`mix_with`](Self::mix_with) to a range of stereo frames only.
+    pub fn mix_frames(&mut self, dry: &[f32], mix: f32, frames: Range<usize>) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.mix_frames(dry, mix, frames);
+        })
+    }
+
+    /// Same as [`mix_with`](Self::mix_with) with the dry signal in planar channels, as
+    /// given to [`load_planar`](Self::load_planar).
+    pub fn mix_planar<C: AsRef<[f32]>>(&mut self, dry: &[C], mix: f32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.mix_planar(dry, mix);
+        })
+    }
+
+    /// Adds `bed` (interleaved stereo at the buffer's rate) on top of the buffer, scaled
+    /// by `gain`.
+    ///
+    /// A bed shorter than the buffer is repeated if `loop_if_shorter` is set and stops
+    /// otherwise; a longer one is cut off.
+    pub fn overlay(&mut self, bed: &[f32], gain: f32, loop_if_shorter: bool) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.overlay(bed, gain, loop_if_shorter);
+        })
+    }
+
+    /// Runs the buffer through a compressor. `sample_rate` sets the attack and release
+    /// speeds.
+    pub fn compress(&mut self, compressor: &Compressor, sample_rate: u32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.compress(compressor, sample_rate);
+        })
+    }
+
+    /// Runs the buffer through a noise gate, keyed by `key` (e.g. the unprocessed input)
+    /// or by the buffer itself.
+    pub fn gate(&mut self, gate: &Gate, key: Option<&[f32]>, sample_rate: u32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.gate(gate, key, sample_rate);
+        })
+    }
+
+    /// Runs the buffer through a downward expander.
+    pub fn expand(&mut self, expander: &Expander, sample_rate: u32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.expand(expander, sample_rate);
+        })
+    }
+
+    /// Boosts or cuts the attacks and sustains in the buffer.
+    pub fn shape_transients(&mut self, shaper: &TransientShaper, sample_rate: u32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.shape_transients(shaper, sample_rate);
+        })
+    }
+
+    /// Renders pops, clicks and other artifacts into the buffer, in the order given.
+    pub fn add_artifacts(&mut self, events: &[ArtifactEvent], sample_rate: u32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.add_artifacts(events, sample_rate);
+        })
+    }
+
+    /// Bleeds each channel into the other for comfortable headphone listening.
+    pub fn crossfeed(&mut self, crossfeed: &Crossfeed, sample_rate: u32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.crossfeed(crossfeed, sample_rate);
+        })
+    }
+
+    /// Adds mains hum that follows the level of the buffer, like a ground loop.
+    pub fn ground_loop(&mut self, ground_loop: &GroundLoop, sample_rate: u32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.ground_loop(ground_loop, sample_rate);
+        })
+    }
+
+    /// Delays and dulls one channel to spread a mono source across the stereo field.
+    pub fn haas(&mut self, haas: &Haas, sample_rate: u32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.haas(haas, sample_rate);
+        })
+    }
+
+    /// Runs the buffer through one filter band.
+    pub fn equalize(&mut self, band: &EqBand, sample_rate: u32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.equalize(band, sample_rate);
+        })
+    }
+
+    /// Sends the buffer down a telephone line and back.
+    pub fn telephone(&mut self, line: &TelephoneLine, sample_rate: u32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.telephone(line, sample_rate);
+        })
+    }
+
+    /// Mangles the spectrum of the buffer like a low-bitrate lossy codec.
+    pub fn lossy_codec(&mut self, codec: &LossyCodec, sample_rate: u32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.lossy_codec(codec, sample_rate);
+        })
+    }
+
+    /// Runs the buffer through an RIAA pre- or de-emphasis curve.
+    pub fn riaa(&mut self, curve: Riaa, sample_rate: u32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.riaa(curve, sample_rate);
+        })
+    }
+
+    /// Runs the buffer through a peak limiter.
+    pub fn limit(&mut self, limiter: &Limiter, sample_rate: u32) -> &mut Self {
+        self.on_buffer(|slice| {
+            slice.limit(limiter, sample_rate);
+        })
     }
 
     /// Executes the filtration process multiple times.
     /// Each pass further muffles the high frequencies and deepens the "oxidation" effect.
+    ///
+    /// The passes are fused into one traversal of the buffer (see
+    /// [`filter_frames_cascaded`](Self::filter_frames_cascaded)), so high pass counts
+    /// cost little more than memory bandwidth for a single pass. At most [`MAX_PASSES`]
+    /// passes are run (with a warning when tracing is enabled).
     pub fn process_multiple(&mut self, level: OxidationLevel, passes: u32) -> &mut Self {
-        for _ in 0..passes {
-            self.process(level);
+        self.on_buffer(|slice| {
+            slice.process_multiple(level, passes);
+        })
+    }
+}
+
+// Share of the output guard's ceiling below which samples pass untouched.
+const GUARD_KNEE: f32 = 0.8;
+
+// Bends samples above the knee smoothly towards `ceiling`, which they never reach.
+fn soft_limit(samples: &mut [f32], ceiling: f32) {
+    let knee = ceiling * GUARD_KNEE;
+    let room = ceiling - knee;
+    for sample in samples {
+        let level = sample.abs();
+        if level > knee {
+            let limited = knee + room * math::tanh((level - knee) / room);
+            *sample = limited.copysign(*sample);
         }
+    }
+}
 
-        self
+// Returns the frame count of planar channels the engine can take.
+fn check_planar<C: AsRef<[f32]>>(channels: &[C]) -> Result<usize> {
+    match channels {
+        [mono] => Ok(mono.as_ref().len()),
+        [left, right] if left.as_ref().len() == right.as_ref().len() => Ok(left.as_ref().len()),
+        [left, right] => Err(OxidizerError::LengthMismatch {
+            expected: left.as_ref().len(),
+            found: right.as_ref().len(),
+        }),
+        _ => Err(OxidizerError::ChannelMismatch {
+            expected: 2,
+            found: channels.len(),
+        }),
     }
 }
 
+// The sample at interleaved position `i` of planar channels, sanitized like `consume`.
+// Mono fills both sides.
+fn planar_sample<C: AsRef<[f32]>>(channels: &[C], i: usize) -> f32 {
+    let channel = (i % 2).min(channels.len().saturating_sub(1));
+    let sample = channels
+        .get(channel)
+        .and_then(|c| c.as_ref().get(i / 2).copied())
+        .unwrap_or(0.0);
+    if sample.is_finite() { sample } else { 0.0 }
+}
+
+// Keeps the intensity within 0.0 to 1.0, where the noise texture is meaningful.
+fn clamp_intensity(intensity: f32) -> f32 {
+    if (0.0..=1.0).contains(&intensity) {
+        return intensity;
+    }
+    #[cfg(feature = "tracing")]
+    tracing::warn!(intensity, "intensity outside 0.0 to 1.0, clamping");
+    // NaN counts as no noise at all
+    if intensity > 1.0 { 1.0 } else { 0.0 }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::processor::noise::WhiteNoise;
+    use crate::analysis;
+    use crate::processor::noise::{BrownianNoise, WhiteNoise};
 
     #[test]
     fn test_consume_and_collect() {
@@ -136,6 +707,116 @@ mod tests {
         assert!((max_peak - 0.95).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_loudness_normalization() {
+        let rate = 48000;
+        let sine: Vec<f32> = (0..rate * 3)
+            .flat_map(|i| {
+                let s = 0.1 * (i as f32 * 0.05).sin();
+                [s, s]
+            })
+            .collect();
+
+        let mut oxidizer = Oxidizer::new(WhiteNoise::default());
+        let output = oxidizer
+            .consume(sine.clone())
+            .normalize_loudness(rate as u32, -16.0, -1.0)
+            .collect_samples();
+        assert!((analysis::loudness(&output, rate as u32) + 16.0).abs() < 0.1);
+
+        // A target too loud for the peak ceiling is held back by it
+        let output = oxidizer
+            .consume(sine)
+            .normalize_loudness(rate as u32, 0.0, -1.0)
+            .collect_samples();
+        let true_peak = analysis::to_dbfs(analysis::true_peak(&output));
+        assert!((true_peak + 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_dry_wet_mix() {
+        let dry = vec![0.8, -0.4];
+        let mut oxidizer = Oxidizer::new(WhiteNoise::default());
+
+        let output = oxidizer
+            .consume(vec![0.0, 0.0])
+            .mix_with(&dry, 0.25)
+            .collect_samples();
+        assert_eq!(output, vec![0.6, -0.3]);
+
+        let output = oxidizer
+            .consume(vec![0.0, 0.0])
+            .mix_with(&dry, 0.0)
+            .collect_samples();
+        assert_eq!(output, dry);
+    }
+
+    #[test]
+    fn test_smoothing_ramps_parameter_changes() {
+        let dry = vec![1.0; 12];
+        let mut oxidizer = Oxidizer::new(WhiteNoise::default());
+        oxidizer.set_smoothing(4).consume(vec![0.0; 12]);
+
+        // The first value applies at once, the next one is reached over four frames
+        oxidizer
+            .mix_frames(&dry, 1.0, 0..2)
+            .mix_frames(&dry, 0.0, 2..6);
+        assert_eq!(
+            oxidizer.collect_samples(),
+            vec![
+                0.0, 0.0, 0.0, 0.0, 0.25, 0.25, 0.5, 0.5, 0.75, 0.75, 1.0, 1.0
+            ]
+        );
+
+        // Without smoothing, a new filter coefficient takes effect on the next frame
+        let mut jumpy = Oxidizer::new(WhiteNoise::default());
+        jumpy
+            .consume(vec![1.0; 8])
+            .filter_frames(0.0, 0..2)
+            .filter_frames(1.0, 2..4);
+        assert_eq!(jumpy.samples()[4..], [1.0; 4]);
+        let mut smooth = Oxidizer::new(WhiteNoise::default());
+        smooth
+            .set_smoothing(4)
+            .consume(vec![1.0; 8])
+            .filter_frames(0.0, 0..2)
+            .filter_frames(1.0, 2..4);
+        assert_eq!(smooth.samples()[4..6], [0.25; 2]);
+    }
+
+    #[test]
+    fn test_planar_mix_ramps_like_interleaved() {
+        let dry = vec![1.0; 12];
+        let planar = [vec![1.0; 6], vec![1.0; 6]];
+        let mut interleaved = Oxidizer::new(WhiteNoise::default());
+        interleaved.set_smoothing(4).consume(vec![0.0; 12]);
+        interleaved.mix_with(&dry, 1.0).mix_with(&dry, 0.0);
+        let mut split = Oxidizer::new(WhiteNoise::default());
+        split.set_smoothing(4).consume(vec![0.0; 12]);
+        split.mix_planar(&planar, 1.0).mix_planar(&planar, 0.0);
+
+        assert_eq!(split.samples(), interleaved.samples());
+        assert_eq!(split.samples()[..4], [0.25, 0.25, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_overlay_loops_or_stops() {
+        let bed = vec![0.1, 0.2, 0.3, 0.4];
+        let mut oxidizer = Oxidizer::new(WhiteNoise::default());
+
+        let output = oxidizer
+            .consume(vec![0.0; 6])
+            .overlay(&bed, 2.0, true)
+            .collect_samples();
+        assert_eq!(output, vec![0.2, 0.4, 0.6, 0.8, 0.2, 0.4]);
+
+        let output = oxidizer
+            .consume(vec![0.0; 6])
+            .overlay(&bed, 1.0, false)
+            .collect_samples();
+        assert_eq!(output, vec![0.1, 0.2, 0.3, 0.4, 0.0, 0.0]);
+    }
+
     #[test]
     fn test_filter_smoothing() {
         let mut oxidizer = Oxidizer::new(WhiteNoise::default());
@@ -150,6 +831,289 @@ mod tests {
         assert!(output[1] < 0.1);
     }
 
+    #[test]
+    fn test_frame_ranges_match_full_pass() {
+        let input: Vec<f32> = (0..200).map(|i| (i as f32 * 0.37).sin()).collect();
+
+        let mut whole = Oxidizer::new(WhiteNoise::default());
+        let expected = whole
+            .consume(input.clone())
+            .process(OxidationLevel::Deep)
+            .collect_samples();
+
+        // Processing in uneven consecutive blocks must carry the filter state across
+        let mut blocks = Oxidizer::new(WhiteNoise::default());
+        blocks.consume(input);
+        blocks.process_frames(OxidationLevel::Deep, 0..7);
+        blocks.process_frames(OxidationLevel::Deep, 7..64);
+        blocks.process_frames(OxidationLevel::Deep, 64..100);
+        assert_eq!(expected, blocks.collect_samples());
+    }
+
+    #[test]
+    fn test_slices_match_the_owned_buffer() {
+        let input: Vec<f32> = (0..400).map(|i| (i as f32 * 0.37).sin() * 1.5).collect();
+        let seeded = || {
+            let mut white = WhiteNoise::default();
+            white.reseed(11);
+            white
+        };
+        let expected = Oxidizer::new(seeded())
+            .set_output_guard(Some(0.9))
+            .consume(input.clone())
+            .process_multiple(OxidationLevel::Deep, 3)
+            .apply_noise_texture(0.4)
+            .collect_samples();
+
+        // Two borrowed halves, with the state carried from one into the other
+        let mut samples = input;
+        let mut oxidizer = Oxidizer::new(seeded());
+        oxidizer.set_output_guard(Some(0.9));
+        let (first, second) = samples.split_at_mut(200);
+        for half in [first, second] {
+            let mut slice = oxidizer.process_slice(half);
+            slice
+                .process_multiple(OxidationLevel::Deep, 3)
+                .apply_noise_texture(0.4);
+            slice.finish();
+        }
+        assert_eq!(samples, expected);
+        assert!(oxidizer.samples().is_empty());
+
+        // Sanitized on the way in, like `consume`
+        let mut broken = [f32::NAN, 0.5, f32::INFINITY, -0.5];
+        oxidizer.process_slice(&mut broken).normalize_to(0.25);
+        assert_eq!(broken, [0.0, 0.25, 0.0, -0.25]);
+    }
+
+    #[test]
+    fn test_swapping_the_generator_keeps_the_filter_state() {
+        let input: Vec<f32> = (0..200).map(|i| (i as f32 * 0.37).sin()).collect();
+        let expected = Oxidizer::new(WhiteNoise::default())
+            .consume(input.clone())
+            .process(OxidationLevel::Deep)
+            .collect_samples();
+
+        let seeded = || {
+            let mut white = WhiteNoise::default();
+            white.reseed(7);
+            white
+        };
+        let mut oxidizer: Oxidizer<Box<dyn NoiseGenerator>> =
+            Oxidizer::new(Box::new(BrownianNoise::default()));
+        oxidizer
+            .consume(input)
+            .process_frames(OxidationLevel::Deep, 0..50)
+            .set_noise_generator(Box::new(seeded()))
+            .process_frames(OxidationLevel::Deep, 50..100);
+        assert_eq!(expected, oxidizer.collect_samples());
+
+        // The noise now comes from the new generator
+        let noise = oxidizer
+            .consume(vec![0.0; 8])
+            .add_noise_frames(1.0, 0..4)
+            .collect_samples();
+        assert_eq!(noise, crate::processor::noise::render(&mut seeded(), 4));
+    }
+
+    #[test]
+    fn test_clone_settings_runs_on_other_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Oxidizer<WhiteNoise>>();
+        assert_send_sync::<Oxidizer<BrownianNoise>>();
+
+        let seeded = || {
+            let mut brown = BrownianNoise::default();
+            brown.reseed(3);
+            brown
+        };
+        let mut template = Oxidizer::new(seeded());
+        template
+            .set_intensity_curve(IntensityCurve::Linear)
+            .consume(vec![0.9; 8])
+            .process(OxidationLevel::Deep);
+
+        // Workers start from the template's settings, not its buffer or filter state
+        let render = |mut worker: Oxidizer<BrownianNoise>| {
+            std::thread::spawn(move || {
+                worker
+                    .consume(vec![0.5; 64])
+                    .process(OxidationLevel::Deep)
+                    .apply_noise_texture(0.3)
+                    .collect_samples()
+            })
+        };
+        let first = render(template.clone_settings());
+        let second = render(template.clone_settings());
+        assert_eq!(first.join().unwrap(), second.join().unwrap());
+
+        let fresh = Oxidizer::new(seeded())
+            .set_intensity_curve(IntensityCurve::Linear)
+            .consume(vec![0.5; 64])
+            .process(OxidationLevel::Deep)
+            .apply_noise_texture(0.3)
+            .collect_samples();
+        assert_eq!(render(template.clone_settings()).join().unwrap(), fresh);
+    }
+
+    #[test]
+    fn test_channels_wear_independently() {
+        // A 2 kHz tone in both channels, dulled more on the left, with noise only there
+        let tone: Vec<f32> = (0..44100)
+            .flat_map(|i| {
+                let s = 0.5 * (std::f32::consts::TAU * 2000.0 * i as f32 / 44100.0).sin();
+                [s, s]
+            })
+            .collect();
+        let channels = [
+            ChannelConfig {
+                cutoff_octaves: -2.0,
+                noise_db: 0.0,
+            },
+            ChannelConfig {
+                cutoff_octaves: 0.0,
+                noise_db: -96.0,
+            },
+        ];
+        let mut oxidizer = Oxidizer::new(WhiteNoise::default());
+        let output = oxidizer
+            .set_channels(Some(channels), 44100)
+            .consume(tone)
+            .filter_frames_cascaded(OxidationLevel::Worn.alpha(), 2, 0..44100)
+            .collect_samples();
+        let (left, right) = (channel_of(&output, 0), channel_of(&output, 1));
+        assert!(analysis::rms(&left) < 0.5 * analysis::rms(&right));
+
+        let output = oxidizer
+            .consume(vec![0.0; 2000])
+            .apply_noise_texture(0.5)
+            .collect_samples();
+        let (left, right) = (channel_of(&output, 0), channel_of(&output, 1));
+        assert!(analysis::rms(&left) > 0.01);
+        assert!(analysis::rms(&right) < 1e-4);
+
+        // Without adjustments both channels match
+        let mut even = Oxidizer::new(WhiteNoise::default());
+        let output = even
+            .consume(vec![0.5; 200])
+            .process(OxidationLevel::Worn)
+            .collect_samples();
+        assert_eq!(channel_of(&output, 0), channel_of(&output, 1));
+    }
+
+    fn channel_of(samples: &[f32], channel: usize) -> Vec<f32> {
+        samples.iter().skip(channel).step_by(2).copied().collect()
+    }
+
+    #[test]
+    fn test_level_names_round_trip() {
+        for level in OxidationLevel::ALL {
+            assert_eq!(level.to_string().parse::<OxidationLevel>().unwrap(), level);
+        }
+        assert_eq!(
+            "MUFFLED".parse::<OxidationLevel>().unwrap(),
+            OxidationLevel::Muffled
+        );
+        assert!("rusty".parse::<OxidationLevel>().is_err());
+
+        let amount = OxidationLevel::amount(0.45);
+        assert_eq!(
+            amount.to_string().parse::<OxidationLevel>().unwrap(),
+            amount
+        );
+        assert!("1.5".parse::<OxidationLevel>().is_err());
+    }
+
+    #[test]
+    fn test_amount_runs_through_the_named_levels() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+        assert!(close(
+            OxidationLevel::amount(0.0).alpha(),
+            OxidationLevel::Clear.alpha()
+        ));
+        assert!(close(
+            OxidationLevel::amount(0.6).alpha(),
+            OxidationLevel::Deep.alpha()
+        ));
+        assert!(close(
+            OxidationLevel::amount(1.0).alpha(),
+            OxidationLevel::Muffled.alpha()
+        ));
+        assert!(close(
+            OxidationLevel::amount(7.0).alpha(),
+            OxidationLevel::Muffled.alpha()
+        ));
+
+        // Darker all the way
+        let alphas: Vec<f32> = (0..=100)
+            .map(|i| OxidationLevel::amount(i as f32 / 100.0).alpha())
+            .collect();
+        assert!(alphas.windows(2).all(|w| w[1] < w[0]));
+        let named: Vec<f32> = OxidationLevel::ALL.iter().map(|l| l.alpha()).collect();
+        assert!(named.windows(2).all(|w| w[1] < w[0]));
+    }
+
+    #[test]
+    fn test_cutoff_ordering() {
+        let cutoff = |level: OxidationLevel, passes| level.cutoff_hz(44100, passes);
+
+        assert!(cutoff(OxidationLevel::Clear, 1) > cutoff(OxidationLevel::Deep, 1));
+        assert!(cutoff(OxidationLevel::Deep, 1) > cutoff(OxidationLevel::Muffled, 1));
+        // Stacked passes pull the cutoff down
+        assert!(cutoff(OxidationLevel::Deep, 3) < cutoff(OxidationLevel::Deep, 1));
+        assert_eq!(cutoff(OxidationLevel::Deep, 0), 22050.0);
+    }
+
+    #[test]
+    fn test_output_guard_catches_late_stages() {
+        let input: Vec<f32> = (0..2000).map(|i| (i as f32 * 0.01).sin()).collect();
+
+        let mut ox = Oxidizer::new(WhiteNoise::default());
+        let res = ox
+            .set_output_guard(Some(0.5))
+            .consume(input.clone())
+            .normalize()
+            .apply_noise_texture(0.5)
+            .collect_samples();
+        let peak = res.iter().map(|s| s.abs()).fold(0.0, f32::max);
+        assert!(peak <= 0.5, "peak {} above the ceiling", peak);
+
+        // Quiet buffers come out untouched
+        let quiet: Vec<f32> = input.iter().map(|s| s * 0.3).collect();
+        let res = ox.consume(quiet.clone()).collect_samples();
+        assert_eq!(res, quiet);
+    }
+
+    #[test]
+    fn test_cascade_matches_separate_passes() {
+        let input: Vec<f32> = (0..2000)
+            .map(|i| ((i * 7919) % 200) as f32 / 100.0 - 1.0)
+            .collect();
+        let alpha = OxidationLevel::Deep.alpha();
+
+        // Each pass as its own filter, starting from silence, over the whole buffer
+        let mut expected = input.clone();
+        for _ in 0..5 {
+            let mut last = [0.0f32; 2];
+            for frame in expected.chunks_exact_mut(2) {
+                for (sample, last) in frame.iter_mut().zip(&mut last) {
+                    *last += alpha * (*sample - *last);
+                    *sample = *last;
+                }
+            }
+        }
+
+        // Fused, and split in two ranges to check the state carries over
+        let mut ox = Oxidizer::new(WhiteNoise::default());
+        let fused = ox
+            .consume(input)
+            .filter_frames_cascaded(alpha, 5, 0..300)
+            .filter_frames_cascaded(alpha, 5, 300..1000)
+            .collect_samples();
+
+        assert_eq!(fused, expected);
+    }
+
     #[test]
     fn test_multiple_passes_attenuation() {
         // Generate clean zig-zag signal (square wave of Nyquist frequency)
@@ -203,6 +1167,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_intensity_curves() {
+        let log = IntensityCurve::default();
+        assert_eq!(log.apply(0.0), 0.0);
+        assert!((log.apply(1.0) - 1.0).abs() < 1e-6);
+        assert!((log.apply(0.5) - (10f32.sqrt() - 1.0) / 9.0).abs() < 1e-6);
+        assert_eq!(IntensityCurve::Linear.apply(0.3), 0.3);
+        assert_eq!(IntensityCurve::custom(|x| x * x).apply(0.5), 0.25);
+
+        // A linear curve adds more noise at the same low setting
+        let render = |curve: IntensityCurve| {
+            let mut oxidizer = Oxidizer::new(WhiteNoise::default());
+            oxidizer
+                .set_intensity_curve(curve)
+                .consume(vec![0.0; 2000])
+                .apply_noise_texture(0.1)
+                .collect_samples()
+        };
+        let rms = |s: Vec<f32>| analysis::rms(&s);
+        assert!(rms(render(IntensityCurve::Linear)) > 1.5 * rms(render(log)));
+    }
+
     #[test]
     fn test_stereo_noise_decorrelation() {
         let mut oxidizer = Oxidizer::new(WhiteNoise::default());
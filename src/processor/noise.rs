@@ -1,23 +1,54 @@
-use rand::Rng;
-use rand::rngs::ThreadRng;
+use crate::analysis::rms;
+use crate::error::{Result, check_range};
+use crate::{fft, math};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 /// Defines the behaviour for audio noise generators.
 pub trait NoiseGenerator {
     /// Generates the next audio sample, typically in the range [-1.0, 1.0].
     fn next_sample(&mut self) -> f32;
+
+    /// Restarts the generator from a fixed seed, so it produces the same sequence on every run.
+    ///
+    /// Generators without any randomness can keep the default, which does nothing.
+    fn reseed(&mut self, _seed: u64) {}
+}
+
+// Lets an engine hold a `Box<dyn NoiseGenerator>` and switch noise types at runtime.
+impl<N: NoiseGenerator + ?Sized> NoiseGenerator for Box<N> {
+    fn next_sample(&mut self) -> f32 {
+        (**self).next_sample()
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        (**self).reseed(seed)
+    }
+}
+
+// The generator of a noise source nobody seeded: drawn from the OS, except in
+// deterministic builds, where every render starts from the same state.
+fn unseeded_rng() -> StdRng {
+    match cfg!(feature = "deterministic") {
+        true => StdRng::seed_from_u64(0),
+        false => StdRng::from_os_rng(),
+    }
 }
 
 /// Simple White Noise generator.
 ///
 /// Produces a signal with equal intensity at all frequencies,
 /// sounding like a radio static or falling rain.
+#[derive(Debug, Clone)]
 pub struct WhiteNoise {
-    rng: ThreadRng,
+    rng: StdRng,
 }
 
 impl Default for WhiteNoise {
     fn default() -> Self {
-        Self { rng: rand::rng() }
+        Self {
+            rng: unseeded_rng(),
+        }
     }
 }
 
@@ -25,6 +56,10 @@ impl NoiseGenerator for WhiteNoise {
     fn next_sample(&mut self) -> f32 {
         self.rng.random_range(-1.0..1.0)
     }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
 }
 
 /// Brownian Noise generator (aka Brown Noise or Red Noise).
@@ -32,31 +67,47 @@ impl NoiseGenerator for WhiteNoise {
 /// Uses a "Leaky Random Walk" algorithm. It has much higher energy at lower
 /// frequencies, resulting in a much deeper and warmer sound compared to the
 /// white noise.
+#[derive(Debug, Clone)]
 pub struct BrownianNoise {
     state: f32,
     damping: f32,
     step: f32,
-    rng: ThreadRng,
+    rng: StdRng,
 }
 
 impl Default for BrownianNoise {
     fn default() -> Self {
-        Self::new(0.98, 0.1)
+        Self {
+            state: 0.0,
+            damping: 0.98,
+            step: 0.1,
+            rng: unseeded_rng(),
+        }
     }
 }
 
 impl BrownianNoise {
-    // Creates a new BrownianNoise generator with custom characteristics.
-    //
-    // * `damping`: How much of the previous state is retained (0.0 to 1.0).
-    // * `step`: The maximum change applied by the random walk in each sample.
-    pub fn new(damping: f32, step: f32) -> Self {
-        Self {
-            state: 0.0,
+    /// Creates a new BrownianNoise generator with custom characteristics.
+    ///
+    /// * `damping`: How much of the previous state is retained, from 0.0 up to (but not
+    ///   including) 1.0. At 1.0 the walk never settles and sticks to the clipping bounds.
+    /// * `step`: The maximum change applied by the random walk in each sample (0.0 to 1.0).
+    ///
+    /// # Errors
+    /// Returns [`InvalidParameter`](crate::OxidizerError::InvalidParameter) for values
+    /// outside those ranges.
+    pub fn new(damping: f32, step: f32) -> Result<Self> {
+        Self::check(damping, step)?;
+        Ok(Self {
             damping,
             step,
-            rng: rand::rng(),
-        }
+            ..Self::default()
+        })
+    }
+
+    pub(crate) fn check(damping: f32, step: f32) -> Result<()> {
+        check_range("damping", damping as f64, 0.0..1.0, "0.0 to below 1.0")?;
+        check_range("step", step as f64, 0.0..=1.0, "0.0 to 1.0")
     }
 }
 
@@ -67,4 +118,175 @@ impl NoiseGenerator for BrownianNoise {
         self.state = (self.state * self.damping + (white * self.step)).clamp(-1.0, 1.0);
         self.state
     }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.state = 0.0;
+    }
+}
+
+// How much of a crackle's impulse is left one sample later, giving each tick a short
+// body instead of a single-sample spike.
+const CRACKLE_DECAY: f32 = 0.55;
+
+/// Crackle generator: sparse ticks of random size and polarity over silence, like dust
+/// and wear on a shellac disc.
+///
+/// Unlike the other generators it's silent most of the time, so even high intensities
+/// leave the music audible between the ticks.
+#[derive(Debug, Clone)]
+pub struct CrackleNoise {
+    density: f32,
+    state: f32,
+    rng: StdRng,
+}
+
+impl Default for CrackleNoise {
+    fn default() -> Self {
+        Self {
+            density: 0.0005,
+            state: 0.0,
+            rng: unseeded_rng(),
+        }
+    }
+}
+
+impl CrackleNoise {
+    /// Creates a crackle generator with a chance of `density` (0.0 to 1.0) that a tick
+    /// starts on any one sample; 0.001 is about 44 ticks per second per channel at
+    /// 44.1 kHz.
+    ///
+    /// # Errors
+    /// Returns [`InvalidParameter`](crate::OxidizerError::InvalidParameter) for a
+    /// density outside that range.
+    pub fn new(density: f32) -> Result<Self> {
+        Self::check(density)?;
+        Ok(Self {
+            density,
+            ..Self::default()
+        })
+    }
+
+    pub(crate) fn check(density: f32) -> Result<()> {
+        check_range("density", density as f64, 0.0..=1.0, "0.0 to 1.0")
+    }
+}
+
+impl NoiseGenerator for CrackleNoise {
+    fn next_sample(&mut self) -> f32 {
+        self.state *= CRACKLE_DECAY;
+        if self.rng.random::<f32>() < self.density {
+            // Mostly small ticks, with the odd loud pop
+            let size = self.rng.random::<f32>();
+            let polarity = if self.rng.random::<bool>() { 1.0 } else { -1.0 };
+            self.state += polarity * size * size;
+        }
+        self.state.clamp(-1.0, 1.0)
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.state = 0.0;
+    }
+}
+
+/// Renders `frames` stereo frames of pure noise as an interleaved [L, R, L, R, ...] buffer.
+///
+/// Left and right samples are drawn one after another, so the channels are decorrelated
+/// exactly like the texture added by [`Oxidizer::apply_noise_texture`](crate::Oxidizer::apply_noise_texture).
+pub fn render<N: NoiseGenerator>(generator: &mut N, frames: usize) -> Vec<f32> {
+    (0..frames * 2).map(|_| generator.next_sample()).collect()
+}
+
+// Samples per analysis frame of `profile`.
+const PROFILE_FRAME: usize = 4096;
+
+// Centers of the octave bands the slope of `profile` is fitted over, in Hz.
+const PROFILE_BANDS_HZ: [f32; 7] = [125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0];
+
+/// The measured color and level of a noise generator (see [`profile`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseProfile {
+    /// How much the power falls per octave, in dB: 0 for white noise, −3 for pink, −6
+    /// for brown.
+    pub slope_db_per_octave: f32,
+    /// RMS level of the rendered noise, linear.
+    pub rms: f32,
+}
+
+/// Renders `seconds` of `generator` the way the engine adds it and measures its
+/// spectral slope and RMS level, to check that a custom generator has the intended color
+/// before it goes on real material.
+///
+/// The slope is a least-squares fit of the power in the octaves around 125 Hz to 8 kHz
+/// (those below the Nyquist frequency of `sample_rate`), averaged over both channels.
+/// Reseed the generator first for a repeatable profile.
+pub fn profile<N: NoiseGenerator>(
+    generator: &mut N,
+    seconds: f32,
+    sample_rate: u32,
+) -> NoiseProfile {
+    let frames = (seconds.max(0.0) * sample_rate as f32) as usize;
+    let samples = render(generator, frames);
+    let rate = sample_rate.max(1) as f32;
+    let hz_per_bin = rate / PROFILE_FRAME as f32;
+    let bands: Vec<f32> = PROFILE_BANDS_HZ
+        .into_iter()
+        .filter(|&hz| hz * std::f32::consts::SQRT_2 < rate / 2.0)
+        .collect();
+
+    // Mean power per bin in each band, from Hann-windowed frames overlapping by half
+    let window = fft::hann(PROFILE_FRAME);
+    let mut power = vec![(0.0f64, 0usize); bands.len()];
+    let (mut re, mut im) = (vec![0.0; PROFILE_FRAME], vec![0.0; PROFILE_FRAME]);
+    for channel in 0..2 {
+        let mut start = 0;
+        while start + PROFILE_FRAME <= frames {
+            for (i, (re, im)) in re.iter_mut().zip(&mut im).enumerate() {
+                *re = samples[(start + i) * 2 + channel] * window[i];
+                *im = 0.0;
+            }
+            fft::forward(&mut re, &mut im);
+            for k in 1..PROFILE_FRAME / 2 {
+                let octaves = math::log10(k as f32 * hz_per_bin / bands[0]) / math::log10(2.0);
+                let band = (octaves + 0.5).floor();
+                if band >= 0.0 && (band as usize) < bands.len() {
+                    let (sum, bins) = &mut power[band as usize];
+                    *sum += (re[k] * re[k] + im[k] * im[k]) as f64;
+                    *bins += 1;
+                }
+            }
+            start += PROFILE_FRAME / 2;
+        }
+    }
+
+    // Least squares of the level in dB against the octave
+    let points: Vec<(f64, f64)> = power
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, (_, bins))| bins > 0)
+        .map(|(octave, (sum, bins))| {
+            let level = 10.0 * math::log10((sum / bins as f64).max(1e-30) as f32);
+            (octave as f64, level as f64)
+        })
+        .collect();
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / count.max(1.0);
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / count.max(1.0);
+    let covariance: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = points
+        .iter()
+        .map(|(x, _)| (x - mean_x) * (x - mean_x))
+        .sum();
+
+    NoiseProfile {
+        slope_db_per_octave: match variance > 0.0 {
+            true => (covariance / variance) as f32,
+            false => 0.0,
+        },
+        rms: rms(&samples),
+    }
 }
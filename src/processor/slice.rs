@@ -0,0 +1,406 @@
+//! Running the engine's stages on samples it doesn't own.
+
+use super::{
+    MAX_PASSES, OxidationLevel, Oxidizer, Smoothed, clamp_intensity, planar_sample, soft_limit,
+};
+use crate::analysis;
+use crate::artifacts::ArtifactEvent;
+use crate::codec::{LossyCodec, TelephoneLine};
+use crate::dynamics::{Compressor, Expander, Gate, Limiter, TransientShaper};
+use crate::eq::{EqBand, Riaa};
+use crate::hum::GroundLoop;
+use crate::math;
+use crate::processor::noise::NoiseGenerator;
+use crate::stereo::{Crossfeed, Haas};
+use std::ops::Range;
+
+/// An [`Oxidizer`] working on a borrowed slice of interleaved stereo samples instead of
+/// its own buffer, returned by [`Oxidizer::process_slice`].
+///
+/// It has the same stages as the engine, applied in place. The filter, noise and
+/// smoothing state are the engine's, so consecutive slices of a stream carry on from
+/// one another like consecutive buffers do.
+pub struct SliceProcessor<'a, N: NoiseGenerator> {
+    pub(super) engine: &'a mut Oxidizer<N>,
+    pub(super) samples: &'a mut [f32],
+}
+
+impl<N: NoiseGenerator> SliceProcessor<'_, N> {
+    /// Applies the output guard of the engine (see [`Oxidizer::set_output_guard`]), as
+    /// [`Oxidizer::collect_samples`] does on the way out.
+    pub fn finish(mut self) {
+        self.guard_output();
+    }
+
+    /// See [`Oxidizer::process`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self), fields(frames = self.frames())))]
+    pub fn process(&mut self, level: OxidationLevel) -> &mut Self {
+        self.process_frames(level, 0..self.frames())
+    }
+
+    /// See [`Oxidizer::process_frames`].
+    pub fn process_frames(&mut self, level: OxidationLevel, frames: Range<usize>) -> &mut Self {
+        self.filter_frames(level.alpha(), frames)
+    }
+
+    /// See [`Oxidizer::filter_frames`].
+    pub fn filter_frames(&mut self, alpha: f32, frames: Range<usize>) -> &mut Self {
+        let end = (frames.end * 2).min(self.samples.len());
+        self.engine.set_alpha(alpha);
+
+        for i in (frames.start * 2..end).step_by(2) {
+            let [alpha_l, alpha_r] = self.engine.alpha.each_mut().map(Smoothed::next_value);
+            self.engine.last_l =
+                self.engine.last_l + alpha_l * (self.samples[i] - self.engine.last_l);
+            self.samples[i] = self.engine.last_l;
+
+            self.engine.last_r =
+                self.engine.last_r + alpha_r * (self.samples[i + 1] - self.engine.last_r);
+            self.samples[i + 1] = self.engine.last_r;
+        }
+
+        self
+    }
+
+    /// See [`Oxidizer::filter_frames_cascaded`].
+    pub fn filter_frames_cascaded(
+        &mut self,
+        alpha: f32,
+        passes: u32,
+        frames: Range<usize>,
+    ) -> &mut Self {
+        self.engine
+            .stages
+            .resize(passes.min(MAX_PASSES) as usize, [0.0; 2]);
+        let end = (frames.end * 2).min(self.samples.len());
+        let start = (frames.start * 2).min(end);
+        self.engine.set_alpha(alpha);
+
+        for frame in self.samples[start..end].chunks_exact_mut(2) {
+            let [alpha_l, alpha_r] = self.engine.alpha.each_mut().map(Smoothed::next_value);
+            let (mut l, mut r) = (frame[0], frame[1]);
+            for [last_l, last_r] in &mut self.engine.stages {
+                *last_l += alpha_l * (l - *last_l);
+                *last_r += alpha_r * (r - *last_r);
+                (l, r) = (*last_l, *last_r);
+            }
+            frame[0] = l;
+            frame[1] = r;
+        }
+
+        self
+    }
+
+    /// The slice in its current state.
+    pub fn samples(&self) -> &[f32] {
+        self.samples
+    }
+
+    /// Returns the number of stereo frames in the slice.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / 2
+    }
+
+    /// See [`Oxidizer::normalize`].
+    pub fn normalize(&mut self) -> &mut Self {
+        self.normalize_to(0.95)
+    }
+
+    /// See [`Oxidizer::normalize_to`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self), fields(frames = self.frames())))]
+    pub fn normalize_to(&mut self, target: f32) -> &mut Self {
+        let max_peak = self.samples.iter().map(|s| s.abs()).fold(0.0, f32::max);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(peak = max_peak, target, "normalizing");
+
+        if max_peak > 0.0 {
+            let scale_factor = target / max_peak;
+            for sample in self.samples.iter_mut() {
+                *sample *= scale_factor;
+            }
+        }
+
+        self.engine.normalized_to = Some(target);
+        self
+    }
+
+    /// See [`Oxidizer::normalize_loudness`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self), fields(frames = self.frames())))]
+    pub fn normalize_loudness(
+        &mut self,
+        sample_rate: u32,
+        target_lufs: f32,
+        true_peak_db: f32,
+    ) -> &mut Self {
+        let loudness = analysis::loudness(self.samples, sample_rate);
+        let peak = analysis::true_peak(self.samples);
+        if !loudness.is_finite() || peak == 0.0 {
+            return self;
+        }
+
+        let loudness_gain = analysis::from_dbfs(target_lufs - loudness);
+        let peak_gain = analysis::from_dbfs(true_peak_db) / peak;
+        let gain = loudness_gain.min(peak_gain);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            loudness,
+            peak,
+            gain,
+            peak_limited = peak_gain < loudness_gain,
+            "normalizing loudness"
+        );
+
+        for sample in self.samples.iter_mut() {
+            *sample *= gain;
+        }
+        self
+    }
+
+    // Applies the output guard and checks the normalization on the way out.
+    pub(super) fn guard_output(&mut self) {
+        if self.engine.output_guard.is_some() || self.engine.normalized_to.is_some() {
+            let peak = self.samples.iter().map(|s| s.abs()).fold(0.0, f32::max);
+
+            #[cfg(feature = "tracing")]
+            if let Some(target) = self.engine.normalized_to
+                && peak > target * 1.001
+            {
+                tracing::warn!(
+                    peak,
+                    target,
+                    "stages after normalization raised the peak again"
+                );
+            }
+
+            if let Some(ceiling) = self.engine.output_guard
+                && peak > ceiling
+            {
+                soft_limit(self.samples, ceiling);
+            }
+        }
+
+        self.engine.normalized_to = None;
+    }
+
+    /// See [`Oxidizer::apply_noise_texture`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self), fields(frames = self.frames())))]
+    pub fn apply_noise_texture(&mut self, intensity: f32) -> &mut Self {
+        // Round up so a trailing unpaired sample is textured as well
+        let frames = self.samples.len().div_ceil(2);
+        self.apply_noise_texture_frames(intensity, 0..frames)
+    }
+
+    /// See [`Oxidizer::apply_noise_texture_frames`].
+    pub fn apply_noise_texture_frames(
+        &mut self,
+        intensity: f32,
+        frames: Range<usize>,
+    ) -> &mut Self {
+        self.add_noise_frames(intensity, frames.clone())
+            .saturate_frames(frames)
+    }
+
+    /// See [`Oxidizer::add_noise_frames`].
+    pub fn add_noise_frames(&mut self, intensity: f32, frames: Range<usize>) -> &mut Self {
+        let perceived_intensity = self
+            .engine
+            .intensity_curve
+            .apply(clamp_intensity(intensity));
+        let end = (frames.end * 2).min(self.samples.len());
+        let gains = self.engine.channels.map_or([1.0; 2], |(channels, _)| {
+            channels.map(|c| analysis::from_dbfs(c.noise_db))
+        });
+        for (smoothed, gain) in self.engine.intensity.iter_mut().zip(gains) {
+            smoothed.set_target(perceived_intensity * gain, self.engine.smoothing_frames);
+        }
+
+        for i in (frames.start * 2..end).step_by(2) {
+            let [mut intensity_l, mut intensity_r] =
+                self.engine.intensity.each_mut().map(Smoothed::next_value);
+            if let Some((ducking, follower)) = &mut self.engine.ducking {
+                let right = self.samples.get(i + 1).map_or(0.0, |s| s.abs());
+                let gain = ducking.gain(follower.process(self.samples[i].abs().max(right)));
+                intensity_l *= gain;
+                intensity_r *= gain;
+            }
+            let noise_l = self.engine.noise_generator.next_sample();
+            let noise_r = self.engine.noise_generator.next_sample();
+
+            self.samples[i] += noise_l * intensity_l;
+            if i + 1 < self.samples.len() {
+                self.samples[i + 1] += noise_r * intensity_r;
+            }
+        }
+
+        self
+    }
+
+    /// See [`Oxidizer::saturate_frames`].
+    pub fn saturate_frames(&mut self, frames: Range<usize>) -> &mut Self {
+        let end = (frames.end * 2).min(self.samples.len());
+        let start = (frames.start * 2).min(end);
+        for sample in &mut self.samples[start..end] {
+            *sample = math::tanh(*sample);
+        }
+        self
+    }
+
+    /// See [`Oxidizer::mix_with`].
+    pub fn mix_with(&mut self, dry: &[f32], mix: f32) -> &mut Self {
+        self.mix_frames(dry, mix, 0..self.samples.len().div_ceil(2))
+    }
+
+    /// See [`Oxidizer::mix_frames`].
+    pub fn mix_frames(&mut self, dry: &[f32], mix: f32, frames: Range<usize>) -> &mut Self {
+        let end = (frames.end * 2).min(self.samples.len()).min(dry.len());
+        let start = (frames.start * 2).min(end);
+        self.engine
+            .mix
+            .set_target(mix.clamp(0.0, 1.0), self.engine.smoothing_frames);
+
+        let wet = self.samples[start..end].chunks_mut(2);
+        for (wet, dry) in wet.zip(dry[start..end].chunks(2)) {
+            let mix = self.engine.mix.next_value();
+            for (wet, &dry) in wet.iter_mut().zip(dry) {
+                // Same sanitizing as `consume`
+                let dry = if dry.is_finite() { dry } else { 0.0 };
+                *wet = dry + (*wet - dry) * mix;
+            }
+        }
+
+        self
+    }
+
+    /// See [`Oxidizer::mix_planar`].
+    pub fn mix_planar<C: AsRef<[f32]>>(&mut self, dry: &[C], mix: f32) -> &mut Self {
+        self.engine
+            .mix
+            .set_target(mix.clamp(0.0, 1.0), self.engine.smoothing_frames);
+
+        for (frame, wet) in self.samples.chunks_mut(2).enumerate() {
+            let mix = self.engine.mix.next_value();
+            for (channel, wet) in wet.iter_mut().enumerate() {
+                let dry = planar_sample(dry, frame * 2 + channel);
+                *wet = dry + (*wet - dry) * mix;
+            }
+        }
+        self
+    }
+
+    /// See [`Oxidizer::overlay`].
+    pub fn overlay(&mut self, bed: &[f32], gain: f32, loop_if_shorter: bool) -> &mut Self {
+        // Keep the channels aligned when repeating
+        let period = bed.len() - bed.len() % 2;
+        if period == 0 {
+            return self;
+        }
+
+        for (i, sample) in self.samples.iter_mut().enumerate() {
+            if i >= period && !loop_if_shorter {
+                break;
+            }
+            let layer = bed[i % period];
+            // Same sanitizing as `consume`
+            if layer.is_finite() {
+                *sample += layer * gain;
+            }
+        }
+
+        self
+    }
+
+    /// See [`Oxidizer::compress`].
+    pub fn compress(&mut self, compressor: &Compressor, sample_rate: u32) -> &mut Self {
+        compressor.process(self.samples, sample_rate);
+        self
+    }
+
+    /// See [`Oxidizer::gate`].
+    pub fn gate(&mut self, gate: &Gate, key: Option<&[f32]>, sample_rate: u32) -> &mut Self {
+        match key {
+            Some(key) => gate.process(self.samples, key, sample_rate),
+            None => gate.process_self_keyed(self.samples, sample_rate),
+        }
+        self
+    }
+
+    /// See [`Oxidizer::expand`].
+    pub fn expand(&mut self, expander: &Expander, sample_rate: u32) -> &mut Self {
+        expander.process(self.samples, sample_rate);
+        self
+    }
+
+    /// See [`Oxidizer::shape_transients`].
+    pub fn shape_transients(&mut self, shaper: &TransientShaper, sample_rate: u32) -> &mut Self {
+        shaper.process(self.samples, sample_rate);
+        self
+    }
+
+    /// See [`Oxidizer::add_artifacts`].
+    pub fn add_artifacts(&mut self, events: &[ArtifactEvent], sample_rate: u32) -> &mut Self {
+        for event in events {
+            event.apply(self.samples, sample_rate);
+        }
+        self
+    }
+
+    /// See [`Oxidizer::crossfeed`].
+    pub fn crossfeed(&mut self, crossfeed: &Crossfeed, sample_rate: u32) -> &mut Self {
+        crossfeed.process(self.samples, sample_rate);
+        self
+    }
+
+    /// See [`Oxidizer::ground_loop`].
+    pub fn ground_loop(&mut self, ground_loop: &GroundLoop, sample_rate: u32) -> &mut Self {
+        ground_loop.process(self.samples, sample_rate);
+        self
+    }
+
+    /// See [`Oxidizer::haas`].
+    pub fn haas(&mut self, haas: &Haas, sample_rate: u32) -> &mut Self {
+        haas.process(self.samples, sample_rate);
+        self
+    }
+
+    /// See [`Oxidizer::equalize`].
+    pub fn equalize(&mut self, band: &EqBand, sample_rate: u32) -> &mut Self {
+        band.process(self.samples, sample_rate);
+        self
+    }
+
+    /// See [`Oxidizer::telephone`].
+    pub fn telephone(&mut self, line: &TelephoneLine, sample_rate: u32) -> &mut Self {
+        line.process(self.samples, sample_rate);
+        self
+    }
+
+    /// See [`Oxidizer::lossy_codec`].
+    pub fn lossy_codec(&mut self, codec: &LossyCodec, sample_rate: u32) -> &mut Self {
+        codec.process(self.samples, sample_rate);
+        self
+    }
+
+    /// See [`Oxidizer::riaa`].
+    pub fn riaa(&mut self, curve: Riaa, sample_rate: u32) -> &mut Self {
+        curve.process(self.samples, sample_rate);
+        self
+    }
+
+    /// See [`Oxidizer::limit`].
+    pub fn limit(&mut self, limiter: &Limiter, sample_rate: u32) -> &mut Self {
+        limiter.process(self.samples, sample_rate);
+        self
+    }
+
+    /// See [`Oxidizer::process_multiple`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self), fields(frames = self.frames())))]
+    pub fn process_multiple(&mut self, level: OxidationLevel, passes: u32) -> &mut Self {
+        if passes > MAX_PASSES {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(passes, max = MAX_PASSES, "too many filter passes, clamping");
+        }
+        self.filter_frames_cascaded(level.alpha(), passes, 0..self.frames())
+    }
+}
@@ -0,0 +1,54 @@
+/// A parameter that glides to a new value over a number of frames instead of jumping
+/// to it, so changes mid-stream don't click ("zipper noise").
+///
+/// The first value it's given is taken as is. With a ramp of zero frames, every value
+/// is.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Smoothed {
+    current: Option<f32>,
+    target: f32,
+    step: f32,
+    remaining: usize,
+}
+
+impl Smoothed {
+    /// Heads for `target` over `ramp_frames` frames, starting from wherever the current
+    /// ramp has got to. Setting the target it's already heading for changes nothing.
+    pub fn set_target(&mut self, target: f32, ramp_frames: usize) {
+        let Some(current) = self.current.filter(|_| ramp_frames > 0) else {
+            *self = Self {
+                current: Some(target),
+                target,
+                step: 0.0,
+                remaining: 0,
+            };
+            return;
+        };
+        if target == self.target {
+            return;
+        }
+        self.target = target;
+        self.step = (target - current) / ramp_frames as f32;
+        self.remaining = ramp_frames;
+    }
+
+    /// Returns the value for the next frame.
+    pub fn next_value(&mut self) -> f32 {
+        if self.remaining == 0 {
+            return self.target;
+        }
+        self.remaining -= 1;
+        let value = match self.remaining {
+            // Land exactly on the target, whatever the rounding on the way
+            0 => self.target,
+            _ => self.current.unwrap_or(self.target) + self.step,
+        };
+        self.current = Some(value);
+        value
+    }
+
+    /// Returns `true` while a ramp is under way.
+    pub fn is_ramping(&self) -> bool {
+        self.remaining > 0
+    }
+}
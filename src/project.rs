@@ -0,0 +1,249 @@
+//! Project files: a whole session in one reproducible description.
+//!
+//! A `.oxproj` file is TOML naming the inputs, the preset the settings start from, the
+//! settings themselves (segments and automation included) and where and how the render
+//! is exported. Relative paths, overlay and sidechain files included, are taken from the
+//! project file's directory, so a project can move together with its audio.
+//!
+//! ```toml
+//! inputs = ["takes/side-a.wav", "takes/side-b.wav"]
+//! crossfade_secs = 2.0
+//! preset = "vinyl"
+//!
+//! [settings]
+//! intensity = 0.2
+//!
+//! [export]
+//! output = "renders/album.flac"
+//! bit_depth = "int24"
+//! ```
+
+use crate::config::OxidizerConfig;
+use crate::error::{OxidizerError, Result};
+use crate::io::{AudioFormat, BitDepth, TimeRange};
+use crate::overlay::OverlaySource;
+use crate::pipeline::{Pipeline, RunReport};
+use crate::presets::{self, Preset};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A session: what is rendered, with which settings, and where to.
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Project {
+    /// Audio files to render. Several are joined into one program, in order.
+    pub inputs: Vec<PathBuf>,
+    /// Crossfade between joined inputs, in seconds.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub crossfade_secs: f64,
+    /// Built-in preset or preset file the settings start from. The settings in the
+    /// project file are applied over it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// The processing settings, including segments and automation.
+    pub settings: OxidizerConfig,
+    pub export: Export,
+}
+
+/// Where and how a [`Project`] is rendered.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Export {
+    pub output: PathBuf,
+    /// Container of the output. Without one it follows the extension of `output`,
+    /// falling back to the format in the settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<AudioFormat>,
+    /// Overrides the bit depth in the settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bit_depth: Option<BitDepth>,
+    /// Overrides the sample rate in the settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<u32>,
+    /// Offset into the input the render starts at, in seconds.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub start_secs: f64,
+    /// Length of the render, in seconds; until the end of the input without one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+}
+
+impl Default for Export {
+    fn default() -> Self {
+        Self {
+            output: PathBuf::from("output.wav"),
+            format: None,
+            bit_depth: None,
+            sample_rate: None,
+            start_secs: 0.0,
+            duration_secs: None,
+        }
+    }
+}
+
+impl Project {
+    /// Loads a project file, resolving its preset and its relative paths.
+    ///
+    /// # Errors
+    /// Returns [`Config`](OxidizerError::Config) for a file that doesn't parse and
+    /// [`InvalidValue`](OxidizerError::InvalidValue) for an unknown preset.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let base = directory(path)?;
+        let mut table: toml::Table =
+            toml::from_str(&std::fs::read_to_string(path)?).map_err(|e| config_error(path, e))?;
+
+        // The settings are layered over the preset, key by key
+        let preset = table.get("preset").and_then(|v| v.as_str());
+        if let Some(name) = preset {
+            let config = preset_config(name, &base)?;
+            let mut settings = match toml::Value::try_from(config) {
+                Ok(toml::Value::Table(settings)) => settings,
+                Ok(_) => toml::Table::new(),
+                Err(e) => return Err(config_error(path, e)),
+            };
+            if let Some(toml::Value::Table(overrides)) = table.remove("settings") {
+                merge(&mut settings, overrides);
+            }
+            table.insert("settings".to_string(), toml::Value::Table(settings));
+        }
+
+        let mut project: Project = table.try_into().map_err(|e| config_error(path, e))?;
+        map_paths(&mut project, &|file| base.join(file));
+        Ok(project)
+    }
+
+    /// Writes the project file. Absolute paths inside the directory of `path` are
+    /// written relative to it; relative paths are written as they are, and read back
+    /// relative to the project file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let base = directory(path)?;
+        let relative = |file: &Path| file.strip_prefix(&base).unwrap_or(file).to_path_buf();
+
+        let mut project = self.clone();
+        map_paths(&mut project, &relative);
+        let text = toml::to_string_pretty(&project).map_err(|e| config_error(path, e))?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// The settings with the export settings applied.
+    pub fn config(&self) -> OxidizerConfig {
+        let mut config = self.settings.clone();
+        if let Some(format) = self
+            .export
+            .format
+            .or_else(|| AudioFormat::from_path(&self.export.output))
+        {
+            config.format = format;
+        }
+        if let Some(bit_depth) = self.export.bit_depth {
+            config.bit_depth = bit_depth;
+        }
+        if let Some(sample_rate) = self.export.sample_rate {
+            config.sample_rate = sample_rate;
+        }
+        config
+    }
+
+    /// A pipeline set up to render the project: its settings, range and crossfade.
+    pub fn pipeline(&self) -> Pipeline {
+        let mut pipeline = Pipeline::new(self.config());
+        let seconds = |secs: f64| Duration::from_secs_f64(secs.max(0.0));
+        pipeline
+            .set_range(TimeRange::new(
+                seconds(self.export.start_secs),
+                self.export.duration_secs.map(seconds),
+            ))
+            .set_crossfade(seconds(self.crossfade_secs));
+        pipeline
+    }
+
+    /// Renders the project to its output file.
+    ///
+    /// # Errors
+    /// Returns [`InvalidValue`](OxidizerError::InvalidValue) for a project without
+    /// inputs, and the errors of [`Pipeline::run_joined_with_report`].
+    pub fn render(&self) -> Result<RunReport> {
+        if self.inputs.is_empty() {
+            return Err(OxidizerError::InvalidValue(
+                "The project has no inputs".to_string(),
+            ));
+        }
+        self.pipeline()
+            .run_joined_with_report(&self.inputs, &self.export.output)
+    }
+}
+
+// Replaces every file path of `project` with `map` of it.
+fn map_paths(project: &mut Project, map: &impl Fn(&Path) -> PathBuf) {
+    for input in &mut project.inputs {
+        *input = map(input);
+    }
+    project.export.output = map(&project.export.output);
+    map_config_paths(&mut project.settings, map);
+}
+
+// Replaces the overlay and sidechain files of `config`, its segments and its branches
+// with `map` of them.
+fn map_config_paths(config: &mut OxidizerConfig, map: &impl Fn(&Path) -> PathBuf) {
+    for overlay in &mut config.overlay {
+        if let OverlaySource::File(file) = &mut overlay.source {
+            *file = map(file);
+        }
+    }
+    if let Some(sidechain) = &mut config.sidechain {
+        *sidechain = map(sidechain);
+    }
+    for segment in &mut config.segments {
+        map_config_paths(&mut segment.config, map);
+    }
+    for branch in &mut config.branches {
+        map_config_paths(&mut branch.config, map);
+    }
+}
+
+// The settings of a built-in preset, or of a preset file relative to `base`.
+fn preset_config(name: &str, base: &Path) -> Result<OxidizerConfig> {
+    if let Some(preset) = presets::find(name) {
+        return Ok(preset.config);
+    }
+    let path = base.join(name);
+    if path.is_file() {
+        return Ok(Preset::load(path)?.config);
+    }
+    Err(OxidizerError::InvalidValue(format!(
+        "Unknown preset: {} (available: {}, or a preset file)",
+        name,
+        presets::names().collect::<Vec<_>>().join(", ")
+    )))
+}
+
+// Copies `overrides` into `table`, merging nested tables instead of replacing them.
+fn merge(table: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (table.get_mut(&key), value) {
+            (Some(toml::Value::Table(inner)), toml::Value::Table(value)) => merge(inner, value),
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+// The directory of the project file at `path`, as an absolute path.
+fn directory(path: &Path) -> Result<PathBuf> {
+    Ok(std::path::absolute(path)?
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default())
+}
+
+fn is_zero(secs: &f64) -> bool {
+    *secs == 0.0
+}
+
+fn config_error(path: &Path, error: impl std::fmt::Display) -> OxidizerError {
+    OxidizerError::Config(format!("{}: {}", path.display(), error))
+}
@@ -0,0 +1,223 @@
+//! Speed instabilities of the playback medium, and the chorus built the same way.
+//!
+//! A capstan or turntable that doesn't run at a perfectly steady speed bends the pitch
+//! of everything it plays. The effects here read the signal through a delay line whose
+//! length follows a modulator: a growing delay lowers the pitch, a shrinking one raises
+//! it.
+//!
+//! The same delay line gives the once-per-revolution wobble of an off-center record.
+
+use crate::modulation::{Lfo, LfoShape};
+
+/// Flutter: fast, shallow speed variations, like a worn idler wheel or a stretched
+/// belt, heard as a watery shimmer on sustained notes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Flutter {
+    pub shape: LfoShape,
+    /// Cycles per second of the speed variation.
+    pub rate_hz: f32,
+    /// Largest deviation from the nominal speed, in percent.
+    pub depth_percent: f32,
+}
+
+impl Default for Flutter {
+    fn default() -> Self {
+        Self {
+            shape: LfoShape::Sine,
+            rate_hz: 6.0,
+            depth_percent: 0.1,
+        }
+    }
+}
+
+impl Flutter {
+    /// Applies the flutter to interleaved stereo `samples` in place. `seed` drives the
+    /// random-walk shape.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32, seed: u64) {
+        let lfo = Lfo {
+            shape: self.shape,
+            rate_hz: self.rate_hz,
+            depth: 1.0,
+        };
+        let mut oscillator = lfo.start(seed);
+        let seconds = 1.0 / sample_rate.max(1) as f32;
+        // The delay swing whose rate of change peaks at the given speed deviation
+        let swing = self.depth_percent.max(0.0) as f64
+            / 100.0
+            / (std::f64::consts::TAU * self.rate_hz.max(0.01) as f64);
+
+        modulate_delay(samples, sample_rate, 2.0 * swing, || {
+            swing * (1.0 + oscillator.advance(seconds) as f64)
+        });
+    }
+}
+
+/// The nominal speed of a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecordSpeed {
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "33"))]
+    Lp, // 33⅓ rpm: long-playing albums.
+    #[cfg_attr(feature = "serde", serde(rename = "45"))]
+    Single, // 45 rpm: seven-inch singles.
+    #[cfg_attr(feature = "serde", serde(rename = "78"))]
+    Shellac, // 78 rpm: shellac discs before the vinyl era.
+}
+
+impl RecordSpeed {
+    /// Every record speed.
+    pub const ALL: [RecordSpeed; 3] = [RecordSpeed::Lp, RecordSpeed::Single, RecordSpeed::Shellac];
+
+    /// Returns the name used on the command line and in config files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RecordSpeed::Lp => "33",
+            RecordSpeed::Single => "45",
+            RecordSpeed::Shellac => "78",
+        }
+    }
+
+    pub fn try_from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|speed| speed.name() == s)
+            .ok_or_else(|| format!("Unknown record speed: {} (use 33, 45 or 78)", s))
+    }
+
+    /// Revolutions per minute.
+    pub fn rpm(&self) -> f32 {
+        match self {
+            RecordSpeed::Lp => 100.0 / 3.0,
+            RecordSpeed::Single => 45.0,
+            RecordSpeed::Shellac => 78.0,
+        }
+    }
+}
+
+/// Wobble: a record pressed with its hole off center. The stylus runs faster on the
+/// far side of each revolution and slower on the near side, so the pitch swings up and
+/// down exactly once per turn, slow and perfectly regular, unlike the wandering of worn
+/// drive parts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Wobble {
+    pub speed: RecordSpeed,
+    /// Largest deviation from the nominal speed, in percent.
+    pub depth_percent: f32,
+}
+
+impl Default for Wobble {
+    fn default() -> Self {
+        Self {
+            speed: RecordSpeed::Lp,
+            depth_percent: 0.5,
+        }
+    }
+}
+
+impl Wobble {
+    /// Applies the wobble to interleaved stereo `samples` in place.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        // A sine flutter at the rate of revolution, with no randomness in it
+        let flutter = Flutter {
+            shape: LfoShape::Sine,
+            rate_hz: self.speed.rpm() / 60.0,
+            depth_percent: self.depth_percent,
+        };
+        flutter.process(samples, sample_rate, 0);
+    }
+}
+
+/// Chorus: a slightly late copy of the signal, its delay swept by an LFO, mixed back
+/// in. The detuned double smears transients and blurs the pitch, like sound bent by
+/// moving water.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Chorus {
+    /// Cycles per second of the sweep.
+    pub rate_hz: f32,
+    /// Average delay of the copy, in milliseconds.
+    pub delay_ms: f32,
+    /// How far the delay swings either way, in milliseconds.
+    pub depth_ms: f32,
+    /// Share of the delayed copy in the output, from 0 to 1.
+    pub mix: f32,
+}
+
+impl Default for Chorus {
+    fn default() -> Self {
+        Self {
+            rate_hz: 0.8,
+            delay_ms: 15.0,
+            depth_ms: 3.0,
+            mix: 0.3,
+        }
+    }
+}
+
+impl Chorus {
+    /// Applies the chorus to interleaved stereo `samples` in place.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        let mut oscillator = Lfo {
+            rate_hz: self.rate_hz,
+            depth: 1.0,
+            ..Default::default()
+        }
+        .start(0);
+        let seconds = 1.0 / sample_rate.max(1) as f32;
+        let delay = self.delay_ms.max(0.0) as f64 / 1000.0;
+        let depth = self.depth_ms.clamp(0.0, self.delay_ms.max(0.0)) as f64 / 1000.0;
+
+        let mut wet = samples.to_vec();
+        modulate_delay(&mut wet, sample_rate, delay + depth, || {
+            delay + depth * oscillator.advance(seconds) as f64
+        });
+        let mix = self.mix.clamp(0.0, 1.0);
+        for (sample, wet) in samples.iter_mut().zip(wet) {
+            *sample += (wet - *sample) * mix;
+        }
+    }
+}
+
+// Reads interleaved stereo `samples` through a delay line of `delay()` seconds per
+// frame, never more than `max_delay` seconds, interpolating between samples.
+pub(crate) fn modulate_delay(
+    samples: &mut [f32],
+    sample_rate: u32,
+    max_delay: f64,
+    mut delay: impl FnMut() -> f64,
+) {
+    let rate = sample_rate.max(1) as f64;
+    let max_delay = max_delay.max(0.0) * rate;
+    let source = samples.to_vec();
+    let frames = source.len() / 2;
+
+    for frame in 0..frames {
+        let position = frame as f64 - (delay() * rate).clamp(0.0, max_delay);
+        let index = position.floor();
+        let fraction = (position - index) as f32;
+        for channel in 0..2 {
+            // Before the start the line is still silent
+            let at = |i: f64| match i >= 0.0 && (i as usize) < frames {
+                true => source[i as usize * 2 + channel],
+                false => 0.0,
+            };
+            let (a, b) = (at(index), at(index + 1.0));
+            samples[frame * 2 + channel] = a + (b - a) * fraction;
+        }
+    }
+}
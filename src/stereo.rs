@@ -0,0 +1,154 @@
+//! Stereo image processors.
+//!
+//! They work on interleaved stereo buffers like the rest of the engine and move sound
+//! between the channels rather than changing its level.
+
+use crate::analysis::from_dbfs;
+use crate::processor::levels::alpha_for_cutoff;
+use std::collections::VecDeque;
+
+/// Headphone crossfeed: each channel bleeds into the other, low-passed and slightly
+/// late, the way sound from a speaker reaches the far ear around the head.
+///
+/// Hard-panned instruments of early stereo mixes stop pressing on one ear. The result
+/// is scaled so centered bass keeps its level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Crossfeed {
+    /// Level of the bleed relative to the direct signal, in dB.
+    pub level_db: f32,
+    /// Cutoff of the low pass on the bleed, in Hz.
+    pub cutoff_hz: f32,
+    /// Delay of the bleed, in milliseconds.
+    pub delay_ms: f32,
+}
+
+impl Default for Crossfeed {
+    fn default() -> Self {
+        Self {
+            level_db: -6.0,
+            cutoff_hz: 700.0,
+            delay_ms: 0.3,
+        }
+    }
+}
+
+impl Crossfeed {
+    /// Crossfeeds interleaved stereo `samples` in place.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        let alpha = alpha_for_cutoff(self.cutoff_hz, sample_rate);
+        let gain = from_dbfs(self.level_db);
+        let scale = 1.0 / (1.0 + gain);
+
+        let delay = (self.delay_ms.max(0.0) / 1000.0 * sample_rate as f32).round() as usize;
+        // Low-passed frames waiting out the delay
+        let mut line = VecDeque::from(vec![[0.0f32; 2]; delay]);
+        let mut lowpassed = [0.0f32; 2];
+
+        for frame in samples.chunks_exact_mut(2) {
+            for (low, &sample) in lowpassed.iter_mut().zip(&*frame) {
+                *low += alpha * (sample - *low);
+            }
+            line.push_back(lowpassed);
+            let [bleed_l, bleed_r] = line.pop_front().unwrap_or_default();
+
+            frame[0] = (frame[0] + gain * bleed_r) * scale;
+            frame[1] = (frame[1] + gain * bleed_l) * scale;
+        }
+    }
+}
+
+/// One side of a stereo signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Channel {
+    Left,
+    #[default]
+    Right,
+}
+
+impl Channel {
+    /// Both channels, left first.
+    pub const ALL: [Channel; 2] = [Channel::Left, Channel::Right];
+
+    /// Returns the name used on the command line and in config files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Channel::Left => "left",
+            Channel::Right => "right",
+        }
+    }
+
+    pub fn try_from_str(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|channel| channel.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown channel: {} (use left or right)", s))
+    }
+
+    /// Position of the channel in an interleaved frame.
+    pub fn index(&self) -> usize {
+        match self {
+            Channel::Left => 0,
+            Channel::Right => 1,
+        }
+    }
+}
+
+/// Haas-effect spreader: one channel is delayed by a few milliseconds and dulled, so a
+/// mono source seems to come from a wide space instead of a point in the middle.
+///
+/// Unlike a width control, it works on sources without any stereo information at all.
+/// Below ~35 ms the ear hears one sound rather than an echo. The damping keeps the
+/// delayed side from sounding like a comb filter when the result is summed to mono.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Haas {
+    /// Delay of the late channel, in milliseconds.
+    pub delay_ms: f32,
+    /// Cutoff of the low pass on the late channel, in Hz.
+    pub damping_hz: f32,
+    /// The channel that is delayed.
+    pub delayed: Channel,
+}
+
+impl Default for Haas {
+    fn default() -> Self {
+        Self {
+            delay_ms: 15.0,
+            damping_hz: 6000.0,
+            delayed: Channel::Right,
+        }
+    }
+}
+
+impl Haas {
+    /// Spreads interleaved stereo `samples` in place.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        let channel = self.delayed.index();
+        let alpha = alpha_for_cutoff(self.damping_hz, sample_rate);
+
+        let delay = (self.delay_ms.max(0.0) / 1000.0 * sample_rate as f32).round() as usize;
+        let mut line = VecDeque::from(vec![0.0f32; delay]);
+        let mut damped = 0.0;
+
+        for frame in samples.chunks_exact_mut(2) {
+            line.push_back(frame[channel]);
+            let late = line.pop_front().unwrap_or_default();
+            damped += alpha * (late - damped);
+            frame[channel] = damped;
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Known signals and golden-output comparisons for regression tests of the DSP.
+//!
+//! Everything here works on interleaved stereo buffers like the rest of the crate, with
+//! both channels carrying the same signal. Pair it with the `deterministic` feature and
+//! a fixed seed, and a render can be compared against a golden file from another
+//! machine.
+//!
+//! ```rust
+//! use oxidizer::{OxidizerConfig, Pipeline, testing};
+//!
+//! let input = testing::sweep(20.0, 20000.0, 0.5, 1.0, 44100);
+//! let config = OxidizerConfig { seed: Some(7), ..Default::default() };
+//! let render = Pipeline::new(config.clone()).process(input.clone(), 44100)?;
+//! let again = Pipeline::new(config).process(input, 44100)?;
+//! testing::assert_rms_close(&again, &render, -60.0);
+//! testing::assert_spectrum_close(&again, &render, 44100, 0.5);
+//! # Ok::<(), oxidizer::OxidizerError>(())
+//! ```
+
+use crate::analysis::{rms, to_dbfs};
+use crate::fft;
+use crate::math;
+use crate::processor::noise::{NoiseGenerator, WhiteNoise};
+use std::f64::consts::TAU;
+
+// Samples per analysis frame of the spectral comparison.
+const FRAME: usize = 4096;
+
+// Lowest octave band of the spectral comparison, in Hz. Each band spans an octave up.
+const LOWEST_BAND_HZ: f32 = 31.25;
+
+// Bands this quiet in both buffers hold nothing worth comparing.
+const SILENT_BAND_DB: f32 = -120.0;
+
+/// A sine of `hz` at peak amplitude `level`, `seconds` long.
+pub fn sine(hz: f32, level: f32, seconds: f32, sample_rate: u32) -> Vec<f32> {
+    let step = TAU * hz as f64 / sample_rate.max(1) as f64;
+    stereo(seconds, sample_rate, |frame| {
+        level * math::sin_f64(step * frame as f64) as f32
+    })
+}
+
+/// An exponential sine sweep from `from_hz` to `to_hz` at peak amplitude `level`,
+/// `seconds` long. It spends as long on every octave, like the sweeps measurement rigs
+/// use.
+pub fn sweep(from_hz: f32, to_hz: f32, level: f32, seconds: f32, sample_rate: u32) -> Vec<f32> {
+    let rate = sample_rate.max(1) as f64;
+    let duration = seconds.max(0.0) as f64;
+    let (from, to) = (
+        from_hz.max(f32::MIN_POSITIVE) as f64,
+        to_hz.max(f32::MIN_POSITIVE) as f64,
+    );
+    let span = math::ln((to / from) as f32) as f64;
+    stereo(seconds, sample_rate, |frame| {
+        let t = frame as f64 / rate;
+        // The phase integrates the frequency from / to, which grows exponentially
+        let phase = match span.abs() < 1e-9 {
+            true => TAU * from * t,
+            false => TAU * from * duration / span * (math::exp_f64(t / duration * span) - 1.0),
+        };
+        level * math::sin_f64(phase) as f32
+    })
+}
+
+/// A unit impulse on the first frame of `frames` frames of silence.
+pub fn impulse(frames: usize) -> Vec<f32> {
+    let mut samples = vec![0.0; frames * 2];
+    samples.iter_mut().take(2).for_each(|s| *s = 1.0);
+    samples
+}
+
+/// `count` bursts of pink noise, each `burst_seconds` long and followed by `gap_seconds`
+/// of silence. Every burst has an RMS level of `level`; `seed` makes them repeatable.
+///
+/// Pink noise has equal energy per octave, about the balance of music, so the bursts
+/// exercise the whole band at once, and their edges exercise gates and envelopes.
+pub fn pink_bursts(
+    count: usize,
+    burst_seconds: f32,
+    gap_seconds: f32,
+    level: f32,
+    sample_rate: u32,
+    seed: u64,
+) -> Vec<f32> {
+    let burst = (burst_seconds.max(0.0) * sample_rate as f32) as usize;
+    let gap = (gap_seconds.max(0.0) * sample_rate as f32) as usize;
+    let mut white = WhiteNoise::default();
+    white.reseed(seed);
+    // Paul Kellet's economy filter: three one-poles spread over the band tilt white
+    // noise by 3 dB per octave
+    let mut poles = [0.0f32; 3];
+    let mut pink: Vec<f32> = (0..burst)
+        .map(|_| {
+            let white = white.next_sample();
+            poles[0] = 0.99765 * poles[0] + white * 0.0990460;
+            poles[1] = 0.96300 * poles[1] + white * 0.2965164;
+            poles[2] = 0.57000 * poles[2] + white * 1.0526913;
+            poles.iter().sum::<f32>() + white * 0.1848
+        })
+        .collect();
+    let scale = level / rms(&pink).max(f32::MIN_POSITIVE);
+    pink.iter_mut().for_each(|s| *s *= scale);
+
+    let mut samples = Vec::with_capacity(count * (burst + gap) * 2);
+    for _ in 0..count {
+        samples.extend(pink.iter().flat_map(|&s| [s, s]));
+        samples.extend(std::iter::repeat_n(0.0, gap * 2));
+    }
+    samples
+}
+
+/// Returns the level of the difference between `actual` and `golden`, in dB relative
+/// to the level of `golden`. Identical buffers give negative infinity; −60 dB means the
+/// difference is a thousandth of the signal.
+///
+/// # Panics
+/// If the buffers differ in length.
+pub fn rms_difference_db(actual: &[f32], golden: &[f32]) -> f32 {
+    assert_eq!(actual.len(), golden.len(), "buffers differ in length");
+    let difference: Vec<f32> = actual.iter().zip(golden).map(|(a, g)| a - g).collect();
+    to_dbfs(rms(&difference)) - to_dbfs(rms(golden))
+}
+
+/// Returns the largest difference in level between `actual` and `golden` in any octave
+/// band from 31.25 Hz up, in dB. Unlike [`rms_difference_db`] it ignores phase, so it
+/// holds up when the noise or a modulator runs differently but the tone is the same.
+pub fn spectral_difference_db(actual: &[f32], golden: &[f32], sample_rate: u32) -> f32 {
+    band_levels(actual, sample_rate)
+        .into_iter()
+        .zip(band_levels(golden, sample_rate))
+        .filter(|&(a, g)| a > SILENT_BAND_DB || g > SILENT_BAND_DB)
+        .map(|(a, g)| (a.max(SILENT_BAND_DB) - g.max(SILENT_BAND_DB)).abs())
+        .fold(0.0, f32::max)
+}
+
+/// Asserts that `actual` differs from `golden` by no more than `tolerance_db`, as
+/// measured by [`rms_difference_db`].
+#[track_caller]
+pub fn assert_rms_close(actual: &[f32], golden: &[f32], tolerance_db: f32) {
+    let difference = rms_difference_db(actual, golden);
+    assert!(
+        difference <= tolerance_db,
+        "render differs from the golden output by {:.1} dB (tolerance {:.1} dB)",
+        difference,
+        tolerance_db
+    );
+}
+
+/// Asserts that no octave band of `actual` is more than `tolerance_db` louder or
+/// quieter than in `golden`, as measured by [`spectral_difference_db`].
+#[track_caller]
+pub fn assert_spectrum_close(actual: &[f32], golden: &[f32], sample_rate: u32, tolerance_db: f32) {
+    let difference = spectral_difference_db(actual, golden, sample_rate);
+    assert!(
+        difference <= tolerance_db,
+        "spectrum differs from the golden output by {:.2} dB in one band (tolerance {:.2} dB)",
+        difference,
+        tolerance_db
+    );
+}
+
+// Fills `seconds` of interleaved stereo with the same `signal(frame)` on both channels.
+fn stereo(seconds: f32, sample_rate: u32, signal: impl Fn(usize) -> f32) -> Vec<f32> {
+    let frames = (seconds.max(0.0) * sample_rate as f32) as usize;
+    (0..frames)
+        .flat_map(|frame| {
+            let s = signal(frame);
+            [s, s]
+        })
+        .collect()
+}
+
+// Mean-square level in each octave band of both channels, in dB (a full-scale sine in
+// the band reads −3 dB), from Hann-windowed frames overlapping by half.
+fn band_levels(samples: &[f32], sample_rate: u32) -> Vec<f32> {
+    let window = fft::hann(FRAME);
+    let hz_per_bin = sample_rate as f32 / FRAME as f32;
+    let bands = (math::log10(sample_rate as f32 / 2.0 / LOWEST_BAND_HZ) / math::log10(2.0))
+        .floor()
+        .max(0.0) as usize;
+    let mut power = vec![0.0f64; bands];
+    let (mut re, mut im) = (vec![0.0; FRAME], vec![0.0; FRAME]);
+    let mut analyzed = 0;
+
+    let frames = samples.len() / 2;
+    for channel in 0..2 {
+        let mut start = 0;
+        // Short buffers still get one (zero-padded) frame
+        while start == 0 || start + FRAME <= frames {
+            for (i, (re, im)) in re.iter_mut().zip(&mut im).enumerate() {
+                let sample = samples.get((start + i) * 2 + channel).copied();
+                *re = sample.unwrap_or(0.0) * window[i];
+                *im = 0.0;
+            }
+            fft::forward(&mut re, &mut im);
+            for k in 1..FRAME / 2 {
+                let band = math::log10(k as f32 * hz_per_bin / LOWEST_BAND_HZ) / math::log10(2.0);
+                if band >= 0.0 && (band as usize) < bands {
+                    power[band as usize] += (re[k] * re[k] + im[k] * im[k]) as f64;
+                }
+            }
+            start += FRAME / 2;
+            analyzed += 1;
+        }
+    }
+    // By Parseval, half the energy of a windowed frame lands below the Nyquist bin
+    let energy: f64 = window.iter().map(|w| (w * w) as f64).sum::<f64>() * FRAME as f64 / 2.0;
+    let scale = 1.0 / (energy * analyzed.max(1) as f64);
+    power
+        .into_iter()
+        .map(|p| 10.0 * math::log10((p * scale).max(1e-30) as f32))
+        .collect()
+}
@@ -0,0 +1,70 @@
+use oxidizer::analysis;
+use oxidizer::{OxidationLevel, OxidizerConfig, Pipeline};
+
+#[test]
+fn test_loudness_of_reference_sine() {
+    // A 997 Hz sine at -20 dBFS in both channels measures -20 LUFS
+    let rate = 48000;
+    let amplitude = 10f32.powf(-20.0 / 20.0);
+    let samples: Vec<f32> = (0..rate * 5)
+        .flat_map(|i| {
+            let s = amplitude * (2.0 * std::f32::consts::PI * 997.0 * i as f32 / rate as f32).sin();
+            [s, s]
+        })
+        .collect();
+
+    let lufs = analysis::loudness(&samples, rate as u32);
+    assert!((lufs + 20.0).abs() < 0.1, "measured {} LUFS", lufs);
+}
+
+#[test]
+fn test_loudness_of_silence() {
+    assert_eq!(analysis::loudness(&[0.0; 96000], 48000), f32::NEG_INFINITY);
+}
+
+#[test]
+fn test_true_peak_finds_inter_sample_peaks() {
+    // A quarter-rate sine sampled 45 degrees off its crests never hits 1.0 on a sample
+    let samples: Vec<f32> = (0..4800)
+        .flat_map(|i| {
+            let s = (std::f32::consts::FRAC_PI_2 * i as f32 + std::f32::consts::FRAC_PI_4).sin();
+            [s, s]
+        })
+        .collect();
+
+    assert!(analysis::peak(&samples) < 0.71);
+    let true_peak = analysis::true_peak(&samples);
+    assert!((true_peak - 1.0).abs() < 0.05, "true peak {}", true_peak);
+}
+
+#[test]
+fn test_spectral_distance_grows_with_the_damage() {
+    let tone: Vec<f32> = (0..44100)
+        .flat_map(|i| {
+            let t = i as f32 / 44100.0;
+            let s = 0.3 * (std::f32::consts::TAU * 440.0 * t).sin()
+                + 0.1 * (std::f32::consts::TAU * 6000.0 * t).sin();
+            [s, s]
+        })
+        .collect();
+    assert_eq!(analysis::spectral_distance(&tone, &tone), 0.0);
+    assert_eq!(analysis::spectral_distance(&[0.0; 4096], &[0.0; 4096]), 0.0);
+
+    // A light and a heavy render of the same tone
+    let config = |level| OxidizerConfig {
+        level,
+        seed: Some(1),
+        ..Default::default()
+    };
+    let render = |level| {
+        Pipeline::new(config(level))
+            .process(tone.clone(), 44100)
+            .unwrap()
+    };
+    let light = analysis::spectral_distance(&render(OxidationLevel::Clear), &tone);
+    let heavy = analysis::spectral_distance(&render(OxidationLevel::Deep), &tone);
+    assert!(light > 0.0 && heavy > light, "{} then {}", light, heavy);
+
+    // Only the overlap counts
+    assert_eq!(analysis::spectral_distance(&tone, &tone[..44100]), 0.0);
+}
@@ -0,0 +1,98 @@
+use oxidizer::analysis::peak;
+use oxidizer::artifacts::{ArtifactEvent, ArtifactKind, Artifacts, RandomArtifacts};
+use oxidizer::{OxidizerConfig, Pipeline};
+
+#[test]
+fn test_random_artifacts_follow_the_seed() {
+    let artifacts = Artifacts {
+        events: vec![ArtifactEvent::new(ArtifactKind::Click, 5.0, 0.3)],
+        random: vec![RandomArtifacts::new(ArtifactKind::Pop, 3)],
+    };
+
+    let events = artifacts.schedule(60.0, 7);
+    assert_eq!(events, artifacts.schedule(60.0, 7));
+    assert_ne!(events, artifacts.schedule(60.0, 8));
+
+    assert_eq!(events.len(), 4);
+    assert!(events.windows(2).all(|w| w[0].time_secs <= w[1].time_secs));
+    let pops: Vec<_> = events
+        .iter()
+        .filter(|e| e.kind == ArtifactKind::Pop)
+        .collect();
+    assert_eq!(pops.len(), 3);
+    assert!(pops.iter().all(|e| (0.0..60.0).contains(&e.time_secs)));
+    assert!(pops.iter().all(|e| (0.2..=0.6).contains(&e.amplitude)));
+}
+
+#[test]
+fn test_artifacts_land_at_their_time() {
+    let mut samples = vec![0.0f32; 44100 * 2];
+    ArtifactEvent::new(ArtifactKind::Pop, 0.5, 0.8).apply(&mut samples, 44100);
+
+    // Silent before, a peak of the given amplitude at 0.5 s, and decayed soon after
+    assert_eq!(peak(&samples[..22050 * 2]), 0.0);
+    assert!((peak(&samples[22050 * 2..22100 * 2]) - 0.8).abs() < 0.05);
+    assert!(peak(&samples[24000 * 2..]) < 0.01);
+
+    // Past the end, nothing happens
+    let mut short = vec![0.0f32; 200];
+    ArtifactEvent::new(ArtifactKind::Click, 1.0, 0.8).apply(&mut short, 44100);
+    assert!(short.iter().all(|&s| s == 0.0));
+}
+
+#[test]
+fn test_needle_skip_replays_the_last_revolution() {
+    // A slow ramp, so every position has its own value
+    let mut samples: Vec<f32> = (0..44100 * 5).flat_map(|i| [i as f32, i as f32]).collect();
+    ArtifactEvent::new(ArtifactKind::NeedleSkip, 2.0, 0.0).apply(&mut samples, 44100);
+
+    // A second into the skip, the audio is what played 1.8 s earlier
+    let frame = 44100 * 3;
+    assert_eq!(samples[frame * 2], (frame - 79380) as f32);
+    // After the revolution, the original carries on
+    assert_eq!(samples[44100 * 4 * 2], (44100 * 4) as f32);
+}
+
+#[test]
+fn test_pipeline_adds_the_artifacts() {
+    let config = OxidizerConfig {
+        intensity: 0.0,
+        artifacts: Artifacts::fixed(vec![ArtifactEvent::new(ArtifactKind::Click, 0.1, 0.5)]),
+        seed: Some(1),
+        ..Default::default()
+    };
+
+    let output = Pipeline::new(config)
+        .process(vec![0.0; 44100], 44100)
+        .unwrap();
+    // Silence with a single click, normalized
+    assert_eq!(peak(&output[..4410 * 2]), 0.0);
+    assert!(peak(&output) > 0.9);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_artifacts_in_config() {
+    let config: OxidizerConfig = toml::from_str(
+        r#"
+        [[artifacts.events]]
+        kind = "needle-skip"
+        time_secs = 95.0
+        amplitude = 0.7
+
+        [[artifacts.random]]
+        kind = "click"
+        count = 20
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.artifacts.events,
+        vec![ArtifactEvent::new(ArtifactKind::NeedleSkip, 95.0, 0.7)]
+    );
+    assert_eq!(
+        config.artifacts.random,
+        vec![RandomArtifacts::new(ArtifactKind::Click, 20)]
+    );
+}
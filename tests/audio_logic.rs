@@ -1,5 +1,5 @@
-use oxidizer::processor::noise::BrownianNoise;
-use oxidizer::{OxidationLevel, Oxidizer};
+use oxidizer::processor::noise::{self, BrownianNoise, CrackleNoise, NoiseGenerator, WhiteNoise};
+use oxidizer::{NoiseConfig, OxidationLevel, Oxidizer, OxidizerConfig, OxidizerError, Pipeline};
 
 #[test]
 fn test_stereo_integrity() {
@@ -42,3 +42,116 @@ fn test_conversion_edge_cases() {
         assert!(sample.is_finite());
     }
 }
+
+#[test]
+fn test_out_of_range_parameters_are_rejected() {
+    assert!(BrownianNoise::new(0.98, 0.1).is_ok());
+    assert!(matches!(
+        BrownianNoise::new(1.0, 0.1),
+        Err(OxidizerError::InvalidParameter {
+            name: "damping",
+            ..
+        })
+    ));
+    assert!(BrownianNoise::new(0.5, f32::NAN).is_err());
+
+    let invalid = [
+        OxidizerConfig {
+            intensity: 1.5,
+            ..Default::default()
+        },
+        OxidizerConfig {
+            passes: 10_000,
+            ..Default::default()
+        },
+        OxidizerConfig {
+            noise: NoiseConfig::Brown {
+                damping: -0.1,
+                step: 0.1,
+            },
+            ..Default::default()
+        },
+    ];
+    for config in invalid {
+        assert!(config.validate().is_err());
+        assert!(Pipeline::new(config).process(vec![0.0; 64], 44100).is_err());
+    }
+    assert!(OxidizerConfig::default().validate().is_ok());
+}
+
+#[test]
+fn test_noise_intensity_is_clamped() {
+    let render = |intensity| {
+        let mut noise = WhiteNoise::default();
+        noise.reseed(4);
+        Oxidizer::new(noise)
+            .consume(vec![0.0; 1000])
+            .apply_noise_texture(intensity)
+            .collect_samples()
+    };
+    assert_eq!(render(25.0), render(1.0));
+    assert!(render(-3.0).iter().all(|&s| s == 0.0));
+}
+
+#[test]
+fn test_crackle_is_sparse() {
+    let mut crackle = CrackleNoise::new(0.001).unwrap();
+    crackle.reseed(9);
+    let samples = noise::render(&mut crackle, 44100);
+
+    // Each tick dies away within a few samples, leaving near silence in between
+    let quiet = samples.iter().filter(|s| s.abs() < 1e-3).count();
+    assert!(quiet > samples.len() * 9 / 10, "{} quiet samples", quiet);
+    assert!(samples.iter().any(|s| s.abs() > 0.1));
+    assert!(samples.iter().all(|s| s.abs() <= 1.0));
+
+    assert!(CrackleNoise::new(1.5).is_err());
+}
+
+// Pink noise from Paul Kellet's economy filter over white noise, as a custom generator.
+struct PinkNoise {
+    white: WhiteNoise,
+    poles: [f32; 3],
+}
+
+impl NoiseGenerator for PinkNoise {
+    fn next_sample(&mut self) -> f32 {
+        let white = self.white.next_sample();
+        self.poles[0] = 0.99765 * self.poles[0] + white * 0.0990460;
+        self.poles[1] = 0.96300 * self.poles[1] + white * 0.2965164;
+        self.poles[2] = 0.57000 * self.poles[2] + white * 1.0526913;
+        (self.poles.iter().sum::<f32>() + white * 0.1848) * 0.2
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.white.reseed(seed);
+        self.poles = [0.0; 3];
+    }
+}
+
+#[test]
+fn test_profile_measures_the_color() {
+    let mut white = WhiteNoise::default();
+    white.reseed(1);
+    let profile = noise::profile(&mut white, 10.0, 44100);
+    assert!(profile.slope_db_per_octave.abs() < 0.5, "{:?}", profile);
+    // Uniform on ±1.0
+    assert!(
+        (profile.rms - 1.0 / 3.0f32.sqrt()).abs() < 0.01,
+        "{:?}",
+        profile
+    );
+
+    let mut pink = PinkNoise {
+        white: WhiteNoise::default(),
+        poles: [0.0; 3],
+    };
+    pink.reseed(1);
+    let slope = noise::profile(&mut pink, 10.0, 44100).slope_db_per_octave;
+    assert!((slope + 3.0).abs() < 0.5, "{}", slope);
+
+    let mut brown = BrownianNoise::default();
+    brown.reseed(1);
+    let slope = noise::profile(&mut brown, 10.0, 44100).slope_db_per_octave;
+    assert!(slope < -4.0, "{}", slope);
+}
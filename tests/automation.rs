@@ -0,0 +1,53 @@
+use oxidizer::automation::{Automation, Interpolation, Timing};
+use oxidizer::{NoiseConfig, Normalization, OxidizerConfig, Pipeline};
+
+#[test]
+fn test_automation_interpolates_between_breakpoints() {
+    let curve = Automation::new(
+        vec![(2.0, 1.0), (1.0, 0.0), (3.0, 0.0)],
+        Interpolation::Linear,
+        Timing::Seconds,
+    )
+    .unwrap();
+
+    // Holds the outer values and ramps in between, whatever the breakpoint order
+    assert_eq!(curve.value_at(0.0, 10.0), 0.0);
+    assert_eq!(curve.value_at(1.5, 10.0), 0.5);
+    assert_eq!(curve.value_at(2.0, 10.0), 1.0);
+    assert_eq!(curve.value_at(2.75, 10.0), 0.25);
+    assert_eq!(curve.value_at(9.0, 10.0), 0.0);
+
+    let ramp = Automation::ramp(0.0, 1.0).unwrap();
+    assert_eq!(ramp.value_at(5.0, 10.0), 0.5);
+
+    assert!(Automation::new(Vec::new(), Interpolation::Step, Timing::Seconds).is_err());
+    assert!(Automation::new(vec![(1.5, 0.0)], Interpolation::Step, Timing::Fraction).is_err());
+}
+
+#[test]
+fn test_intensity_automation_follows_the_curve() {
+    let rate = 44100;
+    let mut config = OxidizerConfig {
+        noise: NoiseConfig::White,
+        normalization: Normalization::None,
+        passes: 0,
+        seed: Some(1),
+        ..Default::default()
+    };
+    // Silent for the first half, then full intensity
+    config.automation.intensity = Some(
+        Automation::new(
+            vec![(0.0, 0.0), (0.5, 1.0)],
+            Interpolation::Step,
+            Timing::Fraction,
+        )
+        .unwrap(),
+    );
+
+    let output = Pipeline::new(config)
+        .process(vec![0.0; rate * 2], rate as u32)
+        .unwrap();
+    let (first, second) = output.split_at(rate);
+    assert!(first.iter().all(|&s| s == 0.0));
+    assert!(second.iter().any(|&s| s != 0.0));
+}
@@ -0,0 +1,102 @@
+use oxidizer::OxidizerError;
+use oxidizer::batch::{self, OverwritePolicy};
+use std::path::PathBuf;
+
+#[test]
+fn test_plan_outputs_applies_overwrite_policy() {
+    let dir = std::env::temp_dir().join("oxidizer_plan_outputs");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("song_oxidized.wav"), b"").unwrap();
+
+    // Two inputs sharing a stem map to the same output name
+    let inputs = [PathBuf::from("a/song.mp3"), PathBuf::from("b/song.flac")];
+    let plan = |policy| batch::plan_outputs(&inputs, &dir, batch::DEFAULT_TEMPLATE, policy);
+
+    assert!(matches!(
+        plan(OverwritePolicy::Refuse),
+        Err(OxidizerError::OutputExists(_))
+    ));
+    assert_eq!(
+        plan(OverwritePolicy::Rename).unwrap(),
+        [
+            dir.join("song_oxidized_1.wav"),
+            dir.join("song_oxidized_2.wav")
+        ]
+    );
+    assert_eq!(
+        plan(OverwritePolicy::Overwrite).unwrap(),
+        [
+            dir.join("song_oxidized.wav"),
+            dir.join("song_oxidized_1.wav")
+        ]
+    );
+
+    // Only the inputs given their own policy are overwritten
+    let policy = |input: &std::path::Path| match input.starts_with("a") {
+        true => OverwritePolicy::Overwrite,
+        false => OverwritePolicy::Refuse,
+    };
+    assert_eq!(
+        batch::plan_outputs_with(&inputs[..1], &dir, batch::DEFAULT_TEMPLATE, policy).unwrap(),
+        [dir.join("song_oxidized.wav")]
+    );
+    assert!(batch::plan_outputs_with(&inputs[1..], &dir, batch::DEFAULT_TEMPLATE, policy).is_err());
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_manifest_records_finished_inputs() {
+    use oxidizer::OxidizerConfig;
+    use oxidizer::batch::Manifest;
+    use oxidizer::io::TimeRange;
+    use std::time::Duration;
+
+    let dir = std::env::temp_dir().join("oxidizer_manifest");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let (input, output) = (dir.join("song.wav"), dir.join("song_oxidized.wav"));
+    std::fs::write(&input, b"").unwrap();
+
+    let config = OxidizerConfig::default();
+    let hash = batch::config_hash(&config);
+    assert_eq!(hash, batch::config_hash(&config.clone()));
+    let changed = batch::config_hash(&OxidizerConfig {
+        intensity: 0.5,
+        ..config.clone()
+    });
+    assert_ne!(hash, changed);
+
+    // The range, the noise-only flag and the output template change the outputs too
+    let run = |range, noise_only, template| batch::run_hash(&config, range, noise_only, template);
+    let whole = run(TimeRange::default(), false, batch::DEFAULT_TEMPLATE);
+    assert_eq!(
+        whole,
+        run(TimeRange::default(), false, batch::DEFAULT_TEMPLATE)
+    );
+    let first = TimeRange::new(Duration::ZERO, Some(Duration::from_secs(1)));
+    assert_ne!(whole, run(first, false, batch::DEFAULT_TEMPLATE));
+    assert_ne!(
+        whole,
+        run(TimeRange::default(), true, batch::DEFAULT_TEMPLATE)
+    );
+    assert_ne!(whole, run(TimeRange::default(), false, "{stem}.wav"));
+
+    let mut manifest = Manifest::load(&dir).unwrap();
+    assert_eq!(manifest, Manifest::default());
+    manifest.record(&input, &output, &hash);
+    manifest.save(&dir).unwrap();
+
+    let manifest = Manifest::load(&dir).unwrap();
+    // The output has to be there, rendered with the same settings
+    assert!(!manifest.is_done(&input, &hash));
+    std::fs::write(&output, b"").unwrap();
+    assert!(manifest.is_done(&input, &hash));
+    assert!(!manifest.is_done(&input, &changed));
+    assert!(!manifest.is_done(&dir.join("other.wav"), &hash));
+    assert!(manifest.contains(&input));
+    assert!(!manifest.contains(&dir.join("other.wav")));
+
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,108 @@
+use oxidizer::analysis::rms;
+use oxidizer::codec::{Companding, LossyCodec, TelephoneLine};
+
+// A stereo sine at `hz`, one second at 44.1 kHz.
+fn tone(hz: f32, level: f32) -> Vec<f32> {
+    (0..44100)
+        .flat_map(|i| {
+            let s = level * (std::f32::consts::TAU * hz * i as f32 / 44100.0).sin();
+            [s, s]
+        })
+        .collect()
+}
+
+#[test]
+fn test_companding_keeps_the_error_proportional() {
+    for law in Companding::ALL {
+        assert_eq!(law.round_trip(0.0), 0.0);
+        assert!((law.round_trip(1.0) - 1.0).abs() < 1e-4);
+        assert_eq!(law.round_trip(-0.5), -law.round_trip(0.5));
+        // Clipped like the codec's full scale
+        assert_eq!(law.round_trip(3.0), law.round_trip(1.0));
+
+        // Loud and quiet samples come back within a few percent of their level
+        for level in [0.9f32, 0.1, 0.01] {
+            let error = (law.round_trip(level) - level).abs() / level;
+            assert!(error < 0.05, "{}: {} off by {}", law.name(), level, error);
+        }
+    }
+    assert_ne!(
+        Companding::MuLaw.round_trip(0.3),
+        Companding::ALaw.round_trip(0.3)
+    );
+}
+
+#[test]
+fn test_telephone_line_keeps_the_voice_band() {
+    let line = TelephoneLine::default();
+    let gain = |hz: f32| {
+        let mut samples = tone(hz, 0.5);
+        line.process(&mut samples, 44100);
+        assert_eq!(samples.len(), 88200);
+        20.0 * (rms(&samples[8820..]) / rms(&tone(hz, 0.5)[8820..])).log10()
+    };
+
+    assert!(gain(1000.0).abs() < 1.0, "{} dB", gain(1000.0));
+    assert!(gain(100.0) < -15.0, "{} dB", gain(100.0));
+    assert!(gain(8000.0) < -30.0, "{} dB", gain(8000.0));
+}
+
+#[test]
+fn test_lossy_codec_cuts_the_treble_and_keeps_the_tone() {
+    let codec = LossyCodec::at_bitrate(32);
+    assert!(codec.cutoff_hz < 10000.0);
+
+    // A loud tone well inside the band survives, give or take a quantization step
+    let mut low = tone(1000.0, 0.5);
+    let level = rms(&low);
+    codec.process(&mut low, 44100);
+    let ratio = rms(&low[8820..79380]) / level;
+    assert!((0.8..1.25).contains(&ratio), "tone came back at {}", ratio);
+
+    // Content above the cutoff is gone
+    let mut high = tone(12000.0, 0.5);
+    codec.process(&mut high, 44100);
+    assert!(rms(&high) < 0.005, "treble left at {}", rms(&high));
+}
+
+#[test]
+fn test_lossy_codec_drops_quiet_partials() {
+    let codec = LossyCodec::at_bitrate(64);
+    let loud = tone(1000.0, 0.5);
+    let quiet = tone(3000.0, 0.5 * 10f32.powf(-60.0 / 20.0));
+    let mut mixed: Vec<f32> = loud.iter().zip(&quiet).map(|(a, b)| a + b).collect();
+    codec.process(&mut mixed, 44100);
+
+    // What's left beside the loud tone is far weaker than the quiet one was
+    let mut reference = loud.clone();
+    codec.process(&mut reference, 44100);
+    let residue: Vec<f32> = mixed.iter().zip(&reference).map(|(a, b)| a - b).collect();
+    assert!(
+        rms(&residue) < 0.1 * rms(&quiet),
+        "quiet partial kept: {}",
+        rms(&residue)
+    );
+}
+
+#[test]
+fn test_lossy_codec_smears_an_attack_ahead_of_itself() {
+    let codec = LossyCodec::at_bitrate(32);
+    // Silence, then a loud burst of noise halfway through
+    let mut state = 1u32;
+    let mut samples: Vec<f32> = (0..44100)
+        .flat_map(|i| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            let s = match i >= 22050 {
+                true => (state >> 8) as f32 / (1 << 24) as f32 - 0.5,
+                false => 0.0,
+            };
+            [s, s]
+        })
+        .collect();
+    codec.process(&mut samples, 44100);
+
+    // Quantization noise shows up in the frame before the attack
+    let before = &samples[2 * (22050 - 1024)..2 * 22050];
+    assert!(rms(before) > 1e-4, "no pre-echo: {}", rms(before));
+    assert!(rms(&samples[..2 * 20000]) == 0.0);
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "serde")]
+
+use oxidizer::automation::Automation;
+use oxidizer::io::BitDepth;
+use oxidizer::{ChannelConfig, NoiseConfig, Normalization, OxidationLevel, OxidizerConfig};
+
+#[test]
+fn test_config_round_trip() {
+    let mut config = OxidizerConfig {
+        level: OxidationLevel::Muffled,
+        passes: 4,
+        noise: NoiseConfig::White,
+        normalization: Normalization::None,
+        bit_depth: BitDepth::Float32,
+        seed: Some(42),
+        ..Default::default()
+    };
+    config.automation.cutoff = Some(Automation::ramp(8000.0, 400.0).unwrap());
+
+    for name in ["oxidizer_round_trip.toml", "oxidizer_round_trip.json"] {
+        let path = std::env::temp_dir().join(name);
+        config.save(&path).unwrap();
+        assert_eq!(OxidizerConfig::load(&path).unwrap(), config);
+    }
+}
+
+#[test]
+fn test_partial_config_keeps_defaults() {
+    let path = std::env::temp_dir().join("oxidizer_partial.toml");
+    std::fs::write(
+        &path,
+        "level = \"clear\"\n\n[noise]\ntype = \"brown\"\ndamping = 0.9\nstep = 0.2\n",
+    )
+    .unwrap();
+
+    let config = OxidizerConfig::load(&path).unwrap();
+    assert_eq!(config.level, OxidationLevel::Clear);
+    assert_eq!(
+        config.noise,
+        NoiseConfig::Brown {
+            damping: 0.9,
+            step: 0.2
+        }
+    );
+    assert_eq!(config.passes, OxidizerConfig::default().passes);
+}
+
+#[test]
+fn test_level_amount_in_config() {
+    let path = std::env::temp_dir().join("oxidizer_amount.toml");
+    std::fs::write(&path, "level = { amount = 0.45 }\n").unwrap();
+    let config = OxidizerConfig::load(&path).unwrap();
+    assert_eq!(config.level, OxidationLevel::amount(0.45));
+
+    config.save(&path).unwrap();
+    assert_eq!(OxidizerConfig::load(&path).unwrap(), config);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn test_channels_in_config() {
+    let path = std::env::temp_dir().join("oxidizer_channels.toml");
+    std::fs::write(
+        &path,
+        "[[channels]]\ncutoff_octaves = -1.0\nnoise_db = 6.0\n\n[[channels]]\n",
+    )
+    .unwrap();
+    let config = OxidizerConfig::load(&path).unwrap();
+    let left = ChannelConfig {
+        cutoff_octaves: -1.0,
+        noise_db: 6.0,
+    };
+    assert_eq!(config.channels, Some([left, ChannelConfig::default()]));
+
+    config.save(&path).unwrap();
+    assert_eq!(OxidizerConfig::load(&path).unwrap(), config);
+    let _ = std::fs::remove_file(path);
+}
@@ -0,0 +1,181 @@
+use oxidizer::analysis::{peak, rms, to_dbfs};
+use oxidizer::dynamics::{Compressor, Expander, Gate, Limiter, NoiseDucking, TransientShaper};
+use oxidizer::processor::IntensityCurve;
+use oxidizer::processor::noise::WhiteNoise;
+use oxidizer::{Normalization, Oxidizer, OxidizerConfig, Pipeline};
+
+// A stereo sine at `level` (linear), `seconds` long at 44.1 kHz.
+fn tone(level: f32, seconds: f32) -> Vec<f32> {
+    (0..(44100.0 * seconds) as usize)
+        .flat_map(|i| {
+            let s = level * (i as f32 * 0.05).sin();
+            [s, s]
+        })
+        .collect()
+}
+
+#[test]
+fn test_compressor_reduces_level_above_threshold() {
+    let compressor = Compressor {
+        threshold_db: -20.0,
+        ratio: 4.0,
+        ..Default::default()
+    };
+
+    // 14 dB over the threshold comes out about 3.5 dB over it once the attack has
+    // settled (the gain relaxes a little between the peaks of the wave)
+    let mut loud = tone(from_db(-6.0), 1.0);
+    compressor.process(&mut loud, 44100);
+    assert!((to_dbfs(peak(&loud[44100..])) + 16.5).abs() < 1.5);
+
+    // Below the threshold nothing changes
+    let mut quiet = tone(from_db(-30.0), 0.5);
+    let original = quiet.clone();
+    compressor.process(&mut quiet, 44100);
+    assert_eq!(quiet, original);
+
+    // Makeup gain is applied on top
+    let mut quiet = tone(from_db(-30.0), 0.5);
+    Compressor {
+        makeup_db: 6.0,
+        ..compressor
+    }
+    .process(&mut quiet, 44100);
+    assert!((to_dbfs(peak(&quiet)) + 24.0).abs() < 0.1);
+}
+
+#[test]
+fn test_expander_pushes_quiet_parts_down() {
+    let expander = Expander {
+        threshold_db: -20.0,
+        ratio: 2.0,
+        ..Default::default()
+    };
+
+    // 10 dB under the threshold ends up about 20 dB under it
+    let mut quiet = tone(from_db(-30.0), 1.0);
+    expander.process(&mut quiet, 44100);
+    assert!((to_dbfs(peak(&quiet[44100..])) + 40.0).abs() < 1.5);
+
+    // Above the threshold it stays (mostly) open
+    let mut loud = tone(from_db(-6.0), 1.0);
+    expander.process(&mut loud, 44100);
+    assert!((to_dbfs(peak(&loud[44100..])) + 6.0).abs() < 0.5);
+}
+
+#[test]
+fn test_transient_shaper_boosts_onsets() {
+    // Decaying hits, four per second
+    let hits: Vec<f32> = (0..44100)
+        .flat_map(|i| {
+            let t = (i % 11025) as f32 / 44100.0;
+            let s = 0.5 * (-t * 20.0).exp() * (i as f32 * 0.3).sin();
+            [s, s]
+        })
+        .collect();
+    let onset = 11025 * 2..11025 * 2 + 400;
+    let tail = 11025 * 2 + 4000..11025 * 2 + 8000;
+
+    let mut shaped = hits.clone();
+    TransientShaper::new(6.0, -6.0).process(&mut shaped, 44100);
+    assert!(peak(&shaped[onset.clone()]) > 1.5 * peak(&hits[onset]));
+    assert!(peak(&shaped[tail.clone()]) < 0.7 * peak(&hits[tail]));
+
+    // No gains, no change
+    let mut neutral = hits.clone();
+    TransientShaper::default().process(&mut neutral, 44100);
+    assert_eq!(neutral, hits);
+}
+
+#[test]
+fn test_limiter_holds_the_ceiling() {
+    let limiter = Limiter {
+        ceiling_db: -3.0,
+        ..Default::default()
+    };
+    let mut loud = tone(1.5, 0.5);
+    limiter.process(&mut loud, 44100);
+    assert!(to_dbfs(peak(&loud)) <= -3.0 + 1e-4);
+}
+
+fn from_db(db: f32) -> f32 {
+    oxidizer::analysis::from_dbfs(db)
+}
+
+#[test]
+fn test_gate_silences_below_threshold_after_hold() {
+    let gate = Gate::new(-40.0, 0.0, 0.0, 10.0);
+    // Half a second of tone, then half a second of silence in the key
+    let mut key = tone(0.5, 0.5);
+    key.extend(vec![0.0; 44100]);
+    let mut hiss = vec![0.01; key.len()];
+    gate.process(&mut hiss, &key, 44100);
+
+    // Open from the first peak of the tone on
+    assert_eq!(hiss[10..44100], vec![0.01; 44090][..]);
+    // Held open for 10 ms into the silence, then closed
+    assert_eq!(hiss[44100 + 400], 0.01);
+    assert!(hiss[44100 + 1000..].iter().all(|&s| s < 1e-5));
+}
+
+#[test]
+fn test_self_keyed_gate_matches_keying_by_a_copy() {
+    let gate = Gate::new(-30.0, 1.0, 20.0, 5.0);
+    let mut signal = tone(0.3, 0.2);
+    signal.extend(tone(0.001, 0.2));
+    let key = signal.clone();
+
+    let mut keyed = signal.clone();
+    gate.process(&mut keyed, &key, 44100);
+    gate.process_self_keyed(&mut signal, 44100);
+    assert_eq!(signal, keyed);
+}
+
+#[test]
+fn test_output_gate_mutes_hiss_over_silence() {
+    let input = vec![0.0; 44100];
+    let render = |gate: Option<Gate>| {
+        let config = OxidizerConfig {
+            gate,
+            seed: Some(1),
+            normalization: Normalization::None,
+            ..Default::default()
+        };
+        Pipeline::new(config).process(input.clone(), 44100).unwrap()
+    };
+
+    assert!(peak(&render(None)) > 1e-3);
+    assert!(peak(&render(Some(Gate::default()))) < 1e-4);
+}
+
+#[test]
+fn test_ducking_pushes_the_noise_behind_the_music() {
+    // A second of music, then a second of silence
+    let mut program = tone(0.5, 1.0);
+    program.extend(vec![0.0; 88200]);
+    let noise = |ducking: Option<NoiseDucking>| {
+        let noisy = Oxidizer::new(WhiteNoise::default())
+            .set_intensity_curve(IntensityCurve::Linear)
+            .set_noise_ducking(ducking, 44100)
+            .consume(program.clone())
+            .add_noise_frames(0.1, 0..88200)
+            .collect_samples();
+        let added: Vec<f32> = noisy.iter().zip(&program).map(|(n, p)| n - p).collect();
+        // Once the envelope has settled: under the music, and after the release
+        let level = |range: std::ops::Range<usize>| to_dbfs(rms(&added[range]));
+        (level(8820..88200), level(132300..176400))
+    };
+
+    let (music, gap) = noise(None);
+    assert!((music - gap).abs() < 0.5);
+
+    let (music, gap) = noise(Some(NoiseDucking {
+        release_ms: 100.0,
+        ..Default::default()
+    }));
+    assert!(
+        (gap - music - 12.0).abs() < 1.0,
+        "{} dB under the music",
+        gap - music
+    );
+}
@@ -0,0 +1,105 @@
+use oxidizer::analysis::rms;
+use oxidizer::eq::{BandKind, EqBand, Riaa};
+use oxidizer::{Normalization, OxidationLevel, OxidizerConfig, Pipeline};
+
+// A stereo sine at `hz`, one second at 44.1 kHz.
+fn tone(hz: f32) -> Vec<f32> {
+    (0..44100)
+        .flat_map(|i| {
+            let s = 0.1 * (std::f32::consts::TAU * hz * i as f32 / 44100.0).sin();
+            [s, s]
+        })
+        .collect()
+}
+
+#[test]
+fn test_riaa_follows_the_standard_curve() {
+    // Playback gains from the RIAA table, relative to 1 kHz
+    let table = [
+        (20.0, 19.27),
+        (100.0, 13.09),
+        (1000.0, 0.0),
+        (10000.0, -13.73),
+    ];
+    for (hz, expected) in table {
+        let response = Riaa::DeEmphasis.response_db(hz, 44100);
+        assert!(
+            (response - expected).abs() < 1.0,
+            "{} Hz: {} dB",
+            hz,
+            response
+        );
+        assert!((Riaa::PreEmphasis.response_db(hz, 44100) + response).abs() < 1e-3);
+    }
+
+    // The filter does what the response says
+    let mut low = tone(100.0);
+    Riaa::DeEmphasis.process(&mut low, 44100);
+    let gain = 20.0 * (rms(&low[8820..]) / rms(&tone(100.0))).log10();
+    assert!((gain - 13.09).abs() < 0.5, "{} dB", gain);
+}
+
+#[test]
+fn test_riaa_curves_cancel_out() {
+    let input: Vec<f32> = (0..8820)
+        .map(|i| ((i * 7919) % 200) as f32 / 1000.0 - 0.1)
+        .collect();
+
+    let mut samples = input.clone();
+    Riaa::PreEmphasis.process(&mut samples, 44100);
+    Riaa::DeEmphasis.process(&mut samples, 44100);
+
+    for (out, original) in samples.iter().zip(&input) {
+        assert!((out - original).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn test_eq_bands_shape_the_response() {
+    let high_pass = EqBand::pass(BandKind::HighPass, 200.0);
+    assert!((high_pass.response_db(200.0, 44100) + 3.01).abs() < 0.1);
+    assert!(high_pass.response_db(50.0, 44100) < -20.0);
+    assert!(high_pass.response_db(5000.0, 44100).abs() < 0.1);
+
+    let low_pass = EqBand::pass(BandKind::LowPass, 4000.0);
+    assert!((low_pass.response_db(4000.0, 44100) + 3.01).abs() < 0.1);
+    assert!(low_pass.response_db(16000.0, 44100) < -20.0);
+
+    let peak = EqBand::peak(1250.0, 6.0, 4.0);
+    assert!((peak.response_db(1250.0, 44100) - 6.0).abs() < 0.01);
+    assert!(peak.response_db(300.0, 44100).abs() < 0.5);
+
+    // The filter does what the response says
+    let mut boosted = tone(1250.0);
+    peak.process(&mut boosted, 44100);
+    let gain = 20.0 * (rms(&boosted[8820..]) / rms(&tone(1250.0))).log10();
+    assert!((gain - 6.0).abs() < 0.2, "{} dB", gain);
+}
+
+#[test]
+fn test_config_response_matches_the_render() {
+    let config = OxidizerConfig {
+        level: OxidationLevel::Dusty,
+        passes: 2,
+        intensity: 0.0,
+        mix: 0.7,
+        eq: vec![EqBand::peak(1250.0, 6.0, 2.0)],
+        normalization: Normalization::None,
+        ..Default::default()
+    };
+    let frequencies = [100.0, 1250.0, 5000.0];
+    let response = config.response_at(&frequencies);
+
+    let pipeline = Pipeline::new(config);
+    for (hz, expected) in frequencies.into_iter().zip(response) {
+        let output = pipeline.process(tone(hz), 44100).unwrap();
+        let gain = 20.0 * (rms(&output[8820..]) / rms(&tone(hz)[8820..])).log10();
+        assert!(
+            (gain - expected).abs() < 0.1,
+            "{} Hz: {} dB, rendered {} dB",
+            hz,
+            expected,
+            gain
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use oxidizer::fft;
+
+#[test]
+fn test_inverse_undoes_forward() {
+    let original: Vec<f32> = (0..256)
+        .map(|i| ((i * 37 % 101) as f32 / 50.0) - 1.0)
+        .collect();
+    let (mut re, mut im) = (original.clone(), vec![0.0; 256]);
+    fft::forward(&mut re, &mut im);
+    fft::inverse(&mut re, &mut im);
+    for (a, b) in re.iter().zip(&original) {
+        assert!((a - b).abs() < 1e-4, "{} came back as {}", b, a);
+    }
+    assert!(im.iter().all(|x| x.abs() < 1e-4));
+}
+
+#[test]
+fn test_sine_lands_in_its_bin() {
+    let (mut re, mut im): (Vec<f32>, _) = (
+        (0..64)
+            .map(|i| (std::f32::consts::TAU * 5.0 * i as f32 / 64.0).sin())
+            .collect(),
+        vec![0.0; 64],
+    );
+    fft::forward(&mut re, &mut im);
+    let magnitude = |k: usize| (re[k] * re[k] + im[k] * im[k]).sqrt();
+    assert!((magnitude(5) - 32.0).abs() < 1e-3);
+    assert!((magnitude(59) - 32.0).abs() < 1e-3);
+    for k in (0..64).filter(|k| *k != 5 && *k != 59) {
+        assert!(magnitude(k) < 1e-3, "bin {} holds {}", k, magnitude(k));
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_rejects_lengths_that_arent_powers_of_two() {
+    fft::forward(&mut [0.0; 6], &mut [0.0; 6]);
+}
@@ -0,0 +1,72 @@
+use oxidizer::analysis::rms;
+use oxidizer::hum::GroundLoop;
+
+// A 1 kHz tone, one second at 44.1 kHz, at the given level in both channels.
+fn tone(level: f32) -> Vec<f32> {
+    (0..44100)
+        .flat_map(|i| {
+            let s = (std::f32::consts::TAU * 1000.0 * i as f32 / 44100.0).sin() * level;
+            [s, s]
+        })
+        .collect()
+}
+
+// The hum added to `samples`, as one channel past the first 0.1 s.
+fn hum_of(ground_loop: &GroundLoop, samples: &[f32]) -> Vec<f32> {
+    let mut hummed = samples.to_vec();
+    ground_loop.process(&mut hummed, 44100);
+    hummed
+        .iter()
+        .zip(samples)
+        .step_by(2)
+        .skip(4410)
+        .map(|(hummed, dry)| hummed - dry)
+        .collect()
+}
+
+#[test]
+fn test_ground_loop_follows_the_program() {
+    let ground_loop = GroundLoop::default();
+    assert!(
+        hum_of(&ground_loop, &vec![0.0; 44100 * 2])
+            .iter()
+            .all(|&s| s == 0.0)
+    );
+
+    // Ten times the level, ten times the hum
+    let loud = rms(&hum_of(&ground_loop, &tone(0.5)));
+    let quiet = rms(&hum_of(&ground_loop, &tone(0.05)));
+    assert!((loud / quiet - 10.0).abs() < 0.5, "ratio {}", loud / quiet);
+    // And peaks about 30 dB under the envelope of the program
+    let peak = hum_of(&ground_loop, &tone(0.5))
+        .iter()
+        .fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!(
+        (20.0 * (peak / 0.5).log10() + 30.0).abs() < 1.0,
+        "peak {}",
+        peak
+    );
+}
+
+#[test]
+fn test_ground_loop_hums_at_the_mains_frequency() {
+    let ground_loop = GroundLoop {
+        frequency_hz: 60.0,
+        tilt_db: -120.0,
+        ..Default::default()
+    };
+    let hum = hum_of(&ground_loop, &tone(0.5));
+
+    // With the harmonics tilted away, the hum is a pure 60 Hz sine
+    let power = |hz: f32| {
+        let (mut re, mut im) = (0.0f32, 0.0f32);
+        for (i, s) in hum.iter().enumerate() {
+            let phase = std::f32::consts::TAU * hz * i as f32 / 44100.0;
+            re += s * phase.cos();
+            im += s * phase.sin();
+        }
+        re.hypot(im)
+    };
+    assert!(power(60.0) > 100.0 * power(120.0));
+    assert!(power(60.0) > 100.0 * power(50.0));
+}
@@ -0,0 +1,229 @@
+use oxidizer::io::{self, AudioFormat, BitDepth, TimeRange};
+use oxidizer::{OxidizerConfig, OxidizerError, Pipeline};
+use std::path::Path;
+use std::time::Duration;
+
+const INPUT: &str = "assets/audio/original.mp3";
+
+#[test]
+fn test_range_decodes_requested_window() {
+    let path = Path::new(INPUT);
+    let rate = io::probe(path).unwrap().sample_rate.unwrap() as usize;
+
+    let range = TimeRange::new(Duration::from_secs(2), Some(Duration::from_millis(1500)));
+    let window = io::load_audio_range(path, range).unwrap();
+    assert_eq!(window.len(), rate * 3 / 2 * 2);
+
+    // Seeking must land on the same samples a full decode has at that position
+    let full = io::load_audio(path).unwrap();
+    let offset = rate * 2 * 2;
+    assert_eq!(window[..], full[offset..offset + window.len()]);
+}
+
+#[test]
+fn test_inspect_reads_headers() {
+    let details = io::inspect(Path::new(INPUT)).unwrap();
+    assert_eq!(details.container, Some("MP3"));
+    assert_eq!(details.stream, io::probe(Path::new(INPUT)).unwrap());
+    assert_eq!(details.bit_depth, None);
+    assert!(details.codec.contains("Layer 3"));
+    assert!(
+        details
+            .tags
+            .iter()
+            .any(|(_, value)| value.starts_with("Lavf"))
+    );
+
+    let path = std::env::temp_dir().join("oxidizer_inspect.wav");
+    io::save_audio_as(
+        &path,
+        vec![0.0; 2000],
+        22050,
+        AudioFormat::Wav,
+        BitDepth::Int24,
+        |_, _| Ok(()),
+    )
+    .unwrap();
+    let details = io::inspect(&path).unwrap();
+    assert_eq!(details.container, Some("WAV"));
+    assert_eq!(details.bit_depth, Some(24));
+    assert_eq!(details.stream.sample_rate, Some(22050));
+    assert_eq!(details.stream.frames, Some(1000));
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn test_range_past_end_is_cut_short() {
+    let path = Path::new(INPUT);
+    let range = TimeRange::new(Duration::from_secs(24 * 3600), None);
+    assert!(io::load_audio_range(path, range).unwrap().is_empty());
+}
+
+#[test]
+fn test_flac_round_trip_is_lossless() {
+    let mut samples = io::load_audio(Path::new(INPUT)).unwrap();
+    // An odd length leaves a short final block
+    samples.truncate(2 * 100_001);
+
+    for (bit_depth, scale) in [(BitDepth::Int16, 32_768.0), (BitDepth::Int24, 8_388_608.0)] {
+        let path = std::env::temp_dir().join(format!("oxidizer_{}.flac", bit_depth.name()));
+        io::save_audio_as(
+            &path,
+            samples.clone(),
+            44_100,
+            AudioFormat::Flac,
+            bit_depth,
+            |_, _| Ok(()),
+        )
+        .unwrap();
+
+        let decoded = io::load_audio(&path).unwrap();
+        assert_eq!(decoded.len(), samples.len());
+
+        let max = scale - 1.0;
+        for (original, decoded) in samples.iter().zip(&decoded) {
+            let expected = (original.clamp(-1.0, 1.0) * max) as i32;
+            assert_eq!((decoded * scale).round() as i32, expected);
+        }
+    }
+}
+
+// Writes `samples` as FLAC and checks that they decode to the same integers.
+fn assert_flac_lossless(samples: &[f32], bit_depth: BitDepth, name: &str) {
+    let scale = match bit_depth {
+        BitDepth::Int16 => 32_768.0,
+        _ => 8_388_608.0,
+    };
+    let path = std::env::temp_dir().join(format!("oxidizer_{}.flac", name));
+    io::save_audio_as(
+        &path,
+        samples.to_vec(),
+        44_100,
+        AudioFormat::Flac,
+        bit_depth,
+        |_, _| Ok(()),
+    )
+    .unwrap();
+
+    let decoded = io::load_audio(&path).unwrap();
+    assert_eq!(decoded.len(), samples.len(), "{}", name);
+    for (i, (original, decoded)) in samples.iter().zip(&decoded).enumerate() {
+        let expected = (original.clamp(-1.0, 1.0) * (scale - 1.0)) as i32;
+        assert_eq!(
+            (decoded * scale).round() as i32,
+            expected,
+            "{}: sample {}",
+            name,
+            i
+        );
+    }
+    let _ = std::fs::remove_file(path);
+}
+
+// `frames` of a quiet two-tone signal, different on each channel.
+fn flac_signal(frames: usize) -> Vec<f32> {
+    (0..frames)
+        .flat_map(|i| {
+            let t = i as f32 / 44_100.0;
+            let left = 0.3 * (std::f32::consts::TAU * 440.0 * t).sin();
+            let right = 0.2 * (std::f32::consts::TAU * 660.0 * t).sin();
+            [left, right]
+        })
+        .collect()
+}
+
+#[test]
+fn test_flac_keeps_a_partial_last_block() {
+    assert_flac_lossless(&flac_signal(4096 + 1000), BitDepth::Int16, "partial_block");
+}
+
+#[test]
+fn test_flac_keeps_blocks_shorter_than_the_predictor() {
+    // Too short for the highest fixed predictor order, alone and after a full block
+    assert_flac_lossless(&flac_signal(3), BitDepth::Int16, "tiny_stream");
+    assert_flac_lossless(&flac_signal(4096 + 2), BitDepth::Int24, "tiny_block");
+}
+
+#[test]
+fn test_flac_numbers_frames_past_127() {
+    // Frame numbers from 128 on take two bytes in the frame header
+    assert_flac_lossless(
+        &flac_signal(130 * 4096 + 17),
+        BitDepth::Int16,
+        "long_stream",
+    );
+}
+
+#[test]
+fn test_flac_codes_a_full_scale_side_channel() {
+    // Opposite full-scale channels: the mid channel is silent, so the pair goes out as
+    // mid/side with a side channel that needs 25 bits
+    let mut state = 1u32;
+    let samples: Vec<f32> = (0..5000)
+        .flat_map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let s = if state >> 31 == 1 { 1.0 } else { -1.0 };
+            [s, -s]
+        })
+        .collect();
+    assert_flac_lossless(&samples, BitDepth::Int24, "full_scale_side");
+}
+
+#[test]
+fn test_flac_rejects_float_samples() {
+    let path = std::env::temp_dir().join("oxidizer_float.flac");
+    let result = io::save_audio_as(
+        &path,
+        vec![0.0; 64],
+        44_100,
+        AudioFormat::Flac,
+        BitDepth::Float32,
+        |_, _| Ok(()),
+    );
+    assert!(matches!(
+        result,
+        Err(OxidizerError::UnsupportedFormat { .. })
+    ));
+}
+
+#[test]
+fn test_errors_tell_the_cause() {
+    let path = std::env::temp_dir().join("oxidizer_not_audio.txt");
+    std::fs::write(&path, "definitely not audio").unwrap();
+    match io::load_audio(&path) {
+        Err(OxidizerError::UnsupportedFormat { found, supported }) => {
+            assert_eq!(found, "oxidizer_not_audio.txt");
+            assert_eq!(supported, io::INPUT_FORMATS);
+        }
+        other => panic!(
+            "expected an unsupported format, got {:?}",
+            other.map(|s| s.len())
+        ),
+    }
+    let _ = std::fs::remove_file(path);
+
+    let mut pipeline = Pipeline::new(OxidizerConfig::default());
+    pipeline.set_range(TimeRange::new(Duration::from_secs(24 * 3600), None));
+    let output = std::env::temp_dir().join("oxidizer_empty.wav");
+    assert!(matches!(
+        pipeline.run(Path::new(INPUT), &output),
+        Err(OxidizerError::EmptyInput(path)) if path == Path::new(INPUT)
+    ));
+}
+
+#[test]
+fn test_append_crossfaded_overlaps_the_join() {
+    let mut program = vec![1.0; 20];
+    io::append_crossfaded(&mut program, &[0.5; 20], 4);
+    // 10 + 10 frames, 4 of them shared
+    assert_eq!(program.len(), 32);
+    assert_eq!(program[..12], [1.0; 12]);
+    assert_eq!(program[20..], [0.5; 12]);
+    // The outgoing side fades down while the incoming one comes up
+    assert!(program[12] > program[18]);
+
+    // Without a crossfade, the inputs are simply appended
+    let mut program = vec![1.0; 4];
+    io::append_crossfaded(&mut program, &[0.5; 4], 0);
+    assert_eq!(program, [1.0, 1.0, 1.0, 1.0, 0.5, 0.5, 0.5, 0.5]);
+}
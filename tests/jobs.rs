@@ -0,0 +1,33 @@
+use oxidizer::jobs::{self, JobConfig, JobStatus};
+use oxidizer::{OxidizerConfig, OxidizerError};
+
+#[test]
+fn test_job_reports_missing_input() {
+    let handle = jobs::spawn(JobConfig {
+        input: "does/not/exist.mp3".into(),
+        output: std::env::temp_dir().join("oxidizer_missing_input.wav"),
+        config: OxidizerConfig::default(),
+    });
+
+    while !handle.is_finished() {
+        std::thread::yield_now();
+    }
+
+    assert_eq!(handle.status(), JobStatus::Failed);
+    assert!(matches!(handle.wait(), Err(OxidizerError::Io(_))));
+}
+
+#[test]
+fn test_cancelled_job_leaves_no_output() {
+    let output = std::env::temp_dir().join("oxidizer_cancelled_job.wav");
+    let handle = jobs::spawn(JobConfig {
+        input: "assets/audio/original.mp3".into(),
+        output: output.clone(),
+        config: OxidizerConfig::default(),
+    });
+
+    handle.cancel();
+
+    assert!(matches!(handle.wait(), Err(OxidizerError::Cancelled)));
+    assert!(!output.exists());
+}
@@ -0,0 +1,180 @@
+use oxidizer::analysis::{from_dbfs, rms};
+use oxidizer::automation::AutomationTarget;
+use oxidizer::modulation::{
+    Drift, EnvelopeFollower, EnvelopeResponse, EnvelopeSource, Lfo, LfoShape,
+};
+use oxidizer::{NoiseConfig, Normalization, OxidizerConfig, Pipeline};
+
+#[test]
+fn test_lfo_shapes() {
+    let sine = Lfo {
+        shape: LfoShape::Sine,
+        rate_hz: 1.0,
+        depth: 1.0,
+    };
+    let mut oscillator = sine.start(0);
+    // Quarter-cycle steps: 0, peak, 0, trough
+    let values: Vec<f32> = (0..4).map(|_| oscillator.advance(0.25)).collect();
+    assert!((values[0]).abs() < 1e-6);
+    assert!((values[1] - 1.0).abs() < 1e-6);
+    assert!((values[3] + 1.0).abs() < 1e-6);
+
+    // One octave of depth doubles or halves the cutoff at the extremes
+    assert_eq!(sine.modulate(1000.0, 1.0), 2000.0);
+    assert_eq!(sine.modulate(1000.0, -1.0), 500.0);
+
+    let mut triangle = Lfo {
+        shape: LfoShape::Triangle,
+        ..sine
+    }
+    .start(0);
+    let values: Vec<f32> = (0..4).map(|_| triangle.advance(0.25)).collect();
+    assert_eq!(values, vec![-1.0, 0.0, 1.0, 0.0]);
+}
+
+#[test]
+fn test_random_walk_is_bounded_and_seeded() {
+    let walk = Lfo {
+        shape: LfoShape::RandomWalk,
+        rate_hz: 5.0,
+        depth: 1.0,
+    };
+    let render = |seed| -> Vec<f32> {
+        let mut oscillator = walk.start(seed);
+        (0..10_000).map(|_| oscillator.advance(0.01)).collect()
+    };
+
+    let first = render(3);
+    assert!(first.iter().all(|v| (-1.0..=1.0).contains(v)));
+    assert_eq!(first, render(3));
+    assert_ne!(first, render(4));
+}
+
+#[test]
+fn test_drift_is_bounded_smooth_and_seeded() {
+    let drift = Drift::new(AutomationTarget::Cutoff, 2000.0, 6000.0);
+    let values: Vec<f32> = (0..2000)
+        .map(|i| drift.value_at(i as f32 * 0.1, 7))
+        .collect();
+    assert!(values.iter().all(|v| (2000.0..=6000.0).contains(v)));
+    assert!(values.windows(2).all(|w| (w[1] - w[0]).abs() < 200.0));
+    // It actually wanders
+    let (low, high) = values
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    assert!(high - low > 1000.0);
+
+    let same: Vec<f32> = (0..2000)
+        .map(|i| drift.value_at(i as f32 * 0.1, 7))
+        .collect();
+    let other: Vec<f32> = (0..2000)
+        .map(|i| drift.value_at(i as f32 * 0.1, 8))
+        .collect();
+    assert_eq!(values, same);
+    assert_ne!(values, other);
+}
+
+#[test]
+fn test_envelope_follower_attack_and_release() {
+    let rate = 1000;
+    let mut follower = EnvelopeFollower::new(10.0, 100.0, rate);
+
+    // One time constant after a step, the envelope has covered ~63% of it
+    let attacked = (0..10).map(|_| follower.process(1.0)).last().unwrap();
+    assert!((attacked - 0.632).abs() < 0.01);
+
+    // The release is ten times slower
+    let released = (0..10).map(|_| follower.process(0.0)).last().unwrap();
+    assert!(released > attacked * 0.85);
+}
+
+#[test]
+fn test_envelope_response_gain() {
+    let response = EnvelopeResponse::new(AutomationTarget::Intensity, 0.5);
+    assert_eq!(response.gain(from_dbfs(-30.0)), 1.0);
+    // 20 dB below the threshold raises the parameter by 10 dB
+    assert!((response.gain(from_dbfs(-50.0)) - from_dbfs(10.0)).abs() < 1e-4);
+    assert!(response.gain(from_dbfs(-10.0)) < 1.0);
+    // Silence hits the bound instead of infinity
+    assert_eq!(response.gain(0.0), 16.0);
+}
+
+#[test]
+fn test_intensity_follows_input_level() {
+    let rate = 44100;
+    // A quiet second followed by a loud one
+    let input: Vec<f32> = (0..rate * 2)
+        .flat_map(|i| {
+            let s = if i < rate {
+                0.0
+            } else {
+                0.5 * (i as f32 * 0.03).sin()
+            };
+            [s, s]
+        })
+        .collect();
+    let render = |envelope: Vec<EnvelopeResponse>| {
+        let config = OxidizerConfig {
+            noise: NoiseConfig::White,
+            normalization: Normalization::None,
+            passes: 0,
+            seed: Some(5),
+            envelope,
+            ..Default::default()
+        };
+        Pipeline::new(config)
+            .process(input.clone(), rate as u32)
+            .unwrap()
+    };
+
+    let plain = render(Vec::new());
+    let following = render(vec![EnvelopeResponse::new(
+        AutomationTarget::Intensity,
+        0.5,
+    )]);
+    let quiet = 0..rate * 2;
+    assert!(rms(&following[quiet.clone()]) > 4.0 * rms(&plain[quiet]));
+}
+
+#[test]
+fn test_sidechain_ducks_noise() {
+    let rate = 44100;
+    let input = vec![0.0; rate * 4];
+    // A voiceover that speaks for one second, then pauses
+    let voice: Vec<f32> = (0..rate * 2)
+        .flat_map(|i| {
+            let s = if i < rate {
+                0.5 * (i as f32 * 0.03).sin()
+            } else {
+                0.0
+            };
+            [s, s]
+        })
+        .collect();
+
+    let config = OxidizerConfig {
+        noise: NoiseConfig::White,
+        normalization: Normalization::None,
+        passes: 0,
+        seed: Some(5),
+        envelope: vec![EnvelopeResponse {
+            source: EnvelopeSource::Sidechain,
+            release_ms: 50.0,
+            ..EnvelopeResponse::new(AutomationTarget::Intensity, 0.5)
+        }],
+        ..Default::default()
+    };
+    // Without a sidechain there's nothing to follow
+    assert!(
+        Pipeline::new(config.clone())
+            .process(input.clone(), rate as u32)
+            .is_err()
+    );
+
+    let ducked = Pipeline::new(config)
+        .set_sidechain(voice, rate as u32)
+        .process(input, rate as u32)
+        .unwrap();
+    let (speaking, pause) = ducked.split_at(rate * 2);
+    assert!(rms(pause) > 4.0 * rms(&speaking[rate / 2..]));
+}
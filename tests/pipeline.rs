@@ -0,0 +1,607 @@
+use oxidizer::analysis::{loudness, peak, rms};
+use oxidizer::dynamics::Compressor;
+use oxidizer::eq::EqBand;
+use oxidizer::io::{self, AudioFormat, BitDepth, TimeRange};
+use oxidizer::overlay::resample;
+use oxidizer::{
+    Branch, BranchInput, NoiseConfig, NoiseRouting, Normalization, OxidationLevel, OxidizerConfig,
+    Pipeline,
+};
+use std::path::Path;
+use std::time::Duration;
+
+#[test]
+fn test_seeded_renders_are_identical() {
+    let input = Path::new("assets/audio/original.mp3");
+    let config = OxidizerConfig {
+        seed: Some(7),
+        ..Default::default()
+    };
+
+    let mut pipeline = Pipeline::new(config);
+    pipeline.set_range(TimeRange::new(Duration::ZERO, Some(Duration::from_secs(1))));
+
+    let dir = std::env::temp_dir();
+    let first = dir.join("oxidizer_seeded_1.wav");
+    let second = dir.join("oxidizer_seeded_2.wav");
+    pipeline.run(input, &first).unwrap();
+    pipeline.run(input, &second).unwrap();
+
+    assert_eq!(
+        std::fs::read(&first).unwrap(),
+        std::fs::read(&second).unwrap()
+    );
+    let _ = std::fs::remove_file(first);
+    let _ = std::fs::remove_file(second);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_sidecar_reproduces_the_render() {
+    use oxidizer::pipeline::Sidecar;
+
+    let input = Path::new("assets/audio/original.mp3");
+    let config = OxidizerConfig {
+        seed: Some(u64::MAX - 7),
+        intensity: 0.2,
+        ..Default::default()
+    };
+    let range = TimeRange::new(Duration::ZERO, Some(Duration::from_millis(500)));
+
+    let dir = std::env::temp_dir();
+    let first = dir.join("oxidizer_sidecar_1.wav");
+    let report = Pipeline::new(config)
+        .set_range(range)
+        .run_with_report(input, &first)
+        .unwrap();
+    let sidecar = Sidecar::new(&report).unwrap();
+    assert_eq!(sidecar.seed, Some(u64::MAX - 7));
+    assert_eq!(sidecar.input_hashes, [io::file_hash(input).unwrap()]);
+    assert_eq!(
+        Sidecar::path_for(&first, "json"),
+        dir.join("oxidizer_sidecar_1.wav.json")
+    );
+
+    for extension in ["json", "toml"] {
+        let path = Sidecar::path_for(&first, extension);
+        sidecar.save(&path).unwrap();
+        assert_eq!(Sidecar::load(&path).unwrap(), sidecar);
+        let _ = std::fs::remove_file(path);
+    }
+
+    let second = dir.join("oxidizer_sidecar_2.wav");
+    Pipeline::new(sidecar.config)
+        .set_range(range)
+        .run(input, &second)
+        .unwrap();
+    assert_eq!(
+        std::fs::read(&first).unwrap(),
+        std::fs::read(&second).unwrap()
+    );
+    let _ = std::fs::remove_file(first);
+    let _ = std::fs::remove_file(second);
+}
+
+#[cfg(feature = "deterministic")]
+#[test]
+fn test_unseeded_renders_are_identical() {
+    let input: Vec<f32> = (0..8820).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+    let pipeline = Pipeline::new(OxidizerConfig::default());
+
+    let first = pipeline.process(input.clone(), 44100).unwrap();
+    let second = pipeline.process(input, 44100).unwrap();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_pre_filter_routing_muffles_the_noise() {
+    let render = |noise_routing| {
+        let config = OxidizerConfig {
+            level: OxidationLevel::Muffled,
+            noise: NoiseConfig::White,
+            noise_routing,
+            intensity: 0.5,
+            normalization: Normalization::None,
+            seed: Some(3),
+            ..Default::default()
+        };
+        Pipeline::new(config)
+            .process(vec![0.0; 44100], 44100)
+            .unwrap()
+    };
+    // Level of the sample-to-sample changes, relative to the whole signal
+    let brightness = |samples: Vec<f32>| {
+        let left: Vec<f32> = samples.iter().step_by(2).copied().collect();
+        let changes: Vec<f32> = left.windows(2).map(|w| w[1] - w[0]).collect();
+        rms(&changes) / rms(&left)
+    };
+
+    assert!(brightness(render(NoiseRouting::PostFilter)) > 1.0);
+    assert!(brightness(render(NoiseRouting::PreFilter)) < 0.2);
+}
+
+#[test]
+fn test_branches_are_summed_in_parallel() {
+    let input: Vec<f32> = (0..44100).map(|i| 0.3 * (i as f32 * 0.01).sin()).collect();
+    let dry = OxidizerConfig {
+        mix: 0.0,
+        normalization: Normalization::None,
+        seed: Some(2),
+        ..Default::default()
+    };
+
+    // A dry branch 6 dB down adds half the input again
+    let config = OxidizerConfig {
+        branches: vec![Branch::new(dry.clone(), -6.0206)],
+        ..dry.clone()
+    };
+    let output = Pipeline::new(config).process(input.clone(), 44100).unwrap();
+    assert!(
+        output
+            .iter()
+            .zip(&input)
+            .all(|(o, i)| (o - 1.5 * i).abs() < 1e-4)
+    );
+
+    // A noise-only branch leaves the program alone and adds its noise
+    let noise_bus = Branch {
+        input: BranchInput::Silence,
+        ..Branch::new(
+            OxidizerConfig {
+                noise: NoiseConfig::White,
+                intensity: 0.3,
+                ..Default::default()
+            },
+            0.0,
+        )
+    };
+    let config = OxidizerConfig {
+        branches: vec![noise_bus],
+        ..dry
+    };
+    let output = Pipeline::new(config).process(input.clone(), 44100).unwrap();
+    let added: Vec<f32> = output.iter().zip(&input).map(|(o, i)| o - i).collect();
+    assert!(rms(&added) > 0.005);
+    assert!(added.iter().all(|s| s.abs() < 0.2));
+}
+
+#[test]
+fn test_loop_runs_into_its_start() {
+    // A sine cut off mid-cycle: played in a loop as is, it jumps back to zero
+    let input: Vec<f32> = (0..22050)
+        .flat_map(|i| {
+            let s = (i as f32 * 0.0723).sin() * 0.8;
+            [s, s]
+        })
+        .collect();
+    let config = OxidizerConfig {
+        level: OxidationLevel::Clear,
+        intensity: 0.3,
+        loop_crossfade_ms: Some(50.0),
+        seed: Some(3),
+        ..Default::default()
+    };
+
+    let output = Pipeline::new(config).process(input.clone(), 44100).unwrap();
+    assert_eq!(output.len(), input.len());
+
+    let largest_step = output
+        .windows(3)
+        .step_by(2)
+        .map(|w| (w[2] - w[0]).abs())
+        .fold(0.0, f32::max);
+    let wrap_step = (output[0] - output[output.len() - 2]).abs();
+    assert!(
+        wrap_step <= largest_step,
+        "loop point jumps by {} (largest step {})",
+        wrap_step,
+        largest_step
+    );
+}
+
+#[test]
+fn test_noise_only_is_what_the_chain_adds() {
+    let input: Vec<f32> = (0..8820).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+    let config = OxidizerConfig {
+        intensity: 0.4,
+        normalization: Normalization::None,
+        seed: Some(5),
+        ..Default::default()
+    };
+
+    let full = Pipeline::new(config.clone())
+        .process(input.clone(), 44100)
+        .unwrap();
+    let noise = Pipeline::new(config.clone())
+        .set_noise_only(true)
+        .process(input.clone(), 44100)
+        .unwrap();
+    let clean = Pipeline::new(OxidizerConfig {
+        intensity: 0.0,
+        ..config
+    })
+    .process(input, 44100)
+    .unwrap();
+
+    assert!(rms(&noise) > 0.01);
+    for ((full, noise), clean) in full.iter().zip(&noise).zip(&clean) {
+        assert!((full - noise - clean).abs() < 1e-6);
+    }
+
+    // Over silence, everything is added, at the level normalization gives the full render
+    let config = OxidizerConfig {
+        seed: Some(5),
+        ..Default::default()
+    };
+    let full = Pipeline::new(config.clone())
+        .process(vec![0.0; 8820], 44100)
+        .unwrap();
+    let noise = Pipeline::new(config)
+        .set_noise_only(true)
+        .process(vec![0.0; 8820], 44100)
+        .unwrap();
+    for (full, noise) in full.iter().zip(&noise) {
+        assert!((full - noise).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_stage_export_writes_each_stage() {
+    let dir = std::env::temp_dir().join("oxidizer_stages");
+    let _ = std::fs::remove_dir_all(&dir);
+    let input: Vec<f32> = (0..8820).map(|i| (i as f32 * 0.05).sin() * 0.9).collect();
+    let config = OxidizerConfig {
+        intensity: 0.5,
+        normalization: Normalization::None,
+        seed: Some(3),
+        ..Default::default()
+    };
+
+    let output = Pipeline::new(config.clone())
+        .set_stage_export(&dir)
+        .process(input.clone(), 44100)
+        .unwrap();
+    let plain = Pipeline::new(config).process(input, 44100).unwrap();
+    assert_eq!(output, plain);
+
+    let load = |name: &str| io::load_audio(&dir.join(name)).unwrap();
+    let (filtered, noise, saturated) = (
+        load("filtered.wav"),
+        load("noise.wav"),
+        load("saturated.wav"),
+    );
+    assert_eq!(filtered.len(), output.len());
+    assert!(rms(&noise) > rms(&filtered));
+    for (noise, saturated) in noise.iter().zip(&saturated) {
+        assert!((noise.tanh() - saturated).abs() < 1e-6);
+    }
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn test_metering_follows_the_chain() {
+    let input: Vec<f32> = (0..8820).map(|i| (i as f32 * 0.05).sin() * 0.9).collect();
+    let config = OxidizerConfig {
+        intensity: 0.5,
+        eq: vec![EqBand::peak(1000.0, 12.0, 1.0)],
+        compressor: Some(Compressor::default()),
+        normalization: Normalization::Peak { ceiling: 0.5 },
+        seed: Some(3),
+        ..Default::default()
+    };
+
+    let mut pipeline = Pipeline::new(config.clone());
+    let output = pipeline
+        .set_metering(true)
+        .process(input.clone(), 44100)
+        .unwrap();
+    // Metering doesn't change the render
+    assert_eq!(output, Pipeline::new(config).process(input, 44100).unwrap());
+
+    let levels = pipeline.stage_levels();
+    let stages: Vec<&str> = levels.iter().map(|l| l.stage.as_str()).collect();
+    assert_eq!(
+        stages,
+        [
+            "input",
+            "filter",
+            "noise",
+            "saturation",
+            "eq",
+            "compressor",
+            "normalization"
+        ]
+    );
+    let level = |stage: &str| levels.iter().find(|l| l.stage == stage).unwrap();
+    assert!((level("input").peak_dbfs - 20.0 * 0.9f32.log10()).abs() < 0.01);
+    // The noise is measured before the saturation squashes the peaks
+    let peak_of = |stage| 10f32.powf(level(stage).peak_dbfs / 20.0);
+    assert!(level("noise").rms_dbfs > level("filter").rms_dbfs);
+    assert!((peak_of("noise").tanh() - peak_of("saturation")).abs() < 1e-5);
+    assert!((level("normalization").peak_dbfs - 20.0 * 0.5f32.log10()).abs() < 0.01);
+    assert!(level("normalization").rms_dbfs < level("normalization").peak_dbfs);
+
+    // A new render starts a new list
+    pipeline.process(vec![0.0; 200], 44100).unwrap();
+    assert_eq!(pipeline.stage_levels().len(), stages.len());
+    assert!(
+        Pipeline::new(OxidizerConfig::default())
+            .stage_levels()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_planar_matches_interleaved() {
+    let input: Vec<f32> = (0..8820).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+    let pipeline = Pipeline::new(OxidizerConfig {
+        seed: Some(8),
+        ..Default::default()
+    });
+    let expected = pipeline.process(input.clone(), 44100).unwrap();
+
+    let mut channels = vec![
+        input.iter().step_by(2).copied().collect::<Vec<_>>(),
+        input.iter().skip(1).step_by(2).copied().collect(),
+    ];
+    pipeline.process_planar(&mut channels, 44100).unwrap();
+    for (i, frame) in expected.chunks_exact(2).enumerate() {
+        assert_eq!([channels[0][i], channels[1][i]], frame);
+    }
+
+    channels[1].pop();
+    assert!(pipeline.process_planar(&mut channels, 44100).is_err());
+}
+
+#[test]
+fn test_matched_pair_has_equal_loudness() {
+    let sine = |hz: f32, level: f32| -> Vec<f32> {
+        (0..88200)
+            .flat_map(|i| {
+                let s = (std::f32::consts::TAU * hz * i as f32 / 44100.0).sin() * level;
+                [s, s]
+            })
+            .collect()
+    };
+
+    // Normalization makes the render much louder than a quiet input
+    let pipeline = Pipeline::new(OxidizerConfig {
+        seed: Some(2),
+        ..Default::default()
+    });
+    let pair = pipeline.process_matched(sine(440.0, 0.1), 44100).unwrap();
+    let (dry, wet) = (loudness(&pair.dry, 44100), loudness(&pair.wet, 44100));
+    assert!(
+        (dry - wet).abs() < 0.1,
+        "dry {} LUFS, wet {} LUFS",
+        dry,
+        wet
+    );
+    assert_eq!(pair.dry_gain_db, 0.0);
+    assert!(pair.wet_gain_db < -10.0);
+
+    // Muffling a loud, bright input: raising the render would clip, so both come down
+    let pipeline = Pipeline::new(OxidizerConfig {
+        level: OxidationLevel::Muffled,
+        normalization: Normalization::None,
+        seed: Some(2),
+        ..Default::default()
+    });
+    let pair = pipeline.process_matched(sine(4000.0, 0.9), 44100).unwrap();
+    let (dry, wet) = (loudness(&pair.dry, 44100), loudness(&pair.wet, 44100));
+    assert!(
+        (dry - wet).abs() < 0.1,
+        "dry {} LUFS, wet {} LUFS",
+        dry,
+        wet
+    );
+    assert!(pair.dry_gain_db < 0.0);
+    assert!(peak(&pair.dry).max(peak(&pair.wet)) <= 1.0 + 1e-6);
+}
+
+#[test]
+fn test_resample_keeps_duration() {
+    let ramp: Vec<f32> = (0..100).flat_map(|i| [i as f32, -(i as f32)]).collect();
+    let up = resample(&ramp, 100, 200);
+    assert_eq!(up.len(), 400);
+    // Halfway between the first two frames
+    assert_eq!(&up[2..4], &[0.5, -0.5]);
+    assert_eq!(resample(&ramp, 200, 100).len(), 100);
+}
+
+#[test]
+fn test_overlay_is_resampled_and_looped() {
+    let config = OxidizerConfig {
+        noise: NoiseConfig::White,
+        intensity: 0.0,
+        passes: 0,
+        normalization: Normalization::None,
+        ..Default::default()
+    };
+    // Half a second of a constant bed at half the render rate
+    let bed = vec![0.1; 22050];
+
+    let output = Pipeline::new(config.clone())
+        .overlay((bed.clone(), 22050), 6.0, true)
+        .process(vec![0.0; 88200], 44100)
+        .unwrap();
+    // +6 dB roughly doubles the bed, all the way through the second
+    assert!(output.iter().all(|s| (s - 0.1995).abs() < 1e-3));
+
+    let output = Pipeline::new(config)
+        .overlay((bed, 22050), 6.0, false)
+        .process(vec![0.0; 88200], 44100)
+        .unwrap();
+    assert!(output[..44100].iter().all(|s| (s - 0.1995).abs() < 1e-3));
+    assert!(output[44100..].iter().all(|&s| s == 0.0));
+}
+
+#[test]
+fn test_joined_inputs_form_one_program() {
+    let dir = std::env::temp_dir();
+    let first = dir.join("oxidizer_join_1.wav");
+    let second = dir.join("oxidizer_join_2.wav");
+    let output = dir.join("oxidizer_joined.wav");
+    // One second at 44.1 kHz and one at 22.05 kHz, which gets resampled
+    for (path, rate) in [(&first, 44100), (&second, 22050)] {
+        let samples = vec![0.25; rate as usize * 2];
+        io::save_audio_as(
+            path,
+            samples,
+            rate,
+            AudioFormat::Wav,
+            BitDepth::Int16,
+            |_, _| Ok(()),
+        )
+        .unwrap();
+    }
+
+    let config = OxidizerConfig {
+        seed: Some(3),
+        ..Default::default()
+    };
+    let report = Pipeline::new(config)
+        .set_crossfade(Duration::from_millis(500))
+        .run_joined_with_report(&[&first, &second], &output)
+        .unwrap();
+
+    assert_eq!(report.input, first);
+    assert_eq!(report.joined, vec![second.clone()]);
+    // Two seconds overlapping by half a second
+    assert!((report.input_duration_secs - 1.5).abs() < 1e-3);
+    assert_eq!(io::probe(&output).unwrap().frames, Some(66150));
+
+    for path in [first, second, output] {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[test]
+fn test_segment_gets_its_own_settings() {
+    let rate = 44100;
+    // Two seconds of a bright tone
+    let input: Vec<f32> = (0..rate * 2)
+        .flat_map(|i| {
+            let s = 0.5 * (i as f32 * 0.8).sin();
+            [s, s]
+        })
+        .collect();
+    let config = OxidizerConfig {
+        noise: NoiseConfig::White,
+        intensity: 0.0,
+        normalization: Normalization::None,
+        ..Default::default()
+    };
+
+    let plain = Pipeline::new(config.clone())
+        .process(input.clone(), rate as u32)
+        .unwrap();
+    let muffled_intro = Pipeline::new(config.clone())
+        .apply_in_range(
+            ..Duration::from_secs(1),
+            OxidizerConfig {
+                level: OxidationLevel::Muffled,
+                ..config
+            },
+        )
+        .process(input, rate as u32)
+        .unwrap();
+
+    // The intro loses most of the tone, the rest is untouched once the fade is over
+    let (intro, rest) = (0..rate * 2 - 4410, rate * 2 + 4410..rate * 4);
+    assert!(rms(&muffled_intro[intro.clone()]) < 0.5 * rms(&plain[intro]));
+    assert_eq!(muffled_intro[rest.clone()], plain[rest]);
+}
+
+#[test]
+fn test_album_tracks_continue_seamlessly() {
+    let dir = std::env::temp_dir();
+    let inputs = [
+        dir.join("oxidizer_album_1.wav"),
+        dir.join("oxidizer_album_2.wav"),
+    ];
+    let outputs = [
+        dir.join("oxidizer_album_1_out.wav"),
+        dir.join("oxidizer_album_2_out.wav"),
+    ];
+    let joined = dir.join("oxidizer_album_joined.wav");
+    // A loud track followed by a quiet one
+    for (path, level) in inputs.iter().zip([0.5, 0.1]) {
+        let samples = (0..44100)
+            .flat_map(|i| {
+                let s = level * (i as f32 * 0.05).sin();
+                [s, s]
+            })
+            .collect();
+        io::save_audio_as(
+            path,
+            samples,
+            44100,
+            AudioFormat::Wav,
+            BitDepth::Int16,
+            |_, _| Ok(()),
+        )
+        .unwrap();
+    }
+
+    let pipeline = Pipeline::new(OxidizerConfig {
+        seed: Some(11),
+        ..Default::default()
+    });
+    let reports = pipeline.run_album(&inputs, &outputs).unwrap();
+    assert_eq!(reports.len(), 2);
+    // One gain for the whole album keeps the quiet track quiet
+    assert!(reports[1].after.loudness_lufs < reports[0].after.loudness_lufs - 10.0);
+
+    // The tracks are the joined program cut in two
+    pipeline.run_joined_with_report(&inputs, &joined).unwrap();
+    let mut album = io::load_audio(&outputs[0]).unwrap();
+    album.extend(io::load_audio(&outputs[1]).unwrap());
+    assert_eq!(album, io::load_audio(&joined).unwrap());
+
+    for path in inputs.into_iter().chain(outputs).chain([joined]) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[test]
+fn test_smart_normalization_keeps_the_noise_level() {
+    // A quiet tone, then a second of silence where only the noise plays
+    let input: Vec<f32> = (0..88200)
+        .flat_map(|i| {
+            let s = match i < 44100 {
+                true => (std::f32::consts::TAU * 220.0 * i as f32 / 44100.0).sin() * 0.1,
+                false => 0.0,
+            };
+            [s, s]
+        })
+        .collect();
+    let render = |normalization| {
+        Pipeline::new(OxidizerConfig {
+            level: OxidationLevel::Clear,
+            noise: NoiseConfig::White,
+            intensity: 0.1,
+            normalization,
+            seed: Some(5),
+            ..Default::default()
+        })
+        .process(input.clone(), 44100)
+        .unwrap()
+    };
+    let noise_floor = |output: &[f32]| rms(&output[88200 + 8820..]);
+
+    let raw = render(Normalization::None);
+    let peaked = render(Normalization::Peak { ceiling: 0.9 });
+    let smart = render(Normalization::Smart { ceiling: 0.9 });
+
+    assert!((peak(&smart) - 0.9).abs() < 1e-4, "{}", peak(&smart));
+    // Scaling the output drags the noise floor up with the quiet tone, staging the input
+    // leaves it close to where the intensity put it
+    assert!(noise_floor(&peaked) > 2.0 * noise_floor(&raw));
+    let drift = noise_floor(&smart) / noise_floor(&raw);
+    assert!(
+        (0.9..1.1).contains(&drift),
+        "noise floor moved by {}",
+        drift
+    );
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "serde")]
+
+use oxidizer::io::TimeRange;
+use oxidizer::overlay::Overlay;
+use oxidizer::presets;
+use oxidizer::project::{Export, Project};
+use oxidizer::{OxidizerConfig, Pipeline};
+use std::path::Path;
+use std::time::Duration;
+
+#[test]
+fn test_project_settings_layer_over_the_preset() {
+    let dir = std::env::temp_dir().join("oxidizer_project_preset");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("session.oxproj");
+    std::fs::write(
+        &path,
+        "inputs = [\"takes/song.wav\"]\npreset = \"vinyl\"\n\n[settings]\nintensity = 0.3\n",
+    )
+    .unwrap();
+
+    let project = Project::load(&path).unwrap();
+    let vinyl = presets::find("vinyl").unwrap().config;
+    assert_eq!(
+        project.settings,
+        OxidizerConfig {
+            intensity: 0.3,
+            ..vinyl
+        }
+    );
+    // Relative to the project file
+    let dir = std::path::absolute(&dir).unwrap();
+    assert_eq!(project.inputs, [dir.join("takes/song.wav")]);
+    assert_eq!(project.export.output, dir.join("output.wav"));
+
+    // Saved settings stay as they were, preset or not
+    project.save(&path).unwrap();
+    assert_eq!(Project::load(&path).unwrap(), project);
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn test_project_renders_like_the_pipeline() {
+    let input = std::path::absolute("assets/audio/original.mp3").unwrap();
+    let dir = std::path::absolute(std::env::temp_dir()).unwrap();
+    let config = OxidizerConfig {
+        seed: Some(11),
+        intensity: 0.2,
+        ..Default::default()
+    };
+    let project = Project {
+        inputs: vec![input.clone()],
+        settings: config.clone(),
+        export: Export {
+            output: dir.join("oxidizer_project.wav"),
+            start_secs: 0.25,
+            duration_secs: Some(0.5),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let path = dir.join("oxidizer_project.oxproj");
+    project.save(&path).unwrap();
+    let loaded = Project::load(&path).unwrap();
+    assert_eq!(loaded, project);
+    loaded.render().unwrap();
+
+    let direct = dir.join("oxidizer_project_direct.wav");
+    Pipeline::new(config)
+        .set_range(TimeRange::new(
+            Duration::from_millis(250),
+            Some(Duration::from_millis(500)),
+        ))
+        .run(Path::new(&input), &direct)
+        .unwrap();
+    assert_eq!(
+        std::fs::read(&project.export.output).unwrap(),
+        std::fs::read(&direct).unwrap()
+    );
+    for file in [path, project.export.output, direct] {
+        let _ = std::fs::remove_file(file);
+    }
+}
+
+#[test]
+fn test_project_overlays_move_with_the_project() {
+    // The test runs from the crate root, not from the project's directory
+    let dir = std::path::absolute(std::env::temp_dir().join("oxidizer_project_overlay")).unwrap();
+    std::fs::create_dir_all(&dir).unwrap();
+    for name in ["song.mp3", "rain.mp3"] {
+        std::fs::copy("assets/audio/original.mp3", dir.join(name)).unwrap();
+    }
+    let path = dir.join("session.oxproj");
+    std::fs::write(
+        &path,
+        "inputs = [\"song.mp3\"]\n\n[settings]\nseed = 3\n\n[[settings.overlay]]\n\
+         source = \"rain.mp3\"\ngain_db = -18.0\n\n[export]\noutput = \"out.wav\"\n\
+         duration_secs = 0.5\n",
+    )
+    .unwrap();
+
+    let project = Project::load(&path).unwrap();
+    project.render().unwrap();
+
+    let config = OxidizerConfig {
+        seed: Some(3),
+        overlay: vec![Overlay::new(dir.join("rain.mp3"), -18.0, true)],
+        ..Default::default()
+    };
+    assert_eq!(project.settings, config);
+    let direct = dir.join("direct.wav");
+    Pipeline::new(config)
+        .set_range(TimeRange::new(
+            Duration::ZERO,
+            Some(Duration::from_millis(500)),
+        ))
+        .run(&dir.join("song.mp3"), &direct)
+        .unwrap();
+    assert_eq!(
+        std::fs::read(dir.join("out.wav")).unwrap(),
+        std::fs::read(&direct).unwrap()
+    );
+
+    // Saved relative to the project again
+    project.save(&path).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains("source = \"rain.mp3\""), "{}", text);
+    assert_eq!(Project::load(&path).unwrap(), project);
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,164 @@
+use oxidizer::dynamics::NoiseDucking;
+use oxidizer::processor::{IntensityCurve, Scratch};
+use oxidizer::{
+    ChannelConfig, NoiseConfig, NoiseRouting, Normalization, OxidationLevel, OxidizerConfig,
+    OxidizerError, Pipeline, render_into, render_planar,
+};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// Counts the allocations made on a thread while it's counting.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get().map(|n| n + 1)));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn count_allocations(run: impl FnOnce()) -> usize {
+    ALLOCATIONS.with(|count| count.set(Some(0)));
+    run();
+    ALLOCATIONS.with(|count| count.replace(None)).unwrap()
+}
+
+#[test]
+fn test_render_into_matches_pipeline_without_allocating() {
+    let input: Vec<f32> = (0..20000).map(|i| (i as f32 * 0.03).sin() * 0.6).collect();
+    let configs = [
+        OxidizerConfig {
+            seed: Some(4),
+            ..Default::default()
+        },
+        OxidizerConfig {
+            level: OxidationLevel::Amount(0.7),
+            passes: 4,
+            noise: NoiseConfig::White,
+            noise_routing: NoiseRouting::PreFilter,
+            intensity: 0.3,
+            mix: 0.6,
+            riaa: true,
+            seed: Some(9),
+            ..Default::default()
+        },
+        OxidizerConfig {
+            level: OxidationLevel::Amount(0.4),
+            passes: 2,
+            noise: NoiseConfig::Crackle { density: 0.001 },
+            intensity: 0.5,
+            intensity_curve: IntensityCurve::Log { base: 10.0 },
+            noise_ducking: Some(NoiseDucking::default()),
+            channels: Some([
+                ChannelConfig {
+                    cutoff_octaves: -0.5,
+                    noise_db: 3.0,
+                },
+                ChannelConfig::default(),
+            ]),
+            mix: 0.8,
+            normalization: Normalization::None,
+            seed: Some(21),
+            ..Default::default()
+        },
+    ];
+
+    let mut scratch = Scratch::new(input.len() / 2);
+    let mut output = vec![0.0; input.len()];
+    for config in configs {
+        let allocations = count_allocations(|| {
+            render_into(&input, &mut output, &mut scratch, &config).unwrap();
+        });
+        assert_eq!(allocations, 0);
+
+        let expected = Pipeline::new(config.clone())
+            .process(input.clone(), config.sample_rate)
+            .unwrap();
+        assert_eq!(output, expected);
+    }
+    assert_eq!(scratch.capacity(), input.len() / 2);
+}
+
+#[test]
+fn test_render_into_rejects_what_needs_memory() {
+    let input = vec![0.0; 200];
+    let mut scratch = Scratch::default();
+
+    let result = render_into(&input, &mut [0.0; 100], &mut scratch, &Default::default());
+    assert!(matches!(result, Err(OxidizerError::InvalidValue(_))));
+
+    let config = OxidizerConfig {
+        loop_crossfade_ms: Some(50.0),
+        ..Default::default()
+    };
+    let result = render_into(&input, &mut [0.0; 200], &mut scratch, &config);
+    assert!(matches!(result, Err(OxidizerError::InvalidValue(e)) if e.contains("a loop")));
+
+    // A scratch without room grows once
+    let config = OxidizerConfig::default();
+    render_into(&input, &mut [0.0; 200], &mut scratch, &config).unwrap();
+    assert_eq!(scratch.capacity(), 100);
+}
+
+#[test]
+fn test_render_planar_matches_interleaved() {
+    let input: Vec<f32> = (0..20000).map(|i| (i as f32 * 0.02).sin() * 0.5).collect();
+    let config = OxidizerConfig {
+        mix: 0.7,
+        seed: Some(2),
+        ..Default::default()
+    };
+    let mut scratch = Scratch::new(input.len() / 2);
+    let mut interleaved = vec![0.0; input.len()];
+    render_into(&input, &mut interleaved, &mut scratch, &config).unwrap();
+
+    let mut left: Vec<f32> = input.iter().step_by(2).copied().collect();
+    let mut right: Vec<f32> = input.iter().skip(1).step_by(2).copied().collect();
+    let allocations = count_allocations(|| {
+        let mut channels = [&mut left[..], &mut right[..]];
+        render_planar(&mut channels, &mut scratch, &config).unwrap();
+    });
+    assert_eq!(allocations, 0);
+    for (i, frame) in interleaved.chunks_exact(2).enumerate() {
+        assert_eq!([left[i], right[i]], frame);
+    }
+
+    // One channel runs through both sides and gets their average
+    let mono: Vec<f32> = input.iter().step_by(2).copied().collect();
+    let mut doubled = vec![0.0; input.len()];
+    let both: Vec<f32> = mono.iter().flat_map(|&s| [s, s]).collect();
+    render_into(&both, &mut doubled, &mut scratch, &config).unwrap();
+    let mut channels = vec![mono];
+    render_planar(&mut channels, &mut scratch, &config).unwrap();
+    for (sample, frame) in channels[0].iter().zip(doubled.chunks_exact(2)) {
+        assert_eq!(*sample, (frame[0] + frame[1]) / 2.0);
+    }
+
+    let mut three = vec![vec![0.0; 10]; 3];
+    assert!(matches!(
+        render_planar(&mut three, &mut scratch, &config),
+        Err(OxidizerError::ChannelMismatch {
+            expected: 2,
+            found: 3
+        })
+    ));
+    let mut uneven = vec![vec![0.0; 10], vec![0.0; 8]];
+    assert!(matches!(
+        render_planar(&mut uneven, &mut scratch, &config),
+        Err(OxidizerError::LengthMismatch {
+            expected: 10,
+            found: 8
+        })
+    ));
+}
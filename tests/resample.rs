@@ -0,0 +1,58 @@
+use oxidizer::analysis::rms;
+use oxidizer::overlay::resample;
+use oxidizer::resample::{Linear, ResampleQuality, Resampler, SincKaiser};
+
+// A stereo sine of `hz` at peak 0.5, `frames` long at `rate`.
+fn tone(hz: f32, frames: usize, rate: u32) -> Vec<f32> {
+    (0..frames)
+        .flat_map(|i| {
+            let s = 0.5 * (std::f32::consts::TAU * hz * i as f32 / rate as f32).sin();
+            [s, s]
+        })
+        .collect()
+}
+
+// RMS of the middle half, away from the held edges.
+fn middle_rms(samples: &[f32]) -> f32 {
+    let quarter = samples.len() / 8 * 2;
+    rms(&samples[quarter..samples.len() - quarter])
+}
+
+#[test]
+fn test_linear_matches_the_overlay_resampler() {
+    let input = tone(1000.0, 4410, 44100);
+    assert_eq!(
+        Linear.resample(&input, 44100, 48000),
+        resample(&input, 44100, 48000)
+    );
+}
+
+#[test]
+fn test_every_quality_keeps_duration_and_level() {
+    let input = tone(1000.0, 44100, 44100);
+    for quality in ResampleQuality::ALL {
+        let output = quality.resampler().resample(&input, 44100, 48000);
+        assert_eq!(output.len(), 96000, "{}", quality.name());
+        let level = middle_rms(&output) / middle_rms(&input);
+        assert!((level - 1.0).abs() < 0.01, "{}: {}", quality.name(), level);
+    }
+}
+
+#[test]
+fn test_sinc_filters_out_what_linear_aliases() {
+    // 15 kHz is above the Nyquist frequency of 22.05 kHz; linear interpolation folds it
+    // down to 7.05 kHz, the sinc removes it
+    let input = tone(15000.0, 48000, 48000);
+    let linear = Linear.resample(&input, 48000, 22050);
+    let sinc = SincKaiser::default().resample(&input, 48000, 22050);
+    assert!(middle_rms(&linear) > 0.1, "{}", middle_rms(&linear));
+    assert!(middle_rms(&sinc) < 0.001, "{}", middle_rms(&sinc));
+}
+
+#[test]
+fn test_quality_names_round_trip() {
+    for quality in ResampleQuality::ALL {
+        assert_eq!(ResampleQuality::try_from_str(quality.name()), Ok(quality));
+    }
+    assert!(ResampleQuality::try_from_str("perfect").is_err());
+}
@@ -0,0 +1,112 @@
+use oxidizer::analysis::rms;
+use oxidizer::speed::{Chorus, Flutter, RecordSpeed, Wobble};
+
+// A 1 kHz stereo sine, one second at 44.1 kHz.
+fn tone() -> Vec<f32> {
+    tone_for(44100)
+}
+
+// A 1 kHz stereo sine of `frames` frames at 44.1 kHz.
+fn tone_for(frames: usize) -> Vec<f32> {
+    (0..frames)
+        .flat_map(|i| {
+            let s = 0.5 * (std::f32::consts::TAU * 1000.0 * i as f32 / 44100.0).sin();
+            [s, s]
+        })
+        .collect()
+}
+
+// Zero crossings per second of one channel, counted over blocks of `frames`.
+fn crossings(samples: &[f32], frames: usize) -> Vec<usize> {
+    let left: Vec<f32> = samples.iter().step_by(2).copied().collect();
+    left.chunks_exact(frames)
+        .map(|block| {
+            let count = block
+                .windows(2)
+                .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+                .count();
+            count * 44100 / frames
+        })
+        .collect()
+}
+
+#[test]
+fn test_flutter_wavers_the_pitch() {
+    let flutter = Flutter {
+        rate_hz: 4.0,
+        depth_percent: 1.0,
+        ..Default::default()
+    };
+    let mut samples = tone();
+    flutter.process(&mut samples, 44100, 3);
+
+    // The level stays put while the pitch swings around 1 kHz
+    assert!((rms(&samples[4410..]) - rms(&tone()[4410..])).abs() < 0.01);
+    let rates = crossings(&samples[4410..], 2205);
+    let (low, high) = (rates.iter().min().unwrap(), rates.iter().max().unwrap());
+    assert!(*low < 1990 && *high > 2010, "{}..{}", low, high);
+    assert!(*low > 1940 && *high < 2060, "{}..{}", low, high);
+
+    // Without depth nothing moves
+    let mut steady = tone();
+    Flutter {
+        depth_percent: 0.0,
+        ..flutter
+    }
+    .process(&mut steady, 44100, 3);
+    assert_eq!(steady, tone());
+}
+
+#[test]
+fn test_chorus_mixes_in_a_late_copy() {
+    let chorus = Chorus::default();
+    let mut impulse = vec![0.0; 4410 * 2];
+    impulse[0] = 1.0;
+    impulse[1] = 1.0;
+    chorus.process(&mut impulse, 44100);
+
+    // The original, turned down by the mix, then its copy around the chorus delay
+    assert!((impulse[0] - 0.7).abs() < 1e-6);
+    let late: Vec<usize> = (1..4410).filter(|&i| impulse[i * 2].abs() > 0.01).collect();
+    assert!(!late.is_empty());
+    let (first, last) = (late[0], late[late.len() - 1]);
+    assert!(first >= 529 && last <= 794, "{}..{} frames", first, last);
+    assert_eq!(impulse[1], impulse[0]);
+
+    let mut dry = tone();
+    Chorus { mix: 0.0, ..chorus }.process(&mut dry, 44100);
+    assert_eq!(dry, tone());
+}
+
+#[test]
+fn test_wobble_repeats_every_revolution() {
+    let wobble = Wobble {
+        speed: RecordSpeed::Single,
+        depth_percent: 2.0,
+    };
+    // Two and a half turns at 45 rpm, each 58800 frames long
+    let mut samples = tone_for(110250);
+    wobble.process(&mut samples, 44100);
+
+    // Twenty blocks per turn: the pitch swings, and the swing comes back the same
+    let rates = crossings(&samples, 2940);
+    let (low, high) = (rates.iter().min().unwrap(), rates.iter().max().unwrap());
+    assert!(*low < 1975 && *high > 2025, "{}..{}", low, high);
+    for k in 1..rates.len() - 20 {
+        let drift = rates[k].abs_diff(rates[k + 20]);
+        assert!(
+            drift <= 30,
+            "block {}: {} then {}",
+            k,
+            rates[k],
+            rates[k + 20]
+        );
+    }
+
+    // No seed, no randomness: every render wobbles alike
+    let mut again = tone_for(110250);
+    wobble.process(&mut again, 44100);
+    assert_eq!(again, samples);
+    assert_eq!(RecordSpeed::try_from_str("78"), Ok(RecordSpeed::Shellac));
+    assert!(RecordSpeed::try_from_str("16").is_err());
+}
@@ -0,0 +1,62 @@
+use oxidizer::analysis::rms;
+use oxidizer::stereo::{Crossfeed, Haas};
+
+// A 100 Hz tone, one second at 44.1 kHz, at the given level in each channel.
+fn tone(left: f32, right: f32) -> Vec<f32> {
+    (0..44100)
+        .flat_map(|i| {
+            let s = (std::f32::consts::TAU * 100.0 * i as f32 / 44100.0).sin();
+            [s * left, s * right]
+        })
+        .collect()
+}
+
+// RMS of one channel of interleaved stereo samples, past the first 0.1 s.
+fn channel_rms(samples: &[f32], channel: usize) -> f32 {
+    let channel: Vec<f32> = samples[8820..]
+        .iter()
+        .skip(channel)
+        .step_by(2)
+        .copied()
+        .collect();
+    rms(&channel)
+}
+
+#[test]
+fn test_crossfeed_bleeds_into_the_other_channel() {
+    let mut panned = tone(0.5, 0.0);
+    Crossfeed::default().process(&mut panned, 44100);
+
+    // The bass of a hard-left tone reaches the right ear about 6 dB down
+    let ratio = channel_rms(&panned, 1) / channel_rms(&panned, 0);
+    assert!((20.0 * ratio.log10() + 6.0).abs() < 1.0, "ratio {}", ratio);
+
+    // Centered bass keeps its level
+    let mut centered = tone(0.5, 0.5);
+    Crossfeed::default().process(&mut centered, 44100);
+    assert!((channel_rms(&centered, 0) / channel_rms(&tone(0.5, 0.5), 0) - 1.0).abs() < 0.02);
+}
+
+#[test]
+fn test_haas_delays_one_channel() {
+    // A single click in both channels
+    let mut samples = vec![0.0f32; 4410 * 2];
+    samples[0] = 1.0;
+    samples[1] = 1.0;
+    let haas = Haas {
+        delay_ms: 10.0,
+        ..Default::default()
+    };
+    haas.process(&mut samples, 44100);
+
+    // The left channel is untouched, the right one hears the click 441 frames later
+    assert_eq!(samples[0], 1.0);
+    assert!(samples.iter().skip(2).step_by(2).all(|&s| s == 0.0));
+    let right: Vec<f32> = samples.iter().skip(1).step_by(2).copied().collect();
+    let loudest = (0..right.len())
+        .max_by(|&a, &b| right[a].total_cmp(&right[b]))
+        .unwrap();
+    assert_eq!(loudest, 441);
+    // And dulled: the click is smeared by the damping
+    assert!(right[441] < 1.0 && right[442] > 0.0);
+}
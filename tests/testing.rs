@@ -0,0 +1,88 @@
+#![cfg(feature = "testing")]
+
+use oxidizer::analysis::{peak, rms};
+use oxidizer::testing;
+
+// Zero crossings of the left channel within `samples`.
+fn crossings(samples: &[f32]) -> usize {
+    let left: Vec<f32> = samples.iter().step_by(2).copied().collect();
+    left.windows(2)
+        .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+        .count()
+}
+
+#[test]
+fn test_signals_have_the_asked_shape() {
+    let sine = testing::sine(1000.0, 0.5, 1.0, 44100);
+    assert_eq!(sine.len(), 88200);
+    assert!((peak(&sine) - 0.5).abs() < 1e-3);
+    assert!(crossings(&sine).abs_diff(2000) <= 1);
+
+    // 20 Hz to 20 kHz in a second: slow at the start, fast at the end
+    let sweep = testing::sweep(20.0, 20000.0, 0.5, 1.0, 44100);
+    let (start, end) = (&sweep[..8820], &sweep[sweep.len() - 8820..]);
+    assert!(crossings(start) < crossings(end) / 100);
+    assert!(peak(&sweep) <= 0.5 + 1e-6);
+
+    let impulse = testing::impulse(100);
+    assert_eq!(&impulse[..2], &[1.0, 1.0]);
+    assert_eq!(rms(&impulse[2..]), 0.0);
+}
+
+#[test]
+fn test_pink_bursts_repeat_with_the_seed() {
+    let bursts = testing::pink_bursts(3, 0.5, 0.25, 0.1, 44100, 9);
+    assert_eq!(bursts.len(), 3 * 33075 * 2);
+    for burst in bursts.chunks(33075 * 2) {
+        let (noise, gap) = burst.split_at(22050 * 2);
+        assert!((rms(noise) - 0.1).abs() < 1e-4);
+        assert_eq!(rms(gap), 0.0);
+    }
+    assert_eq!(bursts, testing::pink_bursts(3, 0.5, 0.25, 0.1, 44100, 9));
+    assert_ne!(bursts, testing::pink_bursts(3, 0.5, 0.25, 0.1, 44100, 10));
+
+    // Another seed draws other noise of the same color
+    let (one, other) = (
+        testing::pink_bursts(1, 4.0, 0.0, 0.1, 44100, 9),
+        testing::pink_bursts(1, 4.0, 0.0, 0.1, 44100, 10),
+    );
+    let difference = testing::spectral_difference_db(&other, &one, 44100);
+    assert!(difference < 2.0, "{} dB apart", difference);
+}
+
+#[test]
+fn test_comparisons_measure_the_difference() {
+    let golden = testing::pink_bursts(1, 1.0, 0.0, 0.2, 44100, 1);
+    assert_eq!(
+        testing::rms_difference_db(&golden, &golden),
+        f32::NEG_INFINITY
+    );
+    assert_eq!(
+        testing::spectral_difference_db(&golden, &golden, 44100),
+        0.0
+    );
+
+    // Half the level: the difference is half the signal, and every band is 6 dB down
+    let half: Vec<f32> = golden.iter().map(|s| s * 0.5).collect();
+    assert!((testing::rms_difference_db(&half, &golden) + 6.02).abs() < 0.01);
+    assert!((testing::spectral_difference_db(&half, &golden, 44100) - 6.02).abs() < 0.01);
+    testing::assert_rms_close(&half, &golden, -6.0);
+    testing::assert_spectrum_close(&half, &golden, 44100, 6.1);
+
+    // A lost octave band shows up in the spectrum
+    let dull = testing::sine(100.0, 0.5, 1.0, 44100);
+    let bright: Vec<f32> = dull
+        .iter()
+        .zip(testing::sine(5000.0, 0.5, 1.0, 44100))
+        .map(|(a, b)| a + b)
+        .collect();
+    assert!(testing::spectral_difference_db(&dull, &bright, 44100) > 60.0);
+}
+
+#[test]
+#[should_panic(expected = "differs from the golden output")]
+fn test_assertions_fail_beyond_the_tolerance() {
+    let golden = testing::sine(440.0, 0.5, 0.5, 44100);
+    let louder: Vec<f32> = golden.iter().map(|s| s * 1.1).collect();
+    testing::assert_rms_close(&louder, &golden, -40.0);
+}